@@ -1,11 +1,18 @@
 use {
+    borsh::BorshDeserialize,
     clap::{
         crate_description, crate_name, crate_version, value_t_or_exit, App, AppSettings, Arg,
-        SubCommand,
+        ArgMatches, SubCommand,
     },
     nobilitydao::{
-        state::{HouseData, TitleData, MAX_KIND, MAX_RANK, MAX_VASSALS, MIN_KIND, MIN_RANK},
-        utils::try_from_slice_checked,
+        error::TitleError,
+        instruction::{TitleInstruction, TitleParams, VassalSlot},
+        state::{
+            unpack_string, validate_coat_of_arms, validate_display_name, validate_realm_name,
+            ElectionData, HoldingsData, HouseData, Kind, ProposalData, Rank, RealmConfigData,
+            RealmData, SuccessionLaw, TitleData, VassalPageData,
+        },
+        utils::try_from_account_data,
     },
     solana_clap_utils::{
         input_parsers::{keypair_of, pubkey_of},
@@ -16,16 +23,22 @@ use {
     solana_client::rpc_client::RpcClient,
     solana_sdk::{
         commitment_config::CommitmentConfig,
+        decode_error::DecodeError,
+        instruction::{Instruction, InstructionError},
         native_token::lamports_to_sol,
         pubkey::Pubkey,
         signature::{read_keypair_file, Keypair, Signer},
-        transaction::Transaction,
+        transaction::{Transaction, TransactionError},
     },
     std::{
         fmt::Display,
     },
 };
 
+/// Titles moved to the heir per TriggerSuccession transaction, well within the
+/// transaction size limit.
+const SUCCESSION_BATCH_SIZE: usize = 20;
+
 struct Config {
     keypair: Keypair,
     json_rpc_url: String,
@@ -36,9 +49,8 @@ pub fn is_short<T>(string: T) -> Result<(), String>
 where
     T: AsRef<str> + Display,
 {
-    if string.as_ref().len() >= 128 {
-        return Err(format!("too long: {}", string));
-    }
+    validate_display_name(string.as_ref().as_bytes())
+        .map_err(|err| format!("{}: {}", err, string))?;
     Ok(())
 }
 
@@ -61,7 +73,17 @@ where
             return Err(format!("{}", err));
         }
     }
-    is_short(string)?;
+    validate_coat_of_arms(string.as_ref().as_bytes())
+        .map_err(|err| format!("{}: {}", err, string))?;
+    Ok(())
+}
+
+pub fn is_realm_name<T>(string: T) -> Result<(), String>
+where
+    T: AsRef<str> + Display,
+{
+    validate_realm_name(string.as_ref().as_bytes())
+        .map_err(|err| format!("{}: {}", err, string))?;
     Ok(())
 }
 
@@ -93,6 +115,24 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
                 .global(true)
                 .help("Filepath or URL to a keypair [default: client keypair]"),
         )
+        .arg(
+            Arg::with_name("realm")
+                .long("realm")
+                .value_name("REALM_NAME")
+                .validator(is_realm_name)
+                .takes_value(true)
+                .global(true)
+                .help("Name of the realm, for commands on a realm's root title"),
+        )
+        .arg(
+            Arg::with_name("house")
+                .long("house")
+                .value_name("HOUSE_ADDRESS")
+                .validator(is_valid_pubkey)
+                .takes_value(true)
+                .global(true)
+                .help("Address of the user's house, for houses whose authority was transferred [default: the house derived from the user's wallet]"),
+        )
         .arg(
             Arg::with_name("verbose")
                 .long("verbose")
@@ -120,6 +160,12 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
                         .validator(is_valid_pubkey)
                         .index(1)
                         .help("The address of the wallet whose house should be shown"),
+                )
+                .arg(
+                    Arg::with_name("titles")
+                        .long("titles")
+                        .takes_value(false)
+                        .help("List the titles the house holds"),
                 ),
         )
         .subcommand(
@@ -149,11 +195,215 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
                         .takes_value(true)
                         .validator(is_short)
                         .help("Display name for the house"),
+                )
+                .arg(
+                    Arg::with_name("governance_token_supply")
+                        .long("governance-token-supply")
+                        .value_name("SUPPLY")
+                        .takes_value(true)
+                        .default_value("1")
+                        .validator(|s| is_within_range(s, 1, u16::MAX as usize))
+                        .help("Number of tokens governing the house. Above 1, the house is governed by proposals to the token holders, and the tokens are minted to a new token account of the user"),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("update-house")
+                .about("Update the coat of arms of the given user wallet's house")
+                .arg(
+                    Arg::with_name("user_address")
+                        .value_name("USER_ADDRESS")
+                        .alias("keypair")
+                        .validator(is_valid_pubkey)
+                        .index(1)
+                        .help("The address of the wallet whose house should be updated"),
+                )
+                .arg(
+                    Arg::with_name("coat_of_arms")
+                        .long("coat-of-arms")
+                        .value_name("COAT_OF_ARMS")
+                        .takes_value(true)
+                        .required(true)
+                        .validator(is_short_url)
+                        .help("New coat of arms of the house"),
+                )
+                .arg(
+                    Arg::with_name("propose")
+                        .long("propose")
+                        .takes_value(false)
+                        .help("Propose the update to the token holders of the house instead"),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("close-house")
+                .about("Close the given user wallet's house, which must hold no titles")
+                .arg(
+                    Arg::with_name("user_address")
+                        .value_name("USER_ADDRESS")
+                        .alias("keypair")
+                        .validator(is_valid_pubkey)
+                        .index(1)
+                        .help("The address of the wallet whose house should be closed"),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("transfer-house-authority")
+                .about("Hand the government of the user wallet's house to another wallet")
+                .arg(
+                    Arg::with_name("new_authority")
+                        .value_name("NEW_AUTHORITY")
+                        .validator(is_valid_pubkey)
+                        .index(1)
+                        .required(true)
+                        .help("The address of the wallet to govern the house"),
+                )
+                .arg(
+                    Arg::with_name("user_address")
+                        .long("user-address")
+                        .value_name("USER_ADDRESS")
+                        .alias("keypair")
+                        .validator(is_valid_pubkey)
+                        .help("The address of the wallet governing the house"),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("set-heir")
+                .about("Name the house inheriting the user wallet's titles once it stops proving life")
+                .arg(
+                    Arg::with_name("heir_house_address")
+                        .value_name("HEIR_HOUSE_ADDRESS")
+                        .validator(is_valid_pubkey)
+                        .index(1)
+                        .required(true)
+                        .help("The address of the heir house - 11111111111111111111111111111111 for no heir"),
+                )
+                .arg(
+                    Arg::with_name("succession_period_slots")
+                        .long("succession-period-slots")
+                        .value_name("SLOTS")
+                        .takes_value(true)
+                        .required(true)
+                        .help("Slots the house may go without proving life before the heir inherits"),
+                )
+                .arg(
+                    Arg::with_name("user_address")
+                        .long("user-address")
+                        .value_name("USER_ADDRESS")
+                        .alias("keypair")
+                        .validator(is_valid_pubkey)
+                        .help("The address of the wallet governing the house"),
+                )
+                .arg(
+                    Arg::with_name("propose")
+                        .long("propose")
+                        .takes_value(false)
+                        .help("Propose the heir to the token holders of the house instead"),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("prove-life")
+                .about("Restart the succession period of the user wallet's house")
+                .arg(
+                    Arg::with_name("user_address")
+                        .long("user-address")
+                        .value_name("USER_ADDRESS")
+                        .alias("keypair")
+                        .validator(is_valid_pubkey)
+                        .help("The address of the wallet governing the house"),
+                )
+                .arg(
+                    Arg::with_name("propose")
+                        .long("propose")
+                        .takes_value(false)
+                        .help("Propose the proof of life to the token holders of the house instead"),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("trigger-succession")
+                .about("Move the titles of a house past its succession period to its heir")
+                .arg(
+                    Arg::with_name("house_address")
+                        .value_name("HOUSE_ADDRESS")
+                        .validator(is_valid_pubkey)
+                        .index(1)
+                        .required(true)
+                        .help("The address of the house whose titles should pass to its heir"),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("withdraw-from-house")
+                .about("Withdraw lamports the given user wallet's house collected")
+                .arg(
+                    Arg::with_name("lamports")
+                        .value_name("LAMPORTS")
+                        .index(1)
+                        .required(true)
+                        .help("Lamports to withdraw, above the rent exemption of the house"),
+                )
+                .arg(
+                    Arg::with_name("recipient_address")
+                        .long("recipient")
+                        .value_name("RECIPIENT_ADDRESS")
+                        .validator(is_valid_pubkey)
+                        .takes_value(true)
+                        .help("The address receiving the lamports - the user wallet if not given"),
+                )
+                .arg(
+                    Arg::with_name("user_address")
+                        .long("user-address")
+                        .value_name("USER_ADDRESS")
+                        .alias("keypair")
+                        .validator(is_valid_pubkey)
+                        .help("The address of the wallet whose house should be withdrawn from"),
+                )
+                .arg(
+                    Arg::with_name("propose")
+                        .long("propose")
+                        .takes_value(false)
+                        .help("Propose the withdrawal to the token holders of the house instead"),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("show-realm-config")
+                .about("Display the rules of the realm"),
+        )
+        .subcommand(
+            SubCommand::with_name("withdraw-from-treasury")
+                .about("Withdraw lamports from the treasury of the realm given with --realm, or of the legacy realm")
+                .arg(
+                    Arg::with_name("lamports")
+                        .value_name("LAMPORTS")
+                        .index(1)
+                        .required(true)
+                        .help("Lamports to withdraw, above the rent exemption of the treasury"),
+                )
+                .arg(
+                    Arg::with_name("recipient_address")
+                        .long("recipient")
+                        .value_name("RECIPIENT_ADDRESS")
+                        .validator(is_valid_pubkey)
+                        .takes_value(true)
+                        .help("The address receiving the lamports - the config keypair if not given"),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("create-realm")
+                .about("Create a realm, an independent hierarchy of titles")
+                .arg(
+                    Arg::with_name("name")
+                        .value_name("REALM_NAME")
+                        .validator(is_realm_name)
+                        .index(1)
+                        .required(true)
+                        .help("Name of the new realm"),
                 ),
         )
+        .subcommand(
+            SubCommand::with_name("show-realm")
+                .about("Display information about the realm given with --realm"),
+        )
         .subcommand(
             SubCommand::with_name("show-root-title")
-                .about("Display information about the root title"),
+                .about("Display information about the root title of the realm given with --realm"),
         )
         .subcommand(
             SubCommand::with_name("show-title")
@@ -189,7 +439,7 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
                                 is_valid_pubkey(s)
                             }
                         })
-                        .help("The address of the liege title - may be empty for root titles"),
+                        .help("The address of the liege title - may be empty for the root title of the realm given with --realm"),
                 )
                 .arg(
                     Arg::with_name("coat_of_arms")
@@ -213,16 +463,16 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
                         .long("rank")
                         .value_name("RANK")
                         .takes_value(true)
-                        .validator(|s| is_within_range(s, MIN_RANK as usize, MAX_RANK as usize))
-                        .help("Title rank"),
+                        .validator(|s| s.parse::<Rank>().map(|_| ()))
+                        .help("Title rank, by name (Deus, Emperor, King, Duke, Count, Baron, Baronet, Knight) or number"),
                 )
                 .arg(
                     Arg::with_name("kind")
                         .long("kind")
                         .value_name("KIND")
                         .takes_value(true)
-                        .validator(|s| is_within_range(s, MIN_KIND as usize, MAX_KIND as usize))
-                        .help("Title kind"),
+                        .validator(|s| s.parse::<Kind>().map(|_| ()))
+                        .help("Title kind, by name (Noble, Religious) or number"),
                 )
                 .arg(
                     Arg::with_name("required_stake_lamports")
@@ -236,222 +486,1850 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
                         .long("liege-vassal-index")
                         .value_name("LIEGE_VASSAL_INDEX")
                         .takes_value(true)
-                        .validator(|s| is_within_range(s, 0, MAX_VASSALS as usize))
-                        .help("Index into the liege's vassal vector"),
+                        .validator(|s| is_within_range(s, 0, u16::MAX as usize))
+                        .help("Index into the liege's vassal list"),
                 ),
         )
-        .get_matches();
-
-    let (sub_command, sub_matches) = app_matches.subcommand();
-    let matches = sub_matches.unwrap();
-
-    let config = {
-        let cli_config = if let Some(config_file) = matches.value_of("config_file") {
-            solana_cli_config::Config::load(config_file).unwrap_or_default()
-        } else {
-            solana_cli_config::Config::default()
-        };
-
-        Config {
-            json_rpc_url: matches
-                .value_of("json_rpc_url")
-                .unwrap_or(&cli_config.json_rpc_url)
-                .to_string(),
-            keypair: read_keypair_file(
-                matches
-                    .value_of("keypair")
-                    .unwrap_or(&cli_config.keypair_path),
-            )?,
-            verbose: matches.is_present("verbose"),
-        }
-    };
-    solana_logger::setup_with_default("solana=info");
-    let rpc_client =
-        RpcClient::new_with_commitment(config.json_rpc_url.clone(), CommitmentConfig::confirmed());
-
-    match (sub_command, sub_matches) {
-        ("show-house", Some(arg_matches)) => {
-            let user_address =
-                pubkey_of(arg_matches, "user_address").unwrap_or(config.keypair.pubkey());
-            let house_addr = nobilitydao::get_house_address(&user_address);
-            println!("House Address: {}", house_addr);
-            let housedata = get_house(&rpc_client, &house_addr)?;
-            let coa_url = housedata.coat_of_arms;
-            let display_name = housedata.display_name;
-            println!("Display Name: {}", display_name);
-            println!("Coat of Arms: {}", coa_url);
-            Ok(())
-        }
-        ("create-house", Some(arg_matches)) => {
-            let user_keypair = keypair_of(arg_matches, "user_address").unwrap_or(config.keypair);
-            let coat_of_arms_str = arg_matches.value_of("coat_of_arms").unwrap();
-            let display_name_str = arg_matches.value_of("display_name").unwrap();
-            create_house(
-                &rpc_client,
-                &user_keypair,
-                coat_of_arms_str,
-                display_name_str,
-            )
-        }
-        ("show-root-title", Some(arg_matches)) => {
-            let liege_address = Pubkey::new(&[0; 32]);
-            let title_address = nobilitydao::get_title_address(&liege_address, 0);
-            println!("Title address: {}", title_address);
-            let titledata = get_title(&rpc_client, &title_address)?;
-            print_title(&titledata)
-        }
-        ("show-title", Some(arg_matches)) => {
-            let title_address = pubkey_of(arg_matches, "title_address").unwrap();
-            println!("Title address: {}", title_address);
-            let titledata = get_title(&rpc_client, &title_address)?;
-            print_title(&titledata)
-        }
-        ("create-title", Some(arg_matches)) => {
-            let user_keypair = keypair_of(arg_matches, "user_address").unwrap_or(config.keypair);
-            let liege_title_address = if arg_matches.value_of("liege_address").unwrap().len() == 0 {
-                Pubkey::new(&[0; 32])
-            } else {
-                pubkey_of(arg_matches, "liege_address").unwrap()
-            };
-            let rank = value_t_or_exit!(arg_matches, "rank", u8);
-            let kind = value_t_or_exit!(arg_matches, "kind", u8);
-            let required_stake_lamports =
-                value_t_or_exit!(arg_matches, "required_stake_lamports", u64);
-            let liege_vassal_index = value_t_or_exit!(arg_matches, "liege_vassal_index", u8);
-            let coat_of_arms_str = arg_matches.value_of("coat_of_arms").unwrap();
-            let display_name_str = arg_matches.value_of("display_name").unwrap();
-            create_title(
-                &rpc_client,
-                &user_keypair,
-                &liege_title_address,
-                rank,
-                kind,
-                required_stake_lamports,
-                liege_vassal_index,
-                coat_of_arms_str,
-                display_name_str,
-            )
-        }
-        _ => unreachable!(),
-    }
-}
-
-fn get_house(rpc_client: &RpcClient, house_address: &Pubkey) -> Result<HouseData, String> {
-    let account = rpc_client
-        .get_multiple_accounts(&[*house_address])
-        .map_err(|err| err.to_string())?
-        .into_iter()
-        .next()
-        .unwrap();
-
-    match account {
-        None => Err(format!("House {} does not exist", house_address)),
-        Some(account) => try_from_slice_checked::<HouseData>(&account.data, HouseData::SIZE)
-            .map_err(|err| format!("Failed to deserialize house {}: {}", house_address, err)),
-    }
-}
-
-fn get_title(rpc_client: &RpcClient, title_address: &Pubkey) -> Result<TitleData, String> {
-    let account = rpc_client
-        .get_multiple_accounts(&[*title_address])
-        .map_err(|err| err.to_string())?
-        .into_iter()
-        .next()
-        .unwrap();
-
-    match account {
-        None => Err(format!("Title {} does not exist", title_address)),
-        Some(account) => try_from_slice_checked::<TitleData>(&account.data, TitleData::SIZE)
-            .map_err(|err| format!("Failed to deserialize title {}: {}", title_address, err)),
-    }
-}
-
-fn print_title(titledata: &TitleData) -> Result<(), Box<dyn std::error::Error>> {
-    let coa_url = &titledata.coat_of_arms;
-    let display_name = &titledata.display_name;
-    println!("Display Name: {}", display_name);
-    println!("Coat of Arms: {}", coa_url);
-    println!("Rank: {}", titledata.rank);
-    println!("Kind: {}", titledata.kind);
-    println!(
-        "Required stake (SOL): {}",
-        lamports_to_sol(titledata.required_stake_lamports)
-    );
-    println!(
-        "Sale price (SOL): {}",
-        lamports_to_sol(titledata.sale_price_lamports)
-    );
-    println!("Holder: {}", titledata.holder_house_address);
-    if titledata.liege_address != Pubkey::new(&[0; 32]) {
-        println!("Liege: {}", titledata.liege_address);
-    }
-    for vassal_address in titledata.vassal_addresses.iter() {
-        println!("Vassal: {}", vassal_address);
+        .subcommand(
+            SubCommand::with_name("update-title")
+                .about("Update the coat of arms of a title")
+                .arg(
+                    Arg::with_name("title_address")
+                        .value_name("TITLE_ADDRESS")
+                        .validator(is_valid_pubkey)
+                        .index(1)
+                        .required(true)
+                        .help("The address of the title that should be updated"),
+                )
+                .arg(
+                    Arg::with_name("user_address")
+                        .long("user-address")
+                        .value_name("USER_ADDRESS")
+                        .alias("keypair")
+                        .validator(is_valid_pubkey)
+                        .help("The address of the wallet which holds the title"),
+                )
+                .arg(
+                    Arg::with_name("coat_of_arms")
+                        .long("coat-of-arms")
+                        .value_name("COAT_OF_ARMS")
+                        .takes_value(true)
+                        .required(true)
+                        .validator(is_short_url)
+                        .help("New coat of arms of the title"),
+                )
+                .arg(
+                    Arg::with_name("propose")
+                        .long("propose")
+                        .takes_value(false)
+                        .help("Propose the update to the token holders of the house instead"),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("set-succession-law")
+                .about("Set how a title passes on once its holder's house stops proving life")
+                .arg(
+                    Arg::with_name("title_address")
+                        .value_name("TITLE_ADDRESS")
+                        .validator(is_valid_pubkey)
+                        .index(1)
+                        .required(true)
+                        .help("The address of the title"),
+                )
+                .arg(
+                    Arg::with_name("succession_law")
+                        .value_name("SUCCESSION_LAW")
+                        .index(2)
+                        .required(true)
+                        .validator(|s| s.parse::<SuccessionLaw>().map(|_| ()))
+                        .help("Succession law, by name (Heir, Elective, Sale) or number"),
+                )
+                .arg(
+                    Arg::with_name("user_address")
+                        .long("user-address")
+                        .value_name("USER_ADDRESS")
+                        .alias("keypair")
+                        .validator(is_valid_pubkey)
+                        .help("The address of the wallet which holds the title"),
+                )
+                .arg(
+                    Arg::with_name("propose")
+                        .long("propose")
+                        .takes_value(false)
+                        .help("Propose the succession law to the token holders of the house instead"),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("petition-vassalage")
+                .about("Petition to hold a new vassal title under a liege title")
+                .arg(
+                    Arg::with_name("liege_address")
+                        .value_name("LIEGE_ADDRESS")
+                        .validator(is_valid_pubkey)
+                        .index(1)
+                        .required(true)
+                        .help("The address of the liege title"),
+                )
+                .arg(
+                    Arg::with_name("user_address")
+                        .long("user-address")
+                        .value_name("USER_ADDRESS")
+                        .alias("keypair")
+                        .validator(is_valid_pubkey)
+                        .help("The address of the wallet which will hold the new title"),
+                )
+                .arg(
+                    Arg::with_name("coat_of_arms")
+                        .long("coat-of-arms")
+                        .value_name("COAT_OF_ARMS")
+                        .takes_value(true)
+                        .validator(is_short_url)
+                        .default_value("")
+                        .help("Coat of arms of the new title"),
+                )
+                .arg(
+                    Arg::with_name("display_name")
+                        .long("display-name")
+                        .value_name("DISPLAY_NAME")
+                        .takes_value(true)
+                        .validator(is_short)
+                        .help("Display name for the title"),
+                )
+                .arg(
+                    Arg::with_name("rank")
+                        .long("rank")
+                        .value_name("RANK")
+                        .takes_value(true)
+                        .validator(|s| s.parse::<Rank>().map(|_| ()))
+                        .help("Title rank, by name (Deus, Emperor, King, Duke, Count, Baron, Baronet, Knight) or number"),
+                )
+                .arg(
+                    Arg::with_name("kind")
+                        .long("kind")
+                        .value_name("KIND")
+                        .takes_value(true)
+                        .validator(|s| s.parse::<Kind>().map(|_| ()))
+                        .help("Title kind, by name (Noble, Religious) or number"),
+                )
+                .arg(
+                    Arg::with_name("required_stake_lamports")
+                        .long("required-stake-lamports")
+                        .value_name("REQUIRED_STAKE_LAMPORTS")
+                        .takes_value(true)
+                        .help("Required number of lamports to stake when activating this title"),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("accept-petition")
+                .about("Accept a petition, creating the petitioned title as the liege's next vassal")
+                .arg(
+                    Arg::with_name("liege_address")
+                        .value_name("LIEGE_ADDRESS")
+                        .validator(is_valid_pubkey)
+                        .index(1)
+                        .required(true)
+                        .help("The address of the liege title"),
+                )
+                .arg(
+                    Arg::with_name("petitioner_address")
+                        .value_name("PETITIONER_ADDRESS")
+                        .validator(is_valid_pubkey)
+                        .index(2)
+                        .required(true)
+                        .help("The address of the petitioner's wallet"),
+                )
+                .arg(
+                    Arg::with_name("petitioner_house")
+                        .long("petitioner-house")
+                        .value_name("PETITIONER_HOUSE")
+                        .validator(is_valid_pubkey)
+                        .takes_value(true)
+                        .help("The address of the petitioner's house [default: the house derived from the petitioner's wallet]"),
+                )
+                .arg(
+                    Arg::with_name("user_address")
+                        .long("user-address")
+                        .value_name("USER_ADDRESS")
+                        .alias("keypair")
+                        .validator(is_valid_pubkey)
+                        .help("The address of the wallet which holds the liege title"),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("reject-petition")
+                .about("Reject a petition as the liege holder, or withdraw it as the petitioner")
+                .arg(
+                    Arg::with_name("liege_address")
+                        .value_name("LIEGE_ADDRESS")
+                        .validator(is_valid_pubkey)
+                        .index(1)
+                        .required(true)
+                        .help("The address of the liege title"),
+                )
+                .arg(
+                    Arg::with_name("petitioner_address")
+                        .value_name("PETITIONER_ADDRESS")
+                        .validator(is_valid_pubkey)
+                        .index(2)
+                        .required(true)
+                        .help("The address of the petitioner's wallet"),
+                )
+                .arg(
+                    Arg::with_name("petitioner_house")
+                        .long("petitioner-house")
+                        .value_name("PETITIONER_HOUSE")
+                        .validator(is_valid_pubkey)
+                        .takes_value(true)
+                        .help("The address of the petitioner's house [default: the house derived from the petitioner's wallet]"),
+                )
+                .arg(
+                    Arg::with_name("user_address")
+                        .long("user-address")
+                        .value_name("USER_ADDRESS")
+                        .alias("keypair")
+                        .validator(is_valid_pubkey)
+                        .help("The address of the wallet of the liege holder or the petitioner"),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("reparent-title")
+                .about("Move a title to another liege, signed by both holders")
+                .arg(
+                    Arg::with_name("title_address")
+                        .value_name("TITLE_ADDRESS")
+                        .validator(is_valid_pubkey)
+                        .index(1)
+                        .required(true)
+                        .help("The address of the title to move"),
+                )
+                .arg(
+                    Arg::with_name("new_liege_address")
+                        .value_name("NEW_LIEGE_ADDRESS")
+                        .validator(is_valid_pubkey)
+                        .index(2)
+                        .required(true)
+                        .help("The address of the new liege title"),
+                )
+                .arg(
+                    Arg::with_name("user_address")
+                        .long("user-address")
+                        .value_name("USER_ADDRESS")
+                        .alias("keypair")
+                        .validator(is_valid_pubkey)
+                        .help("The address of the wallet which holds the title"),
+                )
+                .arg(
+                    Arg::with_name("new_liege_holder")
+                        .long("new-liege-holder")
+                        .value_name("KEYPAIR")
+                        .validator(is_keypair)
+                        .takes_value(true)
+                        .help("Keypair of the wallet which holds the new liege title [default: the title holder's]"),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("escheat-title")
+                .about("Reclaim a delinquent vassal title for the house holding its liege")
+                .arg(
+                    Arg::with_name("title_address")
+                        .value_name("TITLE_ADDRESS")
+                        .validator(is_valid_pubkey)
+                        .index(1)
+                        .required(true)
+                        .help("The address of the vassal title"),
+                )
+                .arg(
+                    Arg::with_name("user_address")
+                        .long("user-address")
+                        .value_name("USER_ADDRESS")
+                        .alias("keypair")
+                        .validator(is_valid_pubkey)
+                        .help("The address of the wallet which holds the liege title"),
+                )
+                .arg(
+                    Arg::with_name("holder_wallet")
+                        .long("holder-wallet")
+                        .value_name("HOLDER_WALLET")
+                        .validator(is_valid_pubkey)
+                        .takes_value(true)
+                        .help("The wallet of the title's holder, which gets back the remaining stake - required for active titles"),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("open-election")
+                .about("Open the election of the next holder of a vacant elective title")
+                .arg(
+                    Arg::with_name("title_address")
+                        .value_name("TITLE_ADDRESS")
+                        .validator(is_valid_pubkey)
+                        .index(1)
+                        .required(true)
+                        .help("The address of the elective title"),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("show-election")
+                .about("Display the election of the next holder of a title")
+                .arg(
+                    Arg::with_name("title_address")
+                        .value_name("TITLE_ADDRESS")
+                        .validator(is_valid_pubkey)
+                        .index(1)
+                        .required(true)
+                        .help("The address of the elective title"),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("cast-ballot")
+                .about("Vote for the next holder of a title with one of its vassals")
+                .arg(
+                    Arg::with_name("title_address")
+                        .value_name("TITLE_ADDRESS")
+                        .validator(is_valid_pubkey)
+                        .index(1)
+                        .required(true)
+                        .help("The address of the elective title"),
+                )
+                .arg(
+                    Arg::with_name("candidate_house_address")
+                        .value_name("CANDIDATE_HOUSE_ADDRESS")
+                        .validator(is_valid_pubkey)
+                        .index(2)
+                        .required(true)
+                        .help("The address of the house to vote for"),
+                )
+                .arg(
+                    Arg::with_name("vassal_address")
+                        .long("vassal-address")
+                        .value_name("VASSAL_ADDRESS")
+                        .validator(is_valid_pubkey)
+                        .required(true)
+                        .help("The address of the vassal title voting"),
+                )
+                .arg(
+                    Arg::with_name("user_address")
+                        .long("user-address")
+                        .value_name("USER_ADDRESS")
+                        .alias("keypair")
+                        .validator(is_valid_pubkey)
+                        .help("The address of the wallet which holds the vassal title"),
+                )
+                .arg(
+                    Arg::with_name("propose")
+                        .long("propose")
+                        .takes_value(false)
+                        .help("Propose the ballot to the token holders of the house instead"),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("conclude-election")
+                .about("Hand an elective title to the winner of its election")
+                .arg(
+                    Arg::with_name("title_address")
+                        .value_name("TITLE_ADDRESS")
+                        .validator(is_valid_pubkey)
+                        .index(1)
+                        .required(true)
+                        .help("The address of the elective title"),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("show-proposal")
+                .about("Display a proposal to the token holders of a house")
+                .arg(
+                    Arg::with_name("proposal_address")
+                        .value_name("PROPOSAL_ADDRESS")
+                        .validator(is_valid_pubkey)
+                        .index(1)
+                        .required(true)
+                        .help("The address of the proposal that should be shown"),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("vote")
+                .about("Vote for a proposal by depositing governance tokens")
+                .arg(
+                    Arg::with_name("proposal_address")
+                        .value_name("PROPOSAL_ADDRESS")
+                        .validator(is_valid_pubkey)
+                        .index(1)
+                        .required(true)
+                        .help("The address of the proposal to vote for"),
+                )
+                .arg(
+                    Arg::with_name("tokens")
+                        .long("tokens")
+                        .value_name("TOKENS")
+                        .takes_value(true)
+                        .required(true)
+                        .validator(|s| is_within_range(s, 1, u16::MAX as usize))
+                        .help("Number of governance tokens to deposit"),
+                )
+                .arg(
+                    Arg::with_name("token_account")
+                        .long("token-account")
+                        .value_name("TOKEN_ACCOUNT")
+                        .validator(is_valid_pubkey)
+                        .takes_value(true)
+                        .required(true)
+                        .help("The token account to deposit the governance tokens from"),
+                )
+                .arg(
+                    Arg::with_name("user_address")
+                        .long("user-address")
+                        .value_name("USER_ADDRESS")
+                        .alias("keypair")
+                        .validator(is_valid_pubkey)
+                        .help("The address of the wallet owning the tokens"),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("withdraw-vote")
+                .about("Withdraw the governance tokens deposited for a proposal")
+                .arg(
+                    Arg::with_name("proposal_address")
+                        .value_name("PROPOSAL_ADDRESS")
+                        .validator(is_valid_pubkey)
+                        .index(1)
+                        .required(true)
+                        .help("The address of the proposal voted for"),
+                )
+                .arg(
+                    Arg::with_name("token_account")
+                        .long("token-account")
+                        .value_name("TOKEN_ACCOUNT")
+                        .validator(is_valid_pubkey)
+                        .takes_value(true)
+                        .required(true)
+                        .help("The token account receiving the governance tokens"),
+                )
+                .arg(
+                    Arg::with_name("user_address")
+                        .long("user-address")
+                        .value_name("USER_ADDRESS")
+                        .alias("keypair")
+                        .validator(is_valid_pubkey)
+                        .help("The address of the wallet which voted"),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("execute-proposal")
+                .about("Execute a proposal approved by the token holders of a house")
+                .arg(
+                    Arg::with_name("proposal_address")
+                        .value_name("PROPOSAL_ADDRESS")
+                        .validator(is_valid_pubkey)
+                        .index(1)
+                        .required(true)
+                        .help("The address of the proposal to execute"),
+                ),
+        )
+        .get_matches();
+
+    let (sub_command, sub_matches) = app_matches.subcommand();
+    let matches = sub_matches.unwrap();
+
+    let config = {
+        let cli_config = if let Some(config_file) = matches.value_of("config_file") {
+            solana_cli_config::Config::load(config_file).unwrap_or_default()
+        } else {
+            solana_cli_config::Config::default()
+        };
+
+        Config {
+            json_rpc_url: matches
+                .value_of("json_rpc_url")
+                .unwrap_or(&cli_config.json_rpc_url)
+                .to_string(),
+            keypair: read_keypair_file(
+                matches
+                    .value_of("keypair")
+                    .unwrap_or(&cli_config.keypair_path),
+            )?,
+            verbose: matches.is_present("verbose"),
+        }
+    };
+    solana_logger::setup_with_default("solana=info");
+    let rpc_client =
+        RpcClient::new_with_commitment(config.json_rpc_url.clone(), CommitmentConfig::confirmed());
+
+    match (sub_command, sub_matches) {
+        ("show-house", Some(arg_matches)) => {
+            let user_address =
+                pubkey_of(arg_matches, "user_address").unwrap_or(config.keypair.pubkey());
+            let house_addr = house_address_of(arg_matches, &user_address);
+            println!("House Address: {}", house_addr);
+            let housedata = get_house(&rpc_client, &house_addr)?;
+            let coa_url = &housedata.coat_of_arms;
+            let display_name = &housedata.display_name;
+            println!("Display Name: {}", display_name);
+            println!("Coat of Arms: {}", coa_url);
+            if housedata.authority == Pubkey::new(&[0; 32]) {
+                println!("Authority: the wallet the house is derived from");
+            } else {
+                println!("Authority: {}", housedata.authority);
+            }
+            println!(
+                "Governance token supply: {}",
+                housedata.governance_token_supply
+            );
+            if housedata.is_token_governed() {
+                println!(
+                    "Governance wallet: {}",
+                    nobilitydao::get_governance_address(&house_addr)
+                );
+                println!(
+                    "Governance mint: {}",
+                    nobilitydao::get_governance_mint_address(&house_addr)
+                );
+                println!("Proposals: {}", housedata.proposal_count);
+            }
+            if housedata.heir_house_address != Pubkey::new(&[0; 32]) {
+                println!("Heir: {}", housedata.heir_house_address);
+                println!(
+                    "Succession period (slots): {}",
+                    housedata.succession_period_slots
+                );
+                println!(
+                    "Last proof of life slot: {}",
+                    housedata.last_proof_of_life_slot
+                );
+            }
+            if housedata.held_title_count == HouseData::UNKNOWN_TITLE_COUNT {
+                println!("Titles held: unknown");
+            } else {
+                println!("Titles held: {}", housedata.held_title_count);
+            }
+            if arg_matches.is_present("titles") {
+                if housedata.held_title_count == HouseData::UNKNOWN_TITLE_COUNT {
+                    println!("Titles held before holdings were recorded are not listed");
+                }
+                for title_address in get_holdings(&rpc_client, &house_addr)?.iter() {
+                    println!("Title: {}", title_address);
+                }
+            }
+            Ok(())
+        }
+        ("create-house", Some(arg_matches)) => {
+            let user_keypair = keypair_of(arg_matches, "user_address").unwrap_or(config.keypair);
+            let coat_of_arms_str = arg_matches.value_of("coat_of_arms").unwrap();
+            let display_name_str = arg_matches.value_of("display_name").unwrap();
+            let governance_token_supply =
+                value_t_or_exit!(arg_matches, "governance_token_supply", u16);
+            create_house(
+                &rpc_client,
+                &user_keypair,
+                governance_token_supply,
+                coat_of_arms_str,
+                display_name_str,
+            )
+        }
+        ("update-house", Some(arg_matches)) => {
+            let user_keypair = keypair_of(arg_matches, "user_address").unwrap_or(config.keypair);
+            let coat_of_arms_str = arg_matches.value_of("coat_of_arms").unwrap();
+            let house_addr = house_address_of(arg_matches, &user_keypair.pubkey());
+            if arg_matches.is_present("propose") {
+                propose(
+                    &rpc_client,
+                    &user_keypair,
+                    &house_addr,
+                    nobilitydao::instruction::update_house(
+                        &nobilitydao::get_governance_address(&house_addr),
+                        &house_addr,
+                        coat_of_arms_str.to_string(),
+                    ),
+                )
+            } else {
+                update_house(&rpc_client, &user_keypair, &house_addr, coat_of_arms_str)
+            }
+        }
+        ("close-house", Some(arg_matches)) => {
+            let user_keypair = keypair_of(arg_matches, "user_address").unwrap_or(config.keypair);
+            let house_addr = house_address_of(arg_matches, &user_keypair.pubkey());
+            close_house(&rpc_client, &user_keypair, &house_addr)
+        }
+        ("transfer-house-authority", Some(arg_matches)) => {
+            let user_keypair = keypair_of(arg_matches, "user_address").unwrap_or(config.keypair);
+            let new_authority = pubkey_of(arg_matches, "new_authority").unwrap();
+            let house_addr = house_address_of(arg_matches, &user_keypair.pubkey());
+            transfer_house_authority(&rpc_client, &user_keypair, &house_addr, &new_authority)
+        }
+        ("set-heir", Some(arg_matches)) => {
+            let user_keypair = keypair_of(arg_matches, "user_address").unwrap_or(config.keypair);
+            let heir_house_address = pubkey_of(arg_matches, "heir_house_address").unwrap();
+            let succession_period_slots =
+                value_t_or_exit!(arg_matches, "succession_period_slots", u64);
+            let house_addr = house_address_of(arg_matches, &user_keypair.pubkey());
+            if arg_matches.is_present("propose") {
+                propose(
+                    &rpc_client,
+                    &user_keypair,
+                    &house_addr,
+                    nobilitydao::instruction::set_heir(
+                        &nobilitydao::get_governance_address(&house_addr),
+                        &house_addr,
+                        &heir_house_address,
+                        succession_period_slots,
+                    ),
+                )
+            } else {
+                println!("House Address: {}", house_addr);
+                send_instruction(
+                    &rpc_client,
+                    &user_keypair,
+                    nobilitydao::instruction::set_heir(
+                        &user_keypair.pubkey(),
+                        &house_addr,
+                        &heir_house_address,
+                        succession_period_slots,
+                    ),
+                )?;
+                println!("Done naming heir!");
+                Ok(())
+            }
+        }
+        ("prove-life", Some(arg_matches)) => {
+            let user_keypair = keypair_of(arg_matches, "user_address").unwrap_or(config.keypair);
+            let house_addr = house_address_of(arg_matches, &user_keypair.pubkey());
+            if arg_matches.is_present("propose") {
+                propose(
+                    &rpc_client,
+                    &user_keypair,
+                    &house_addr,
+                    nobilitydao::instruction::prove_life(
+                        &nobilitydao::get_governance_address(&house_addr),
+                        &house_addr,
+                    ),
+                )
+            } else {
+                println!("House Address: {}", house_addr);
+                send_instruction(
+                    &rpc_client,
+                    &user_keypair,
+                    nobilitydao::instruction::prove_life(&user_keypair.pubkey(), &house_addr),
+                )?;
+                println!("Done proving life!");
+                Ok(())
+            }
+        }
+        ("trigger-succession", Some(arg_matches)) => {
+            let house_addr = pubkey_of(arg_matches, "house_address").unwrap();
+            trigger_succession(&rpc_client, &config.keypair, &house_addr)
+        }
+        ("withdraw-from-house", Some(arg_matches)) => {
+            let user_keypair = keypair_of(arg_matches, "user_address").unwrap_or(config.keypair);
+            let lamports = value_t_or_exit!(arg_matches, "lamports", u64);
+            let recipient_address = pubkey_of(arg_matches, "recipient_address")
+                .unwrap_or_else(|| user_keypair.pubkey());
+            let house_addr = house_address_of(arg_matches, &user_keypair.pubkey());
+            if arg_matches.is_present("propose") {
+                propose(
+                    &rpc_client,
+                    &user_keypair,
+                    &house_addr,
+                    nobilitydao::instruction::withdraw_from_house(
+                        &nobilitydao::get_governance_address(&house_addr),
+                        &house_addr,
+                        &recipient_address,
+                        lamports,
+                    ),
+                )
+            } else {
+                withdraw_from_house(
+                    &rpc_client,
+                    &user_keypair,
+                    &house_addr,
+                    &recipient_address,
+                    lamports,
+                )
+            }
+        }
+        ("show-realm-config", Some(_arg_matches)) => {
+            let realm_config_address = nobilitydao::get_realm_config_address();
+            println!("Realm config address: {}", realm_config_address);
+            let realm_config = get_realm_config(&rpc_client, &realm_config_address)?;
+            let settings = realm_config.settings;
+            println!("Admin: {}", realm_config.admin);
+            println!("Lowest rank: {}", settings.max_rank);
+            println!("Highest kind: {}", settings.max_kind);
+            println!("Max vassals per title: {}", settings.max_vassals);
+            println!("Tax rate (bps per period): {}", settings.tax_rate_bps);
+            println!("Tax period (slots): {}", settings.tax_period_slots);
+            println!(
+                "Not for sale assessment multiplier: {}",
+                settings.not_for_sale_assessment_multiplier
+            );
+            println!("Protocol fee (bps): {}", settings.protocol_fee_bps);
+            println!(
+                "Escheat grace period (slots): {}",
+                settings.escheat_grace_period_slots
+            );
+            println!(
+                "Election period (slots): {}",
+                settings.election_period_slots
+            );
+            println!("Vacancy period (slots): {}", settings.vacancy_period_slots);
+            Ok(())
+        }
+        ("withdraw-from-treasury", Some(arg_matches)) => {
+            let lamports = value_t_or_exit!(arg_matches, "lamports", u64);
+            let recipient_address = pubkey_of(arg_matches, "recipient_address")
+                .unwrap_or_else(|| config.keypair.pubkey());
+            // Without --realm, the treasury of the legacy realm, whose address is all zeroes.
+            let realm_address = realm_address_of(arg_matches).unwrap_or_default();
+            withdraw_from_treasury(
+                &rpc_client,
+                &config.keypair,
+                &realm_address,
+                &recipient_address,
+                lamports,
+            )
+        }
+        ("create-realm", Some(arg_matches)) => {
+            let name = arg_matches.value_of("name").unwrap();
+            create_realm(&rpc_client, &config.keypair, name)
+        }
+        ("show-realm", Some(arg_matches)) => {
+            let realm_address = realm_address_of(arg_matches)?;
+            println!("Realm address: {}", realm_address);
+            let realm = get_realm(&rpc_client, &realm_address)?;
+            println!("Name: {}", realm.name);
+            println!("Authority: {}", realm.authority);
+            if realm.root_title_address == Pubkey::new(&[0; 32]) {
+                println!("Root title address: none yet");
+            } else {
+                println!("Root title address: {}", realm.root_title_address);
+            }
+            println!(
+                "Treasury address: {}",
+                nobilitydao::get_treasury_address(&realm_address)
+            );
+            Ok(())
+        }
+        ("show-root-title", Some(arg_matches)) => {
+            let realm_address = realm_address_of(arg_matches)?;
+            let realm = get_realm(&rpc_client, &realm_address)?;
+            if realm.root_title_address == Pubkey::new(&[0; 32]) {
+                return Err(format!("Realm {} has no root title yet", realm.name).into());
+            }
+            let title_address = realm.root_title_address;
+            println!("Title address: {}", title_address);
+            let titledata = get_title(&rpc_client, &title_address)?;
+            print_title(&rpc_client, &title_address, &titledata)
+        }
+        ("show-title", Some(arg_matches)) => {
+            let title_address = pubkey_of(arg_matches, "title_address").unwrap();
+            println!("Title address: {}", title_address);
+            let titledata = get_title(&rpc_client, &title_address)?;
+            print_title(&rpc_client, &title_address, &titledata)
+        }
+        ("create-title", Some(arg_matches)) => {
+            let user_keypair = keypair_of(arg_matches, "user_address").unwrap_or(config.keypair);
+            let liege_title_address = if arg_matches.value_of("liege_address").unwrap().len() == 0 {
+                Pubkey::new(&[0; 32])
+            } else {
+                pubkey_of(arg_matches, "liege_address").unwrap()
+            };
+            let liege_vassal_index = value_t_or_exit!(arg_matches, "liege_vassal_index", u16);
+            // The root title is created in the given realm, others in their liege's realm.
+            let realm_address = if liege_title_address == Pubkey::new(&[0; 32]) {
+                realm_address_of(arg_matches)?
+            } else {
+                get_title(&rpc_client, &liege_title_address)?.realm_address
+            };
+            let house_addr = house_address_of(arg_matches, &user_keypair.pubkey());
+            create_title(
+                &rpc_client,
+                &user_keypair,
+                &house_addr,
+                &realm_address,
+                VassalSlot {
+                    liege_address: liege_title_address,
+                    liege_vassal_index,
+                },
+                title_params_of(arg_matches),
+            )
+        }
+        ("update-title", Some(arg_matches)) => {
+            let user_keypair = keypair_of(arg_matches, "user_address").unwrap_or(config.keypair);
+            let title_address = pubkey_of(arg_matches, "title_address").unwrap();
+            let coat_of_arms_str = arg_matches.value_of("coat_of_arms").unwrap();
+            let house_addr = house_address_of(arg_matches, &user_keypair.pubkey());
+            if arg_matches.is_present("propose") {
+                propose(
+                    &rpc_client,
+                    &user_keypair,
+                    &house_addr,
+                    nobilitydao::instruction::update_title_coat_of_arms(
+                        &nobilitydao::get_governance_address(&house_addr),
+                        &house_addr,
+                        &title_address,
+                        coat_of_arms_str.to_string(),
+                    ),
+                )
+            } else {
+                update_title(
+                    &rpc_client,
+                    &user_keypair,
+                    &house_addr,
+                    &title_address,
+                    coat_of_arms_str,
+                )
+            }
+        }
+        ("reparent-title", Some(arg_matches)) => {
+            let user_keypair = keypair_of(arg_matches, "user_address").unwrap_or(config.keypair);
+            let title_address = pubkey_of(arg_matches, "title_address").unwrap();
+            let new_liege_address = pubkey_of(arg_matches, "new_liege_address").unwrap();
+            let new_liege_holder_keypair = keypair_of(arg_matches, "new_liege_holder");
+            reparent_title(
+                &rpc_client,
+                &user_keypair,
+                new_liege_holder_keypair.as_ref().unwrap_or(&user_keypair),
+                &title_address,
+                &new_liege_address,
+            )
+        }
+        ("escheat-title", Some(arg_matches)) => {
+            let user_keypair = keypair_of(arg_matches, "user_address").unwrap_or(config.keypair);
+            let title_address = pubkey_of(arg_matches, "title_address").unwrap();
+            let holder_wallet_address = pubkey_of(arg_matches, "holder_wallet");
+            escheat_title(
+                &rpc_client,
+                &user_keypair,
+                &title_address,
+                holder_wallet_address,
+            )
+        }
+        ("petition-vassalage", Some(arg_matches)) => {
+            let user_keypair = keypair_of(arg_matches, "user_address").unwrap_or(config.keypair);
+            let liege_title_address = pubkey_of(arg_matches, "liege_address").unwrap();
+            let house_addr = house_address_of(arg_matches, &user_keypair.pubkey());
+            petition_vassalage(
+                &rpc_client,
+                &user_keypair,
+                &house_addr,
+                &liege_title_address,
+                title_params_of(arg_matches),
+            )
+        }
+        ("accept-petition", Some(arg_matches)) => {
+            let user_keypair = keypair_of(arg_matches, "user_address").unwrap_or(config.keypair);
+            let liege_title_address = pubkey_of(arg_matches, "liege_address").unwrap();
+            let petitioner_address = pubkey_of(arg_matches, "petitioner_address").unwrap();
+            let petitioner_house_address = pubkey_of(arg_matches, "petitioner_house")
+                .unwrap_or_else(|| nobilitydao::get_house_address(&petitioner_address));
+            // The new title becomes the liege's next vassal.
+            let liege = get_title(&rpc_client, &liege_title_address)?;
+            answer_petition(
+                &rpc_client,
+                &user_keypair,
+                nobilitydao::instruction::accept_petition(
+                    &user_keypair.pubkey(),
+                    &liege.holder_house_address,
+                    &liege_title_address,
+                    &petitioner_address,
+                    &petitioner_house_address,
+                    liege.vassal_count,
+                ),
+            )
+        }
+        ("reject-petition", Some(arg_matches)) => {
+            let user_keypair = keypair_of(arg_matches, "user_address").unwrap_or(config.keypair);
+            let liege_title_address = pubkey_of(arg_matches, "liege_address").unwrap();
+            let petitioner_address = pubkey_of(arg_matches, "petitioner_address").unwrap();
+            let petitioner_house_address = pubkey_of(arg_matches, "petitioner_house")
+                .unwrap_or_else(|| nobilitydao::get_house_address(&petitioner_address));
+            let house_addr = house_address_of(arg_matches, &user_keypair.pubkey());
+            answer_petition(
+                &rpc_client,
+                &user_keypair,
+                nobilitydao::instruction::reject_petition(
+                    &user_keypair.pubkey(),
+                    &house_addr,
+                    &liege_title_address,
+                    &petitioner_address,
+                    &petitioner_house_address,
+                ),
+            )
+        }
+        ("set-succession-law", Some(arg_matches)) => {
+            let user_keypair = keypair_of(arg_matches, "user_address").unwrap_or(config.keypair);
+            let title_address = pubkey_of(arg_matches, "title_address").unwrap();
+            let succession_law = value_t_or_exit!(arg_matches, "succession_law", SuccessionLaw);
+            let house_addr = house_address_of(arg_matches, &user_keypair.pubkey());
+            if arg_matches.is_present("propose") {
+                propose(
+                    &rpc_client,
+                    &user_keypair,
+                    &house_addr,
+                    nobilitydao::instruction::set_succession_law(
+                        &nobilitydao::get_governance_address(&house_addr),
+                        &house_addr,
+                        &title_address,
+                        succession_law,
+                    ),
+                )
+            } else {
+                send_instruction(
+                    &rpc_client,
+                    &user_keypair,
+                    nobilitydao::instruction::set_succession_law(
+                        &user_keypair.pubkey(),
+                        &house_addr,
+                        &title_address,
+                        succession_law,
+                    ),
+                )?;
+                println!("Done setting succession law!");
+                Ok(())
+            }
+        }
+        ("open-election", Some(arg_matches)) => {
+            let title_address = pubkey_of(arg_matches, "title_address").unwrap();
+            let titledata = get_title(&rpc_client, &title_address)?;
+            println!(
+                "Election Address: {}",
+                nobilitydao::get_election_address(&title_address)
+            );
+            send_instruction(
+                &rpc_client,
+                &config.keypair,
+                nobilitydao::instruction::open_election(
+                    &config.keypair.pubkey(),
+                    &title_address,
+                    &titledata.holder_house_address,
+                ),
+            )?;
+            println!("Done opening election!");
+            Ok(())
+        }
+        ("show-election", Some(arg_matches)) => {
+            let title_address = pubkey_of(arg_matches, "title_address").unwrap();
+            let election_address = nobilitydao::get_election_address(&title_address);
+            let election = get_election(&rpc_client, &election_address)?;
+            let titledata = get_title(&rpc_client, &title_address)?;
+            let realm_config =
+                get_realm_config(&rpc_client, &nobilitydao::get_realm_config_address())?;
+            println!("Election Address: {}", election_address);
+            println!("Vacating house: {}", election.holder_house_address);
+            println!("Opened at slot: {}", election.opened_slot);
+            println!(
+                "Ends at slot: {}",
+                election
+                    .opened_slot
+                    .saturating_add(realm_config.settings.election_period_slots)
+            );
+            for ballot in election.ballots.iter() {
+                println!(
+                    "Ballot: vassal {} for {}",
+                    ballot.vassal_address, ballot.candidate_house_address
+                );
+            }
+            if let Some((leader, votes)) = election.leader() {
+                println!(
+                    "Leader: {} with {} of {} vassals",
+                    leader, votes, titledata.live_vassal_count
+                );
+            }
+            Ok(())
+        }
+        ("cast-ballot", Some(arg_matches)) => {
+            let user_keypair = keypair_of(arg_matches, "user_address").unwrap_or(config.keypair);
+            let title_address = pubkey_of(arg_matches, "title_address").unwrap();
+            let candidate_house_address =
+                pubkey_of(arg_matches, "candidate_house_address").unwrap();
+            let vassal_address = pubkey_of(arg_matches, "vassal_address").unwrap();
+            let house_addr = house_address_of(arg_matches, &user_keypair.pubkey());
+            if arg_matches.is_present("propose") {
+                propose(
+                    &rpc_client,
+                    &user_keypair,
+                    &house_addr,
+                    nobilitydao::instruction::cast_ballot(
+                        &nobilitydao::get_governance_address(&house_addr),
+                        &house_addr,
+                        &vassal_address,
+                        &title_address,
+                        &candidate_house_address,
+                    ),
+                )
+            } else {
+                send_instruction(
+                    &rpc_client,
+                    &user_keypair,
+                    nobilitydao::instruction::cast_ballot(
+                        &user_keypair.pubkey(),
+                        &house_addr,
+                        &vassal_address,
+                        &title_address,
+                        &candidate_house_address,
+                    ),
+                )?;
+                println!("Done casting ballot!");
+                Ok(())
+            }
+        }
+        ("conclude-election", Some(arg_matches)) => {
+            let title_address = pubkey_of(arg_matches, "title_address").unwrap();
+            let election_address = nobilitydao::get_election_address(&title_address);
+            let election = get_election(&rpc_client, &election_address)?;
+            // Without ballots, the title passes to the heir of the vacating house, or to
+            // the realm's treasury. A lapsed election is closed without a winner, any house
+            // will do.
+            let winner_house_address = match election.leader() {
+                Some((leader, _)) => leader,
+                None => {
+                    let housedata = get_house(&rpc_client, &election.holder_house_address)?;
+                    if housedata.heir_house_address != Pubkey::new(&[0; 32]) {
+                        housedata.heir_house_address
+                    } else {
+                        let titledata = get_title(&rpc_client, &title_address)?;
+                        nobilitydao::get_treasury_address(&titledata.realm_address)
+                    }
+                }
+            };
+            println!("Winner: {}", winner_house_address);
+            send_instruction(
+                &rpc_client,
+                &config.keypair,
+                nobilitydao::instruction::conclude_election(
+                    &config.keypair.pubkey(),
+                    &title_address,
+                    &election.holder_house_address,
+                    &winner_house_address,
+                ),
+            )?;
+            println!("Done concluding election!");
+            Ok(())
+        }
+        ("show-proposal", Some(arg_matches)) => {
+            let proposal_address = pubkey_of(arg_matches, "proposal_address").unwrap();
+            let proposal = get_proposal(&rpc_client, &proposal_address)?;
+            let housedata = get_house(&rpc_client, &proposal.house_address)?;
+            println!("Proposal Address: {}", proposal_address);
+            println!("House: {}", proposal.house_address);
+            println!("Index: {}", proposal.proposal_index);
+            println!(
+                "Approving tokens: {}/{}",
+                proposal.approving_tokens, housedata.governance_token_supply
+            );
+            println!(
+                "Approved: {}",
+                proposal.is_approved(housedata.governance_token_supply)
+            );
+            println!("Executed: {}", proposal.executed);
+            println!("Voting deadline slot: {}", proposal.voting_deadline_slot);
+            match TitleInstruction::try_from_slice(&proposal.data) {
+                Ok(instruction) => println!("Instruction: {:?}", instruction),
+                Err(_) => println!("Instruction data: {:x?}", proposal.data),
+            }
+            for account in proposal.accounts.iter() {
+                println!(
+                    "Account: {} (signer: {}, writable: {})",
+                    account.pubkey, account.is_signer, account.is_writable
+                );
+            }
+            Ok(())
+        }
+        ("vote", Some(arg_matches)) => {
+            let user_keypair = keypair_of(arg_matches, "user_address").unwrap_or(config.keypair);
+            let proposal_address = pubkey_of(arg_matches, "proposal_address").unwrap();
+            let token_account_address = pubkey_of(arg_matches, "token_account").unwrap();
+            let tokens = value_t_or_exit!(arg_matches, "tokens", u64);
+            send_instruction(
+                &rpc_client,
+                &user_keypair,
+                nobilitydao::instruction::cast_vote(
+                    &user_keypair.pubkey(),
+                    &proposal_address,
+                    &token_account_address,
+                    tokens,
+                ),
+            )?;
+            println!("Done voting!");
+            Ok(())
+        }
+        ("withdraw-vote", Some(arg_matches)) => {
+            let user_keypair = keypair_of(arg_matches, "user_address").unwrap_or(config.keypair);
+            let proposal_address = pubkey_of(arg_matches, "proposal_address").unwrap();
+            let token_account_address = pubkey_of(arg_matches, "token_account").unwrap();
+            send_instruction(
+                &rpc_client,
+                &user_keypair,
+                nobilitydao::instruction::withdraw_vote(
+                    &user_keypair.pubkey(),
+                    &proposal_address,
+                    &token_account_address,
+                ),
+            )?;
+            println!("Done withdrawing vote!");
+            Ok(())
+        }
+        ("execute-proposal", Some(arg_matches)) => {
+            let proposal_address = pubkey_of(arg_matches, "proposal_address").unwrap();
+            let proposal = get_proposal(&rpc_client, &proposal_address)?;
+            send_instruction(
+                &rpc_client,
+                &config.keypair,
+                nobilitydao::instruction::execute_proposal(&proposal_address, &proposal),
+            )?;
+            println!("Done executing proposal!");
+            Ok(())
+        }
+        _ => unreachable!(),
+    }
+}
+
+fn get_house(rpc_client: &RpcClient, house_address: &Pubkey) -> Result<HouseData, String> {
+    let account = rpc_client
+        .get_multiple_accounts(&[*house_address])
+        .map_err(|err| err.to_string())?
+        .into_iter()
+        .next()
+        .unwrap();
+
+    match account {
+        None => Err(format!("House {} does not exist", house_address)),
+        Some(account) if account.owner != nobilitydao::id() => Err(format!(
+            "House {} is not owned by the program",
+            house_address
+        )),
+        Some(account) => try_from_account_data::<HouseData>(&account.data)
+            .map_err(|err| format!("Failed to deserialize house {}: {}", house_address, err)),
+    }
+}
+
+fn get_proposal(rpc_client: &RpcClient, proposal_address: &Pubkey) -> Result<ProposalData, String> {
+    let account = rpc_client
+        .get_multiple_accounts(&[*proposal_address])
+        .map_err(|err| err.to_string())?
+        .into_iter()
+        .next()
+        .unwrap();
+
+    match account {
+        None => Err(format!("Proposal {} does not exist", proposal_address)),
+        Some(account) if account.owner != nobilitydao::id() => Err(format!(
+            "Proposal {} is not owned by the program",
+            proposal_address
+        )),
+        Some(account) => try_from_account_data::<ProposalData>(&account.data).map_err(|err| {
+            format!(
+                "Failed to deserialize proposal {}: {}",
+                proposal_address, err
+            )
+        }),
+    }
+}
+
+fn get_election(rpc_client: &RpcClient, election_address: &Pubkey) -> Result<ElectionData, String> {
+    let account = rpc_client
+        .get_multiple_accounts(&[*election_address])
+        .map_err(|err| err.to_string())?
+        .into_iter()
+        .next()
+        .unwrap();
+
+    match account {
+        None => Err(format!("Election {} does not exist", election_address)),
+        Some(account) if account.owner != nobilitydao::id() => Err(format!(
+            "Election {} is not owned by the program",
+            election_address
+        )),
+        Some(account) => try_from_account_data::<ElectionData>(&account.data).map_err(|err| {
+            format!(
+                "Failed to deserialize election {}: {}",
+                election_address, err
+            )
+        }),
+    }
+}
+
+/// Get the titles listed in a house's holdings, none if the house never held any.
+fn get_holdings(rpc_client: &RpcClient, house_address: &Pubkey) -> Result<Vec<Pubkey>, String> {
+    let holdings_address = nobilitydao::get_holdings_address(house_address);
+    let account = rpc_client
+        .get_multiple_accounts(&[holdings_address])
+        .map_err(|err| err.to_string())?
+        .into_iter()
+        .next()
+        .unwrap();
+
+    match account {
+        None => Ok(vec![]),
+        Some(account) if account.owner != nobilitydao::id() => Err(format!(
+            "Holdings {} are not owned by the program",
+            holdings_address
+        )),
+        Some(account) => try_from_account_data::<HoldingsData>(&account.data)
+            .map(|holdings| holdings.title_addresses)
+            .map_err(|err| {
+                format!(
+                    "Failed to deserialize holdings {}: {}",
+                    holdings_address, err
+                )
+            }),
+    }
+}
+
+fn get_title(rpc_client: &RpcClient, title_address: &Pubkey) -> Result<TitleData, String> {
+    let account = rpc_client
+        .get_multiple_accounts(&[*title_address])
+        .map_err(|err| err.to_string())?
+        .into_iter()
+        .next()
+        .unwrap();
+
+    match account {
+        None => Err(format!("Title {} does not exist", title_address)),
+        Some(account) if account.owner != nobilitydao::id() => Err(format!(
+            "Title {} is not owned by the program",
+            title_address
+        )),
+        Some(account) => try_from_account_data::<TitleData>(&account.data)
+            .map_err(|err| format!("Failed to deserialize title {}: {}", title_address, err)),
+    }
+}
+
+fn get_realm_config(
+    rpc_client: &RpcClient,
+    realm_config_address: &Pubkey,
+) -> Result<RealmConfigData, String> {
+    let account = rpc_client
+        .get_multiple_accounts(&[*realm_config_address])
+        .map_err(|err| err.to_string())?
+        .into_iter()
+        .next()
+        .unwrap();
+
+    match account {
+        None => Err(format!(
+            "Realm config {} does not exist",
+            realm_config_address
+        )),
+        Some(account) if account.owner != nobilitydao::id() => Err(format!(
+            "Realm config {} is not owned by the program",
+            realm_config_address
+        )),
+        Some(account) => try_from_account_data::<RealmConfigData>(&account.data).map_err(|err| {
+            format!(
+                "Failed to deserialize realm config {}: {}",
+                realm_config_address, err
+            )
+        }),
+    }
+}
+
+/// The user's house: the one given with --house, or the one derived from the user's
+/// wallet.
+fn house_address_of(arg_matches: &ArgMatches, user_address: &Pubkey) -> Pubkey {
+    pubkey_of(arg_matches, "house").unwrap_or_else(|| nobilitydao::get_house_address(user_address))
+}
+
+/// Fields of a new title given with --rank, --kind, --required-stake-lamports,
+/// --coat-of-arms and --display-name.
+fn title_params_of(arg_matches: &ArgMatches) -> TitleParams {
+    TitleParams {
+        rank: value_t_or_exit!(arg_matches, "rank", Rank),
+        kind: value_t_or_exit!(arg_matches, "kind", Kind),
+        required_stake_lamports: value_t_or_exit!(arg_matches, "required_stake_lamports", u64),
+        coat_of_arms: arg_matches.value_of("coat_of_arms").unwrap().to_string(),
+        display_name: arg_matches.value_of("display_name").unwrap().to_string(),
+    }
+}
+
+/// Address of the realm named with --realm.
+fn realm_address_of(arg_matches: &ArgMatches) -> Result<Pubkey, String> {
+    arg_matches
+        .value_of("realm")
+        .map(nobilitydao::get_realm_address)
+        .ok_or_else(|| "No realm given, use --realm".to_string())
+}
+
+fn get_realm(rpc_client: &RpcClient, realm_address: &Pubkey) -> Result<RealmData, String> {
+    let account = rpc_client
+        .get_multiple_accounts(&[*realm_address])
+        .map_err(|err| err.to_string())?
+        .into_iter()
+        .next()
+        .unwrap();
+
+    match account {
+        None => Err(format!("Realm {} does not exist", realm_address)),
+        Some(account) if account.owner != nobilitydao::id() => Err(format!(
+            "Realm {} is not owned by the program",
+            realm_address
+        )),
+        Some(account) => try_from_account_data::<RealmData>(&account.data)
+            .map_err(|err| format!("Failed to deserialize realm {}: {}", realm_address, err)),
+    }
+}
+
+fn get_vassals(
+    rpc_client: &RpcClient,
+    title_address: &Pubkey,
+    vassal_count: u16,
+) -> Result<Vec<Pubkey>, String> {
+    let page_count = (vassal_count as usize + VassalPageData::VASSALS_PER_PAGE - 1)
+        / VassalPageData::VASSALS_PER_PAGE;
+    let page_addresses: Vec<Pubkey> = (0..page_count as u16)
+        .map(|page_index| nobilitydao::get_vassal_page_address(title_address, page_index))
+        .collect();
+    let mut vassals = vec![];
+    // The RPC caps the number of accounts fetched at once.
+    for chunk in page_addresses.chunks(100) {
+        let accounts = rpc_client
+            .get_multiple_accounts(chunk)
+            .map_err(|err| err.to_string())?;
+        for (page_address, account) in chunk.iter().zip(accounts) {
+            let account =
+                account.ok_or_else(|| format!("Vassal page {} does not exist", page_address))?;
+            let page = try_from_account_data::<VassalPageData>(&account.data).map_err(|err| {
+                format!("Failed to deserialize vassal page {}: {}", page_address, err)
+            })?;
+            // Vassals that moved to another liege are left as all-zero addresses.
+            vassals.extend(
+                page.vassal_addresses
+                    .into_iter()
+                    .filter(|address| *address != Pubkey::new(&[0; 32])),
+            );
+        }
+    }
+    Ok(vassals)
+}
+
+fn print_title(
+    rpc_client: &RpcClient,
+    title_address: &Pubkey,
+    titledata: &TitleData,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let coa_url = unpack_string(&titledata.coat_of_arms)?;
+    let display_name = unpack_string(&titledata.display_name)?;
+    println!("Display Name: {}", display_name);
+    println!("Coat of Arms: {}", coa_url);
+    println!("Rank: {}", titledata.rank);
+    println!("Kind: {}", titledata.kind);
+    if titledata.realm_address == Pubkey::new(&[0; 32]) {
+        println!("Realm: legacy");
+    } else {
+        println!("Realm: {}", titledata.realm_address);
+    }
+    println!(
+        "Required stake (SOL): {}",
+        lamports_to_sol(titledata.required_stake_lamports)
+    );
+    if titledata.sale_price_lamports == TitleData::NOT_FOR_SALE {
+        println!("Sale price (SOL): not for sale");
+    } else {
+        println!(
+            "Sale price (SOL): {}",
+            lamports_to_sol(titledata.sale_price_lamports)
+        );
+    }
+    println!("Holder: {}", titledata.holder_house_address);
+    println!("Succession law: {}", titledata.succession_law);
+    if titledata.lifecycle_state == TitleData::ACTIVE_STATE {
+        println!("State: Active");
+        println!("Stake account: {}", titledata.stake_address);
+    } else {
+        println!("State: Inactive");
+    }
+    if titledata.liege_address != Pubkey::new(&[0; 32]) {
+        println!("Liege: {}", titledata.liege_address);
     }
+    for vassal_address in get_vassals(rpc_client, title_address, titledata.vassal_count)?.iter() {
+        println!("Vassal: {}", vassal_address);
+    }
+    Ok(())
+}
+
+/// Send a transaction, replacing custom program errors with the TitleError message.
+fn send_transaction(
+    rpc_client: &RpcClient,
+    transaction: &Transaction,
+) -> Result<(), Box<dyn std::error::Error>> {
+    rpc_client
+        .send_and_confirm_transaction_with_spinner(transaction)
+        .map_err(|err| match err.get_transaction_error() {
+            Some(TransactionError::InstructionError(
+                index,
+                InstructionError::Custom(code),
+            )) => match <TitleError as DecodeError<TitleError>>::decode_custom_error_to_enum(code) {
+                Some(title_error) => format!(
+                    "Instruction {} failed: {}: {}",
+                    index,
+                    <TitleError as DecodeError<TitleError>>::type_of(),
+                    title_error
+                )
+                .into(),
+                None => err.into(),
+            },
+            _ => err.into(),
+        })?;
     Ok(())
 }
 
 fn create_house(
     rpc_client: &RpcClient,
     user_keypair: &Keypair,
+    governance_token_supply: u16,
     coat_of_arms_str: &str,
     display_name_str: &str,
 ) -> Result<(), Box<dyn std::error::Error>> {
     let house_addr = nobilitydao::get_house_address(&user_keypair.pubkey());
     println!("House Address: {}", house_addr);
 
-    let mut transaction = Transaction::new_with_payer(
-        &[nobilitydao::instruction::create_house(
+    // The governance tokens are minted to a new token account of the user.
+    let token_account_keypair = Keypair::new();
+    let mut signers = vec![user_keypair as &dyn Signer];
+    let instruction = if governance_token_supply > 1 {
+        println!("Token Account: {}", token_account_keypair.pubkey());
+        signers.push(&token_account_keypair);
+        nobilitydao::instruction::create_token_governed_house(
+            &user_keypair.pubkey(),
+            &house_addr,
+            &token_account_keypair.pubkey(),
+            governance_token_supply,
+            coat_of_arms_str.to_string(),
+            display_name_str.to_string(),
+        )
+    } else {
+        nobilitydao::instruction::create_house(
             &user_keypair.pubkey(),
             &house_addr,
             coat_of_arms_str.to_string(),
             display_name_str.to_string(),
+        )
+    };
+    let mut transaction = Transaction::new_with_payer(&[instruction], Some(&user_keypair.pubkey()));
+    let blockhash = rpc_client.get_recent_blockhash()?.0;
+    transaction.try_sign(&signers, blockhash)?;
+
+    send_transaction(rpc_client, &transaction)?;
+    println!("Done creating house!");
+    Ok(())
+}
+
+fn update_house(
+    rpc_client: &RpcClient,
+    user_keypair: &Keypair,
+    house_addr: &Pubkey,
+    coat_of_arms_str: &str,
+) -> Result<(), Box<dyn std::error::Error>> {
+    println!("House Address: {}", house_addr);
+
+    let mut transaction = Transaction::new_with_payer(
+        &[nobilitydao::instruction::update_house(
+            &user_keypair.pubkey(),
+            house_addr,
+            coat_of_arms_str.to_string(),
         )],
         Some(&user_keypair.pubkey()),
     );
     let blockhash = rpc_client.get_recent_blockhash()?.0;
     transaction.try_sign(&[user_keypair], blockhash)?;
 
-    rpc_client.send_and_confirm_transaction_with_spinner(&transaction)?;
-    println!("Done creating house!");
+    send_transaction(rpc_client, &transaction)?;
+    println!("Done updating house!");
+    Ok(())
+}
+
+/// Propose an instruction signed by the house's governance wallet to the token holders
+/// of the house.
+fn propose(
+    rpc_client: &RpcClient,
+    user_keypair: &Keypair,
+    house_addr: &Pubkey,
+    instruction: Instruction,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let proposal_index = get_house(rpc_client, house_addr)?.proposal_count;
+    println!(
+        "Proposal Address: {}",
+        nobilitydao::get_proposal_address(house_addr, proposal_index)
+    );
+
+    send_instruction(
+        rpc_client,
+        user_keypair,
+        nobilitydao::instruction::create_proposal(
+            &user_keypair.pubkey(),
+            house_addr,
+            proposal_index,
+            &instruction,
+        ),
+    )?;
+    println!("Done proposing!");
+    Ok(())
+}
+
+/// Send a transaction of the single instruction, paid and signed by the user.
+fn send_instruction(
+    rpc_client: &RpcClient,
+    user_keypair: &Keypair,
+    instruction: Instruction,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let mut transaction = Transaction::new_with_payer(&[instruction], Some(&user_keypair.pubkey()));
+    let blockhash = rpc_client.get_recent_blockhash()?.0;
+    transaction.try_sign(&[user_keypair], blockhash)?;
+    send_transaction(rpc_client, &transaction)
+}
+
+fn withdraw_from_house(
+    rpc_client: &RpcClient,
+    user_keypair: &Keypair,
+    house_addr: &Pubkey,
+    recipient_address: &Pubkey,
+    lamports: u64,
+) -> Result<(), Box<dyn std::error::Error>> {
+    println!("House Address: {}", house_addr);
+
+    let mut transaction = Transaction::new_with_payer(
+        &[nobilitydao::instruction::withdraw_from_house(
+            &user_keypair.pubkey(),
+            house_addr,
+            recipient_address,
+            lamports,
+        )],
+        Some(&user_keypair.pubkey()),
+    );
+    let blockhash = rpc_client.get_recent_blockhash()?.0;
+    transaction.try_sign(&[user_keypair], blockhash)?;
+
+    send_transaction(rpc_client, &transaction)?;
+    println!("Done withdrawing {} lamports!", lamports);
+    Ok(())
+}
+
+fn withdraw_from_treasury(
+    rpc_client: &RpcClient,
+    authority_keypair: &Keypair,
+    realm_address: &Pubkey,
+    recipient_address: &Pubkey,
+    lamports: u64,
+) -> Result<(), Box<dyn std::error::Error>> {
+    println!(
+        "Treasury Address: {}",
+        nobilitydao::get_treasury_address(realm_address)
+    );
+
+    let mut transaction = Transaction::new_with_payer(
+        &[nobilitydao::instruction::withdraw_from_treasury(
+            &authority_keypair.pubkey(),
+            realm_address,
+            recipient_address,
+            lamports,
+        )],
+        Some(&authority_keypair.pubkey()),
+    );
+    let blockhash = rpc_client.get_recent_blockhash()?.0;
+    transaction.try_sign(&[authority_keypair], blockhash)?;
+
+    send_transaction(rpc_client, &transaction)?;
+    println!("Done withdrawing {} lamports!", lamports);
+    Ok(())
+}
+
+fn close_house(
+    rpc_client: &RpcClient,
+    user_keypair: &Keypair,
+    house_addr: &Pubkey,
+) -> Result<(), Box<dyn std::error::Error>> {
+    println!("House Address: {}", house_addr);
+
+    let mut transaction = Transaction::new_with_payer(
+        &[nobilitydao::instruction::close_house(
+            &user_keypair.pubkey(),
+            house_addr,
+        )],
+        Some(&user_keypair.pubkey()),
+    );
+    let blockhash = rpc_client.get_recent_blockhash()?.0;
+    transaction.try_sign(&[user_keypair], blockhash)?;
+
+    send_transaction(rpc_client, &transaction)?;
+    println!("Done closing house!");
+    Ok(())
+}
+
+fn transfer_house_authority(
+    rpc_client: &RpcClient,
+    user_keypair: &Keypair,
+    house_addr: &Pubkey,
+    new_authority: &Pubkey,
+) -> Result<(), Box<dyn std::error::Error>> {
+    println!("House Address: {}", house_addr);
+    println!("New Authority: {}", new_authority);
+
+    let mut transaction = Transaction::new_with_payer(
+        &[nobilitydao::instruction::transfer_house_authority(
+            &user_keypair.pubkey(),
+            house_addr,
+            new_authority,
+        )],
+        Some(&user_keypair.pubkey()),
+    );
+    let blockhash = rpc_client.get_recent_blockhash()?.0;
+    transaction.try_sign(&[user_keypair], blockhash)?;
+
+    send_transaction(rpc_client, &transaction)?;
+    println!("Done transferring house authority!");
+    Ok(())
+}
+
+/// Move all titles listed in the holdings of a house to its heir, a batch per
+/// transaction.
+fn trigger_succession(
+    rpc_client: &RpcClient,
+    user_keypair: &Keypair,
+    house_addr: &Pubkey,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let housedata = get_house(rpc_client, house_addr)?;
+    if housedata.heir_house_address == Pubkey::new(&[0; 32]) {
+        return Err(format!("House {} has no heir", house_addr).into());
+    }
+    println!("Heir: {}", housedata.heir_house_address);
+
+    // Elective titles with vassals are elected instead, see open-election.
+    let mut title_addresses = vec![];
+    for title_address in get_holdings(rpc_client, house_addr)? {
+        let titledata = get_title(rpc_client, &title_address)?;
+        if titledata.succession_law == SuccessionLaw::Elective && titledata.live_vassal_count > 0 {
+            println!("Title {} is elective, open its election", title_address);
+        } else {
+            title_addresses.push(title_address);
+        }
+    }
+    if title_addresses.is_empty() {
+        println!("House holds no listed titles that pass to the heir");
+        return Ok(());
+    }
+    for batch in title_addresses.chunks(SUCCESSION_BATCH_SIZE) {
+        send_instruction(
+            rpc_client,
+            user_keypair,
+            nobilitydao::instruction::trigger_succession(
+                &user_keypair.pubkey(),
+                house_addr,
+                &housedata.heir_house_address,
+                batch,
+            ),
+        )?;
+        println!("Moved {} titles", batch.len());
+    }
+    println!("Done triggering succession!");
     Ok(())
 }
 
 fn create_title(
     rpc_client: &RpcClient,
     user_keypair: &Keypair,
-    liege_address: &Pubkey,
-    rank: u8,
-    kind: u8,
-    required_stake_lamports: u64,
-    liege_vassal_index: u8,
-    coat_of_arms_str: &str,
-    display_name_str: &str,
+    house_addr: &Pubkey,
+    realm_address: &Pubkey,
+    slot: VassalSlot,
+    params: TitleParams,
 ) -> Result<(), Box<dyn std::error::Error>> {
-    let house_addr = nobilitydao::get_house_address(&user_keypair.pubkey());
-    let new_title_addr = nobilitydao::get_title_address(liege_address, liege_vassal_index);
+    let new_title_addr = if slot.liege_address == Pubkey::new(&[0; 32]) {
+        nobilitydao::get_root_title_address(realm_address)
+    } else {
+        nobilitydao::get_title_address(&slot.liege_address, slot.liege_vassal_index)
+    };
     println!("House Address: {}", house_addr);
     println!("New title Address: {}", new_title_addr);
 
     let mut transaction = Transaction::new_with_payer(
         &[nobilitydao::instruction::create_title(
             &user_keypair.pubkey(),
-            &house_addr,
+            house_addr,
             &new_title_addr,
-            liege_address,
-            rank,
-            kind,
-            required_stake_lamports,
-            liege_vassal_index,
-            coat_of_arms_str.to_string(),
-            display_name_str.to_string(),
+            realm_address,
+            slot,
+            params,
         )],
         Some(&user_keypair.pubkey()),
     );
     let blockhash = rpc_client.get_recent_blockhash()?.0;
     transaction.try_sign(&[user_keypair], blockhash)?;
 
-    rpc_client.send_and_confirm_transaction_with_spinner(&transaction)?;
+    send_transaction(rpc_client, &transaction)?;
     println!("Done creating title!");
     Ok(())
 }
+
+fn create_realm(
+    rpc_client: &RpcClient,
+    user_keypair: &Keypair,
+    name: &str,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let realm_addr = nobilitydao::get_realm_address(name);
+    println!("Realm Address: {}", realm_addr);
+
+    let mut transaction = Transaction::new_with_payer(
+        &[nobilitydao::instruction::create_realm(
+            &user_keypair.pubkey(),
+            name.to_string(),
+        )],
+        Some(&user_keypair.pubkey()),
+    );
+    let blockhash = rpc_client.get_recent_blockhash()?.0;
+    transaction.try_sign(&[user_keypair], blockhash)?;
+
+    send_transaction(rpc_client, &transaction)?;
+    println!("Done creating realm!");
+    Ok(())
+}
+
+fn update_title(
+    rpc_client: &RpcClient,
+    user_keypair: &Keypair,
+    house_addr: &Pubkey,
+    title_address: &Pubkey,
+    coat_of_arms_str: &str,
+) -> Result<(), Box<dyn std::error::Error>> {
+    println!("House Address: {}", house_addr);
+    println!("Title Address: {}", title_address);
+
+    let mut transaction = Transaction::new_with_payer(
+        &[nobilitydao::instruction::update_title_coat_of_arms(
+            &user_keypair.pubkey(),
+            house_addr,
+            title_address,
+            coat_of_arms_str.to_string(),
+        )],
+        Some(&user_keypair.pubkey()),
+    );
+    let blockhash = rpc_client.get_recent_blockhash()?.0;
+    transaction.try_sign(&[user_keypair], blockhash)?;
+
+    send_transaction(rpc_client, &transaction)?;
+    println!("Done updating title!");
+    Ok(())
+}
+
+fn petition_vassalage(
+    rpc_client: &RpcClient,
+    user_keypair: &Keypair,
+    house_addr: &Pubkey,
+    liege_address: &Pubkey,
+    params: TitleParams,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let petition_addr = nobilitydao::get_petition_address(liege_address, house_addr);
+    println!("Petition Address: {}", petition_addr);
+
+    let mut transaction = Transaction::new_with_payer(
+        &[nobilitydao::instruction::petition_vassalage(
+            &user_keypair.pubkey(),
+            house_addr,
+            liege_address,
+            params,
+        )],
+        Some(&user_keypair.pubkey()),
+    );
+    let blockhash = rpc_client.get_recent_blockhash()?.0;
+    transaction.try_sign(&[user_keypair], blockhash)?;
+
+    send_transaction(rpc_client, &transaction)?;
+    println!("Done petitioning!");
+    Ok(())
+}
+
+fn answer_petition(
+    rpc_client: &RpcClient,
+    user_keypair: &Keypair,
+    instruction: Instruction,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let mut transaction =
+        Transaction::new_with_payer(&[instruction], Some(&user_keypair.pubkey()));
+    let blockhash = rpc_client.get_recent_blockhash()?.0;
+    transaction.try_sign(&[user_keypair], blockhash)?;
+
+    send_transaction(rpc_client, &transaction)?;
+    println!("Done answering petition!");
+    Ok(())
+}
+
+fn reparent_title(
+    rpc_client: &RpcClient,
+    user_keypair: &Keypair,
+    new_liege_holder_keypair: &Keypair,
+    title_address: &Pubkey,
+    new_liege_address: &Pubkey,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let titledata = get_title(rpc_client, title_address)?;
+    let new_liege = get_title(rpc_client, new_liege_address)?;
+    println!("Title Address: {}", title_address);
+    println!("Old Liege: {}", titledata.liege_address);
+    println!("New Liege: {}", new_liege_address);
+
+    let mut transaction = Transaction::new_with_payer(
+        &[nobilitydao::instruction::reparent_title(
+            &user_keypair.pubkey(),
+            &titledata.holder_house_address,
+            title_address,
+            VassalSlot {
+                liege_address: titledata.liege_address,
+                liege_vassal_index: titledata.liege_vassal_index,
+            },
+            &new_liege_holder_keypair.pubkey(),
+            &new_liege.holder_house_address,
+            VassalSlot {
+                liege_address: *new_liege_address,
+                liege_vassal_index: new_liege.vassal_count,
+            },
+        )],
+        Some(&user_keypair.pubkey()),
+    );
+    let blockhash = rpc_client.get_recent_blockhash()?.0;
+    if user_keypair.pubkey() == new_liege_holder_keypair.pubkey() {
+        transaction.try_sign(&[user_keypair], blockhash)?;
+    } else {
+        transaction.try_sign(&[user_keypair, new_liege_holder_keypair], blockhash)?;
+    }
+
+    send_transaction(rpc_client, &transaction)?;
+    println!("Done moving title!");
+    Ok(())
+}
+
+fn escheat_title(
+    rpc_client: &RpcClient,
+    user_keypair: &Keypair,
+    title_address: &Pubkey,
+    holder_wallet_address: Option<Pubkey>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let titledata = get_title(rpc_client, title_address)?;
+    let liege = get_title(rpc_client, &titledata.liege_address)?;
+    println!("Title Address: {}", title_address);
+    println!("Liege: {}", titledata.liege_address);
+
+    // Active titles return their remaining stake to the holder's wallet.
+    let active = if titledata.lifecycle_state == TitleData::ACTIVE_STATE {
+        let holder_wallet_address = holder_wallet_address
+            .ok_or_else(|| "Title is active, give the holder's wallet with --holder-wallet")?;
+        Some((titledata.stake_address, holder_wallet_address))
+    } else {
+        None
+    };
+
+    let mut transaction = Transaction::new_with_payer(
+        &[nobilitydao::instruction::escheat_title(
+            &user_keypair.pubkey(),
+            &liege.holder_house_address,
+            &titledata.liege_address,
+            title_address,
+            &titledata.holder_house_address,
+            active
+                .as_ref()
+                .map(|(stake_address, holder_wallet_address)| {
+                    (stake_address, holder_wallet_address)
+                }),
+        )],
+        Some(&user_keypair.pubkey()),
+    );
+    let blockhash = rpc_client.get_recent_blockhash()?.0;
+    transaction.try_sign(&[user_keypair], blockhash)?;
+
+    send_transaction(rpc_client, &transaction)?;
+    println!("Done escheating title!");
+    Ok(())
+}
@@ -18,6 +18,214 @@ pub enum TitleError {
     /// Data type mismatched
     #[error("Data type length mismatched")]
     DataTypeMismatch,
+
+    /// Account holds a different type of data than expected
+    #[error("Account type mismatched")]
+    AccountTypeMismatch,
+
+    /// Account is not owned by the program
+    #[error("Account not owned by the program")]
+    IncorrectAccountOwner,
+
+    /// Rank is not one of the known ranks
+    #[error("Invalid title rank")]
+    InvalidRank,
+
+    /// Kind is not one of the known kinds
+    #[error("Invalid title kind")]
+    InvalidKind,
+
+    /// A root title was given a liege
+    #[error("Root title must have no liege")]
+    RootTitleHasLiege,
+
+    /// A non-root title was given no liege
+    #[error("Title below the root must have a liege")]
+    MissingLiege,
+
+    /// New title's rank is not below the rank of its liege
+    #[error("Title rank must be below the rank of its liege")]
+    RankNotBelowLiege,
+
+    /// Vassal index is not the next free index of the liege
+    #[error("Vassal index is not the liege's next vassal index")]
+    IncorrectVassalIndex,
+
+    /// Liege already has the maximum number of vassals
+    #[error("Liege has too many vassals")]
+    TooManyVassals,
+
+    /// Account data has not been initialized
+    #[error("Account is uninitialized")]
+    UninitializedAccount,
+
+    /// Account must be writable
+    #[error("Account is not writable")]
+    AccountNotWritable,
+
+    /// String does not fit in MAX_STRING_LENGTH bytes with a null terminator
+    #[error("String too long")]
+    StringTooLong,
+
+    /// Buyer's house already holds the title
+    #[error("House already holds this title")]
+    AlreadyTitleHolder,
+
+    /// Title is not for sale
+    #[error("Title is not for sale")]
+    NotForSale,
+
+    /// Offered price differs from the title's sale price
+    #[error("Offered price does not match sale price")]
+    SalePriceMismatch,
+
+    /// Sale price is below the title's required stake
+    #[error("Sale price below required stake")]
+    SalePriceBelowRequiredStake,
+
+    /// Title has already been activated
+    #[error("Title is already active")]
+    TitleAlreadyActive,
+
+    /// Title has not been activated
+    #[error("Title is not active")]
+    TitleNotActive,
+
+    /// Stake account is not the title's stake vault
+    #[error("Incorrect stake account")]
+    IncorrectStakeAccount,
+
+    /// Liege account is not the title's liege
+    #[error("Incorrect liege account")]
+    IncorrectLiege,
+
+    /// Tax must be collected before the title is sold or repriced
+    #[error("Tax not settled, collect tax first")]
+    TaxNotSettled,
+
+    /// Account is already at the current version
+    #[error("Account already at current version")]
+    AccountAlreadyCurrent,
+
+    /// Withdrawal exceeds the lamports above rent exemption
+    #[error("Insufficient lamports above rent exemption")]
+    InsufficientLamports,
+
+    /// String is not valid UTF-8
+    #[error("String is not valid UTF-8")]
+    InvalidUtf8,
+
+    /// String contains a control character
+    #[error("String contains a control character")]
+    StringHasControlCharacter,
+
+    /// Coat of arms is not an https, ipfs or ar URI
+    #[error("Coat of arms must be an https://, ipfs:// or ar:// URI")]
+    UnsupportedUriScheme,
+
+    /// Realm settings are out of range
+    #[error("Invalid realm settings")]
+    InvalidRealmSettings,
+
+    /// Title rank is below the realm's lowest allowed rank
+    #[error("Rank not allowed by realm config")]
+    RankNotAllowed,
+
+    /// Title kind is not allowed by the realm
+    #[error("Kind not allowed by realm config")]
+    KindNotAllowed,
+
+    /// Realm name is empty or too long to be an address seed
+    #[error("Realm name must be 1 to 32 bytes")]
+    InvalidRealmName,
+
+    /// Account belongs to a different realm
+    #[error("Realm mismatch")]
+    RealmMismatch,
+
+    /// Signer is not the authority of the realm
+    #[error("Not the realm authority")]
+    NotRealmAuthority,
+
+    /// Realm already has its root title
+    #[error("Realm already has a root title")]
+    RootTitleExists,
+
+    /// Wallet is not the one of the petitioning house
+    #[error("Incorrect petitioner")]
+    IncorrectPetitioner,
+
+    /// Title already has the given liege
+    #[error("Title is already a vassal of this liege")]
+    AlreadyVassal,
+
+    /// Title is neither inactive past its grace period nor short of stake
+    #[error("Title is not delinquent")]
+    NotDelinquent,
+
+    /// House can't be closed while it holds titles
+    #[error("House still holds titles")]
+    HouseHoldsTitles,
+
+    /// Governance token supply is zero
+    #[error("Governance token supply must be at least 1")]
+    InvalidGovernanceTokenSupply,
+
+    /// House is governed by a single wallet, not by proposals
+    #[error("House is not governed by tokens")]
+    NotTokenGoverned,
+
+    /// Proposal index is not the next free index of the house
+    #[error("Proposal index is not the house's next proposal index")]
+    IncorrectProposalIndex,
+
+    /// Proposal has already been executed
+    #[error("Proposal already executed")]
+    ProposalExecuted,
+
+    /// Proposal lacks the approval of a majority of the governance tokens
+    #[error("Proposal not approved")]
+    ProposalNotApproved,
+
+    /// House has not named an heir
+    #[error("House has no heir")]
+    NoHeir,
+
+    /// House proved life within its succession or vacancy period
+    #[error("Succession is not due")]
+    SuccessionNotDue,
+
+    /// Invalid succession law
+    #[error("Invalid succession law")]
+    InvalidSuccessionLaw,
+
+    /// Title passes by election, not to the heir of its holder
+    #[error("Title is elective")]
+    TitleIsElective,
+
+    /// Title does not pass by election
+    #[error("Title is not elective")]
+    NotElective,
+
+    /// Election period is over
+    #[error("Election has ended")]
+    ElectionEnded,
+
+    /// No candidate has won the election yet
+    #[error("Election is undecided")]
+    ElectionUndecided,
+
+    /// The same title was listed more than once
+    #[error("Duplicate title")]
+    DuplicateTitle,
+
+    /// Title is not listed in the house's holdings
+    #[error("Title is not in the holdings")]
+    TitleNotInHoldings,
+
+    /// Proposal's voting deadline has passed
+    #[error("Proposal voting has ended")]
+    VotingEnded,
 }
 impl From<TitleError> for ProgramError {
     fn from(e: TitleError) -> Self {
@@ -1,52 +1,573 @@
 //! Program instructions
 
-use crate::id;
+use crate::{
+    get_election_address, get_governance_address, get_governance_mint_address,
+    get_holdings_address, get_petition_address, get_proposal_address, get_proposal_escrow_address,
+    get_realm_address, get_realm_config_address, get_root_title_address, get_stake_address,
+    get_title_address, get_treasury_address, get_vassal_page_address, get_vote_address, id,
+    state::{
+        Kind, ProposalData, ProposedAccount, Rank, RealmSettings, SuccessionLaw, VassalPageData,
+    },
+};
 use borsh::{BorshDeserialize, BorshSerialize};
 use solana_program::{
     instruction::{AccountMeta, Instruction},
     pubkey::Pubkey,
+    sysvar,
 };
 
 /// Instructions supported by the program
 #[derive(Clone, Debug, BorshSerialize, BorshDeserialize, PartialEq)]
 pub enum TitleInstruction {
-    /// Create a new record
+    /// Create a new record. The house is derived from the creating wallet, which
+    /// becomes its authority. Houses with more than one governance token are governed
+    /// by their token holders instead: the whole supply is minted to the creating
+    /// wallet, and the house's governance wallet becomes its authority.
     ///
     /// Accounts expected by this instruction:
     ///
     /// 0. `[writable, signer]` Wallet account for account creator
     /// 1. `[writable]` New house account (will be signed by program)
     /// 2. `[]` System program ID
+    /// 3. `[]` Governance wallet of the house, at get_governance_address. Only for
+    ///    houses governed by tokens, as are the following accounts.
+    /// 4. `[writable]` New governance mint account, at get_governance_mint_address
+    ///    (will be signed by program)
+    /// 5. `[writable, signer]` New token account of the creating wallet, receives the
+    ///    governance token supply
+    /// 6. `[]` SPL token program ID
+    /// 7. `[]` Rent sysvar
     CreateHouse{
-        /// Coat of arms URI. Last byte must be 0.
+        /// Coat of arms URI, see state::validate_coat_of_arms.
         coat_of_arms: String,
-        /// Display name for the house. Last byte must be 0.
+        /// Display name for the house, see state::validate_display_name.
         display_name: String,
+        /// See HouseData.governance_token_supply.
+        governance_token_supply: u16,
     },
     /// Create a new record
     ///
     /// Accounts expected by this instruction:
     ///
     /// 0. `[writable, signer]` Wallet account for title creator
-    /// 1. `[]` House account for title creator (will be signed by program)
-    /// 2. `[writable]` New title account (will be signed by program)
+    /// 1. `[writable]` House account for title creator (will be signed by program)
+    /// 2. `[writable]` New title account (will be signed by program). The root title of
+    ///    a realm is at get_root_title_address, other titles at get_title_address.
     /// 3. `[writable]` Liege title account (will be signed by program)
+    /// 4. `[]` System program ID
+    /// 5. `[]` Realm config account
+    /// 6. `[]` Realm account of the new title. Must be the liege's realm. Writable for
+    ///    the root title, which only the realm authority or the realm config admin may
+    ///    create, and which is recorded in the realm.
+    /// 7. `[writable]` Liege's vassal page holding the new vassal index (created by the
+    ///    program if it is the first vassal of the page). Omitted for the root title.
+    /// 8. `[writable]` Holdings account of the creator's house, at get_holdings_address
+    ///    (created by the program if needed). 7 for the root title.
     CreateTitle{
         /// See TitleData.rank.
-        rank: u8,
+        rank: Rank,
         /// See TitleData.kind.
-        kind: u8,
+        kind: Kind,
         /// Required stake for holder of this title; will also be initial sale price.
         required_stake_lamports: u64,
-        /// Coat of arms URI. Last byte must be 0. Maximum length: 128.
+        /// Coat of arms URI, see state::validate_coat_of_arms.
         coat_of_arms: String,
-        /// Display name for the house. Last byte must be 0. Maximum length: 128.
+        /// Display name for the title, see state::validate_display_name.
         display_name: String,
         /// Address of liege title. All zeroes for root title.
         liege_address: Pubkey,
-        /// Index of the title into the liege's vassal vector.
-        liege_vassal_index : u8,
-    }
+        /// Index of the title into the liege's vassal list. Zero for root title.
+        liege_vassal_index: u16,
+    },
+    /// Buy a title from its current holder at the advertised sale price. The stake of an
+    /// active title is refunded to the current holder, and the buyer stakes the title's
+    /// required stake in its place. Titles held by their realm's treasury, see
+    /// ConcludeElection, take the treasury in place of the holder's wallet and house.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    /// 0. `[writable, signer]` Wallet account for buyer
+    /// 1. `[writable]` House account for buyer
+    /// 2. `[writable]` Title account
+    /// 3. `[writable]` Wallet account for current holder, receives the sale price and
+    ///    the refunded stake
+    /// 4. `[]` System program ID
+    /// 5. `[]` Realm config account
+    /// 6. `[writable]` Treasury of the title's realm, receives the protocol fee
+    /// 7. `[writable]` House account for current holder
+    /// 8. `[writable]` Holdings account of the buyer's house (created by the program if
+    ///    needed)
+    /// 9. `[writable]` Holdings account of the current holder's house
+    /// 10. `[writable]` Stake account of the title, for active titles only
+    BuyTitle{
+        /// Sale price the buyer agreed to pay. Must match TitleData.sale_price_lamports,
+        /// so that the holder can't change the price out from under the buyer.
+        sale_price_lamports: u64,
+    },
+    /// Activate an inactive title by funding its stake vault with the title's required
+    /// stake.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    /// 0. `[writable, signer]` Wallet account for title holder, funds the stake
+    /// 1. `[]` House account for title holder
+    /// 2. `[writable]` Title account
+    /// 3. `[writable]` New stake vault account (will be signed by program)
+    /// 4. `[]` System program ID
+    ActivateTitle,
+    /// Set the advertised sale price of a title, or delist it.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    /// 0. `[signer]` Wallet account for title holder
+    /// 1. `[]` House account for title holder
+    /// 2. `[writable]` Title account
+    SetSalePrice{
+        /// New sale price. Must be at least TitleData.required_stake_lamports. None marks
+        /// the title as not for sale.
+        sale_price_lamports: Option<u64>,
+    },
+    /// Collect the Harberger tax owed on an active title, paying it from the title's
+    /// stake vault to the holder of the liege title, or to the realm treasury for the
+    /// root title. If the vault can't cover the tax, the title is foreclosed: the vault
+    /// is drained, the title becomes inactive and passes to the liege's holder. Anybody
+    /// may call this.
+    ///
+    /// Holders keep their deposit topped up by transferring lamports to the stake vault.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    /// 0. `[writable]` Title account
+    /// 1. `[writable]` Stake vault account of the title
+    /// 2. `[writable]` Tax recipient: house account holding the liege title, or the realm
+    ///    treasury for the root title
+    /// 3. `[]` Realm config account
+    /// 4. `[]` Liege title account. Omitted for the root title.
+    /// 5. `[writable]` House account holding the title. Omitted for the root title.
+    /// 6. `[writable]` Holdings account of the house holding the title. Omitted for the
+    ///    root title.
+    /// 7. `[writable]` Holdings account of the tax recipient's house, paid from the
+    ///    stake on foreclosure (created by the program if needed). Unused when the realm
+    ///    treasury holds the liege title. Omitted for the root title.
+    /// 8. `[]` System program ID. Omitted for the root title.
+    CollectTax,
+    /// Withdraw lamports a house collected, such as the tax of its vassal titles, above
+    /// the rent exemption of the house.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    /// 0. `[signer]` Wallet account governing the house
+    /// 1. `[writable]` House account
+    /// 2. `[writable]` Account receiving the lamports
+    WithdrawFromHouse {
+        /// Lamports to withdraw.
+        lamports: u64,
+    },
+    /// Update a house's coat of arms
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    /// 0. `[signer]` Wallet account governing the house
+    /// 1. `[writable]` House account
+    UpdateHouse{
+        /// Coat of arms URI, see state::validate_coat_of_arms.
+        coat_of_arms: String,
+    },
+    /// Update a title's coat of arms
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    /// 0. `[signer]` Wallet account for title holder
+    /// 1. `[]` House account for title holder
+    /// 2. `[writable]` Title account
+    UpdateTitleCoatOfArms{
+        /// Coat of arms URI, see state::validate_coat_of_arms.
+        coat_of_arms: String,
+    },
+    /// Rewrite a house, title, realm or realm config account written by an older
+    /// version of the program in the current layout, resizing the account as needed.
+    /// Anybody may call this.
+    ///
+    /// Version 1 and 2 titles kept their vassal addresses in the title itself; migrating
+    /// one moves them to new vassal pages. Migrated titles keep their address, even
+    /// though their addresses were derived from a different vassal index seed.
+    /// Titles before version 4 predate realms and are migrated into the legacy realm,
+    /// whose address is all zeroes. Titles before version 5 count the vassals left in
+    /// their vassal pages. Migrated inactive titles start their escheat grace period,
+    /// and titles before version 6 pass to the heir, see SuccessionLaw. Realm configs get
+    /// the default settings added since their version: the escheat grace period in
+    /// version 2, the election and vacancy periods in version 3. Version 1 realms
+    /// record their root title if it exists.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    /// 0. `[writable, signer]` Wallet account funding any additional rent
+    /// 1. `[writable]` House, title, realm or realm config account to migrate
+    /// 2. `[]` System program ID
+    /// 3. ..3+N `[writable]` For version 1 and 2 titles with vassals, the title's N new
+    ///    vassal pages (will be signed by program)
+    /// 3. ..3+N `[]` For later titles with vassals, the title's N vassal pages
+    /// 3. `[]` For version 1 realms, the realm's root title account
+    MigrateAccount,
+    /// Create the realm config. Only the upgrade authority of the program may call this,
+    /// once.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    /// 0. `[writable, signer]` Upgrade authority of the program, funds the config
+    /// 1. `[writable]` New realm config account (will be signed by program)
+    /// 2. `[]` ProgramData account of the program
+    /// 3. `[]` System program ID
+    InitializeRealmConfig {
+        /// Wallet allowed to update the config.
+        admin: Pubkey,
+        /// Initial rules of the realm.
+        settings: RealmSettings,
+    },
+    /// Update the realm config, or hand it to a new admin.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    /// 0. `[signer]` Current realm config admin
+    /// 1. `[writable]` Realm config account
+    UpdateRealmConfig {
+        /// Wallet allowed to update the config from now on.
+        admin: Pubkey,
+        /// New rules of the realm.
+        settings: RealmSettings,
+    },
+    /// Withdraw lamports from the treasury of a realm, above its rent exemption. Only
+    /// the realm authority or the realm config admin may call this, and only the admin
+    /// for the legacy realm.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    /// 0. `[signer]` Wallet account of the realm authority or the realm config admin
+    /// 1. `[]` Realm config account
+    /// 2. `[]` Realm account. The system program ID for the legacy realm.
+    /// 3. `[writable]` Treasury of the realm (will be signed by program)
+    /// 4. `[writable]` Account receiving the lamports
+    /// 5. `[]` System program ID
+    WithdrawFromTreasury {
+        /// Lamports to withdraw.
+        lamports: u64,
+    },
+    /// Create a new realm, an independent hierarchy of titles.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    /// 0. `[writable, signer]` Wallet account for realm creator, funds the realm
+    /// 1. `[writable]` New realm account, at get_realm_address (will be signed by program)
+    /// 2. `[]` System program ID
+    CreateRealm {
+        /// Name of the realm, see state::validate_realm_name.
+        name: String,
+    },
+    /// Petition to hold a new vassal title under a liege title. The petitioner pays the
+    /// rent of the petition and of the new title, which is refunded if the petition is
+    /// rejected.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    /// 0. `[writable, signer]` Wallet account for petitioner
+    /// 1. `[]` House account for petitioner
+    /// 2. `[writable]` New petition account, at get_petition_address (will be signed by
+    ///    program)
+    /// 3. `[]` Liege title account
+    /// 4. `[]` System program ID
+    /// 5. `[]` Realm config account
+    PetitionVassalage {
+        /// See TitleData.rank. Must be below the liege's rank.
+        rank: Rank,
+        /// See TitleData.kind.
+        kind: Kind,
+        /// Required stake for holder of this title; will also be initial sale price.
+        required_stake_lamports: u64,
+        /// Coat of arms URI, see state::validate_coat_of_arms.
+        coat_of_arms: String,
+        /// Display name for the title, see state::validate_display_name.
+        display_name: String,
+    },
+    /// Accept a petition: create the petitioned title as the next vassal of the liege,
+    /// held by the petitioning house, and close the petition.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    /// 0. `[writable, signer]` Wallet account for liege holder, funds the vassal page
+    /// 1. `[]` House account for liege holder
+    /// 2. `[writable]` Liege title account
+    /// 3. `[writable]` Petition account
+    /// 4. `[writable]` Wallet account for petitioner, receives the petition's rent
+    /// 5. `[writable]` New title account, at get_title_address (will be signed by
+    ///    program)
+    /// 6. `[writable]` Liege's vassal page holding the new vassal index (created by the
+    ///    program if it is the first vassal of the page)
+    /// 7. `[]` System program ID
+    /// 8. `[]` Realm config account
+    /// 9. `[writable]` House account for petitioner
+    /// 10. `[writable]` Holdings account of the petitioner's house, paid from the
+    ///     petition (created by the program if needed)
+    AcceptPetition {
+        /// Index of the new title into the liege's vassal list. Must be the liege's
+        /// vassal count.
+        liege_vassal_index: u16,
+    },
+    /// Reject a petition, or withdraw it when signed by the petitioner, and close it.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    /// 0. `[signer]` Wallet account for liege holder or petitioner
+    /// 1. `[]` House account for the signing wallet
+    /// 2. `[]` Liege title account
+    /// 3. `[writable]` Petition account
+    /// 4. `[writable]` Wallet account for petitioner, receives the petition's lamports
+    /// 5. `[]` House account for petitioner
+    RejectPetition,
+    /// Move a title, with its own vassals, to another liege of the same realm, with the
+    /// consent of the title's holder and of the new liege's holder. The title keeps its
+    /// address, derived from TitleData.seed_liege_address and seed_liege_vassal_index,
+    /// so from then on it is found through its liege's vassal pages rather than
+    /// get_title_address. Its entry in the old liege's vassal list is left all zeroes,
+    /// and it is appended to the new liege's vassal list.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    /// 0. `[signer]` Wallet account for title holder
+    /// 1. `[]` House account for title holder
+    /// 2. `[writable]` Title account
+    /// 3. `[writable]` Old liege's vassal page holding the title's current index
+    /// 4. `[writable, signer]` Wallet account for new liege holder, funds the vassal
+    ///    page
+    /// 5. `[]` House account for new liege holder
+    /// 6. `[writable]` New liege title account
+    /// 7. `[writable]` New liege's vassal page holding the new vassal index (created by
+    ///    the program if it is the first vassal of the page)
+    /// 8. `[]` System program ID
+    /// 9. `[]` Realm config account
+    /// 10. `[writable]` Old liege title account
+    ReparentTitle {
+        /// Index of the title into the new liege's vassal list. Must be the new
+        /// liege's vassal count.
+        new_liege_vassal_index: u16,
+    },
+    /// Reclaim a delinquent vassal title for the house holding its liege. A title is
+    /// delinquent when it has been inactive for the realm's escheat grace period, or
+    /// when it is active and tax has brought its stake below the required stake, see
+    /// TitleData::is_stake_delinquent. In the latter case its tax must be collected
+    /// first, in the same slot, and the remaining stake goes back to the holder. The
+    /// title reverts to an inactive title at its required stake price. Titles held by
+    /// their realm's treasury take the treasury in place of the holder's house.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    /// 0. `[writable, signer]` Wallet account for liege holder, funds its house's
+    ///    holdings
+    /// 1. `[writable]` House account for liege holder
+    /// 2. `[]` Liege title account
+    /// 3. `[writable]` Title account
+    /// 4. `[writable]` House account for title holder
+    /// 5. `[]` Realm config account
+    /// 6. `[writable]` Holdings account of the title holder's house
+    /// 7. `[writable]` Holdings account of the liege holder's house (created by the
+    ///    program if needed)
+    /// 8. `[]` System program ID
+    /// 9. `[writable]` Stake account of the title, for active titles only
+    /// 10. `[writable]` Wallet account for title holder, receives the remaining stake,
+    ///     for active titles only
+    EscheatTitle,
+    /// Close a house that holds no titles, returning its lamports to its wallet.
+    /// Houses created before the program counted held titles can't be closed.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    /// 0. `[writable, signer]` Wallet account governing the house
+    /// 1. `[writable]` House account
+    /// 2. `[writable]` Holdings account of the house, closed too if it exists
+    CloseHouse,
+    /// Hand the government of a house to another wallet. The house keeps its address,
+    /// which stays derived from the wallet that created it.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    /// 0. `[signer]` Wallet account governing the house
+    /// 1. `[writable]` House account
+    TransferHouseAuthority {
+        /// Wallet to govern the house from now on. Must not be all zeroes.
+        new_authority: Pubkey,
+    },
+    /// Propose an instruction of the program to the token holders of a house, to be
+    /// signed by the house's governance wallet once they approve it. Anybody may
+    /// propose, paying the rent of the proposal and of its escrow. Voting ends
+    /// ProposalData::VOTING_PERIOD_SLOTS after the proposal is made.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    /// 0. `[writable, signer]` Wallet account for proposer
+    /// 1. `[writable]` House account
+    /// 2. `[writable]` New proposal account, at get_proposal_address (will be signed by
+    ///    program)
+    /// 3. `[writable]` New escrow token account of the proposal, at
+    ///    get_proposal_escrow_address (will be signed by program)
+    /// 4. `[]` Governance mint of the house
+    /// 5. `[]` System program ID
+    /// 6. `[]` SPL token program ID
+    /// 7. `[]` Rent sysvar
+    CreateProposal {
+        /// Index of the proposal among the house's proposals. Must be the house's
+        /// proposal count.
+        proposal_index: u32,
+        /// See ProposalData.accounts.
+        accounts: Vec<ProposedAccount>,
+        /// See ProposalData.data.
+        data: Vec<u8>,
+    },
+    /// Vote for a proposal by depositing governance tokens in its escrow, before its
+    /// voting deadline. Votes add up when a wallet votes again.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    /// 0. `[writable, signer]` Wallet account for voter, owns the tokens and funds the
+    ///    vote
+    /// 1. `[writable]` Proposal account
+    /// 2. `[writable]` Vote account of the voter, at get_vote_address (created by the
+    ///    program on the first vote)
+    /// 3. `[writable]` Token account of the voter to deposit from
+    /// 4. `[writable]` Escrow token account of the proposal
+    /// 5. `[]` SPL token program ID
+    /// 6. `[]` System program ID
+    CastVote {
+        /// Governance tokens to deposit.
+        tokens: u64,
+    },
+    /// Withdraw the governance tokens deposited by a voter and close the vote. Takes
+    /// back the approval of a proposal that hasn't been executed or ended yet.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    /// 0. `[writable, signer]` Wallet account for voter, receives the vote's rent
+    /// 1. `[writable]` Proposal account
+    /// 2. `[writable]` Vote account of the voter
+    /// 3. `[writable]` Token account receiving the tokens
+    /// 4. `[writable]` Escrow token account of the proposal
+    /// 5. `[]` SPL token program ID
+    WithdrawVote,
+    /// Execute a proposal approved by a majority of the governance tokens, signing its
+    /// instruction with the house's governance wallet. Anybody may call this, once,
+    /// before the proposal's voting deadline and while the house is still governed by
+    /// its governance wallet.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    /// 0. `[writable]` Proposal account
+    /// 1. `[]` House account
+    /// 2. `[]` This program's ID
+    /// 3. ..3+N `[]` The N accounts of the proposed instruction, in order, writable
+    ///    and signing as proposed, except for the governance wallet which the program
+    ///    signs for
+    ExecuteProposal,
+    /// Name the heir of a house, which inherits its titles once the house goes without
+    /// proving life for the succession period, see TriggerSuccession. Also proves life.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    /// 0. `[signer]` Wallet account governing the house
+    /// 1. `[writable]` House account
+    /// 2. `[]` House account of the heir, unless the heir is all zeroes
+    SetHeir {
+        /// House to inherit the titles. All zeroes to name no heir.
+        heir_house_address: Pubkey,
+        /// Slots the house may go without proving life. Must not be zero.
+        succession_period_slots: u64,
+    },
+    /// Prove that a house is still governed, restarting its succession period.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    /// 0. `[signer]` Wallet account governing the house
+    /// 1. `[writable]` House account
+    ProveLife,
+    /// Move titles of a house that went without proving life for its succession period
+    /// to its heir, as their succession laws allow. Elective titles with vassals can't be
+    /// moved, see OpenElection. Anybody may call this, in as many batches of titles as
+    /// needed. Only titles listed in the house's holdings pass, each listed once; titles
+    /// held since before holdings were recorded stay with the house.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    /// 0. `[writable, signer]` Wallet account for funder, pays for growing the heir's
+    ///    holdings
+    /// 1. `[writable]` House account
+    /// 2. `[writable]` Holdings account of the house
+    /// 3. `[writable]` House account of the heir
+    /// 4. `[writable]` Holdings account of the heir (created by the program if needed)
+    /// 5. `[]` System program ID
+    /// 6. ..6+N `[writable]` The N title accounts held by the house to move
+    TriggerSuccession,
+    /// Change how a title passes on once its holder's house stops proving life.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    /// 0. `[signer]` Wallet account governing the title holder's house
+    /// 1. `[]` House account for title holder
+    /// 2. `[writable]` Title account
+    SetSuccessionLaw {
+        /// See TitleData.succession_law.
+        succession_law: SuccessionLaw,
+    },
+    /// Open the election of the next holder of an elective title whose holder's house
+    /// went without proving life for the realm's vacancy period, whether or not it named
+    /// an heir. Anybody may call this, paying the rent of the election.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    /// 0. `[writable, signer]` Wallet account for funder
+    /// 1. `[]` Title account
+    /// 2. `[]` House account for title holder
+    /// 3. `[writable]` New election account, at get_election_address (will be signed by
+    ///    program)
+    /// 4. `[]` System program ID
+    /// 5. `[]` Realm config account
+    OpenElection,
+    /// Vote for the next holder of a title with one of its vassals, or change the
+    /// vassal's vote.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    /// 0. `[writable, signer]` Wallet account governing the vassal holder's house, pays
+    ///    for growing the election
+    /// 1. `[]` House account for vassal holder
+    /// 2. `[]` Vassal title account
+    /// 3. `[writable]` Election account
+    /// 4. `[]` House account of the candidate
+    /// 5. `[]` Realm config account
+    /// 6. `[]` System program ID
+    CastBallot {
+        /// House to vote for. Must not be the house vacating the title.
+        candidate_house_address: Pubkey,
+    },
+    /// Hand an elective title to the winner of its election and close the election.
+    /// Closes the election without handing the title on if it lapsed. An election that
+    /// ended without ballots hands the title to the heir of the vacating house. Without
+    /// an heir, the title reverts to an inactive title held by the realm's treasury and
+    /// listed for sale at its required stake, and the remaining stake of an active title
+    /// goes to the treasury. Anybody may call this.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    /// 0. `[writable, signer]` Wallet account for caller, receives the election's rent
+    ///    and pays for growing the winner's holdings
+    /// 1. `[writable]` Title account
+    /// 2. `[writable]` Election account
+    /// 3. `[writable]` House account for title holder
+    /// 4. `[writable]` Holdings account of the title holder's house
+    /// 5. `[writable]` House account of the winner, or the realm's treasury account
+    /// 6. `[writable]` Holdings account of the winner (created by the program if
+    ///    needed)
+    /// 7. `[]` Realm config account
+    /// 8. `[]` System program ID
+    /// 9. `[writable]` Stake account of the title, for active titles passing to the
+    ///    treasury
+    ConcludeElection,
 }
 
 /// Create a new CreateHouse instruction.
@@ -66,21 +587,19 @@ pub fn create_house(
         data: TitleInstruction::CreateHouse {
             coat_of_arms: coat_of_arms,
             display_name: display_name,
+            governance_token_supply: 1,
         }
         .try_to_vec().unwrap(),
     }
 }
 
-/// Create a new CreateTitle instruction.
-pub fn create_title(
+/// Create a new CreateHouse instruction for a house governed by tokens.
+/// token_account_address is a new account, which must sign, receiving the supply.
+pub fn create_token_governed_house(
     user_wallet_address: &Pubkey,
     house_address: &Pubkey,
-    new_title_address: &Pubkey,
-    liege_address: &Pubkey,
-    rank: u8,
-    kind: u8,
-    required_stake_lamports: u64,
-    liege_vassal_index: u8,
+    token_account_address: &Pubkey,
+    governance_token_supply: u16,
     coat_of_arms: String,
     display_name: String,
 ) -> Instruction {
@@ -89,19 +608,855 @@ pub fn create_title(
         accounts: vec![
             AccountMeta::new(*user_wallet_address, true),
             AccountMeta::new(*house_address, false),
-            AccountMeta::new(*new_title_address, false),
-            AccountMeta::new(*liege_address, false),
             AccountMeta::new_readonly(solana_program::system_program::id(), false),
+            AccountMeta::new_readonly(get_governance_address(house_address), false),
+            AccountMeta::new(get_governance_mint_address(house_address), false),
+            AccountMeta::new(*token_account_address, true),
+            AccountMeta::new_readonly(spl_token::id(), false),
+            AccountMeta::new_readonly(sysvar::rent::id(), false),
         ],
+        data: TitleInstruction::CreateHouse {
+            coat_of_arms,
+            display_name,
+            governance_token_supply,
+        }
+        .try_to_vec()
+        .unwrap(),
+    }
+}
+
+/// Fields of a new title chosen by its creator or petitioner, for create_title and
+/// petition_vassalage.
+#[derive(Clone, Debug, PartialEq)]
+pub struct TitleParams {
+    /// See TitleData.rank.
+    pub rank: Rank,
+    /// See TitleData.kind.
+    pub kind: Kind,
+    /// Required stake for holder of this title; will also be initial sale price.
+    pub required_stake_lamports: u64,
+    /// Coat of arms URI, see state::validate_coat_of_arms.
+    pub coat_of_arms: String,
+    /// Display name for the title, see state::validate_display_name.
+    pub display_name: String,
+}
+
+/// Place of a title in the list of its liege's vassals, for create_title and
+/// reparent_title.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct VassalSlot {
+    /// Address of the liege title. All zeroes for root titles.
+    pub liege_address: Pubkey,
+    /// Index of the title into the liege's vassal list. Zero for root titles.
+    pub liege_vassal_index: u16,
+}
+
+/// Create a new CreateTitle instruction. For a vassal title, the slot's index must be
+/// the liege's current vassal count.
+pub fn create_title(
+    user_wallet_address: &Pubkey,
+    house_address: &Pubkey,
+    new_title_address: &Pubkey,
+    realm_address: &Pubkey,
+    slot: VassalSlot,
+    params: TitleParams,
+) -> Instruction {
+    let mut accounts = vec![
+        AccountMeta::new(*user_wallet_address, true),
+        AccountMeta::new(*house_address, false),
+        AccountMeta::new(*new_title_address, false),
+        AccountMeta::new(slot.liege_address, false),
+        AccountMeta::new_readonly(solana_program::system_program::id(), false),
+        AccountMeta::new_readonly(get_realm_config_address(), false),
+    ];
+    if slot.liege_address == Pubkey::new(&[0; 32]) {
+        accounts.push(AccountMeta::new(*realm_address, false));
+    } else {
+        accounts.push(AccountMeta::new_readonly(*realm_address, false));
+        let (page_index, _) = VassalPageData::locate(slot.liege_vassal_index);
+        accounts.push(AccountMeta::new(
+            get_vassal_page_address(&slot.liege_address, page_index),
+            false,
+        ));
+    }
+    accounts.push(AccountMeta::new(get_holdings_address(house_address), false));
+    Instruction {
+        program_id: id(),
+        accounts,
         data: TitleInstruction::CreateTitle {
-            rank: rank,
-            kind: kind,
-            required_stake_lamports: required_stake_lamports,
-            coat_of_arms: coat_of_arms,
-            display_name: display_name,
-            liege_address: *liege_address,
-            liege_vassal_index: liege_vassal_index,
+            rank: params.rank,
+            kind: params.kind,
+            required_stake_lamports: params.required_stake_lamports,
+            coat_of_arms: params.coat_of_arms,
+            display_name: params.display_name,
+            liege_address: slot.liege_address,
+            liege_vassal_index: slot.liege_vassal_index,
+        }
+        .try_to_vec()
+        .unwrap(),
+    }
+}
+
+/// Create a new BuyTitle instruction. realm_address is the realm of the title.
+/// holder_house_address is the house currently holding it.
+pub fn buy_title(
+    buyer_wallet_address: &Pubkey,
+    buyer_house_address: &Pubkey,
+    title_address: &Pubkey,
+    holder_wallet_address: &Pubkey,
+    holder_house_address: &Pubkey,
+    realm_address: &Pubkey,
+    sale_price_lamports: u64,
+) -> Instruction {
+    Instruction {
+        program_id: id(),
+        accounts: vec![
+            AccountMeta::new(*buyer_wallet_address, true),
+            AccountMeta::new(*buyer_house_address, false),
+            AccountMeta::new(*title_address, false),
+            AccountMeta::new(*holder_wallet_address, false),
+            AccountMeta::new_readonly(solana_program::system_program::id(), false),
+            AccountMeta::new_readonly(get_realm_config_address(), false),
+            AccountMeta::new(get_treasury_address(realm_address), false),
+            AccountMeta::new(*holder_house_address, false),
+            AccountMeta::new(get_holdings_address(buyer_house_address), false),
+            AccountMeta::new(get_holdings_address(holder_house_address), false),
+            AccountMeta::new(get_stake_address(title_address), false),
+        ],
+        data: TitleInstruction::BuyTitle {
+            sale_price_lamports,
         }
         .try_to_vec().unwrap(),
     }
-}
\ No newline at end of file
+}
+
+/// Create a new ActivateTitle instruction.
+pub fn activate_title(
+    user_wallet_address: &Pubkey,
+    house_address: &Pubkey,
+    title_address: &Pubkey,
+    stake_address: &Pubkey,
+) -> Instruction {
+    Instruction {
+        program_id: id(),
+        accounts: vec![
+            AccountMeta::new(*user_wallet_address, true),
+            AccountMeta::new_readonly(*house_address, false),
+            AccountMeta::new(*title_address, false),
+            AccountMeta::new(*stake_address, false),
+            AccountMeta::new_readonly(solana_program::system_program::id(), false),
+        ],
+        data: TitleInstruction::ActivateTitle.try_to_vec().unwrap(),
+    }
+}
+
+/// Create a new SetSalePrice instruction.
+pub fn set_sale_price(
+    user_wallet_address: &Pubkey,
+    house_address: &Pubkey,
+    title_address: &Pubkey,
+    sale_price_lamports: Option<u64>,
+) -> Instruction {
+    Instruction {
+        program_id: id(),
+        accounts: vec![
+            AccountMeta::new_readonly(*user_wallet_address, true),
+            AccountMeta::new_readonly(*house_address, false),
+            AccountMeta::new(*title_address, false),
+        ],
+        data: TitleInstruction::SetSalePrice {
+            sale_price_lamports,
+        }
+        .try_to_vec().unwrap(),
+    }
+}
+
+/// Create a new CollectTax instruction. liege is the liege title and the house
+/// holding the title, None for the root title.
+pub fn collect_tax(
+    title_address: &Pubkey,
+    stake_address: &Pubkey,
+    recipient_address: &Pubkey,
+    liege: Option<(&Pubkey, &Pubkey)>,
+) -> Instruction {
+    let mut accounts = vec![
+        AccountMeta::new(*title_address, false),
+        AccountMeta::new(*stake_address, false),
+        AccountMeta::new(*recipient_address, false),
+        AccountMeta::new_readonly(get_realm_config_address(), false),
+    ];
+    if let Some((liege_address, holder_house_address)) = liege {
+        accounts.push(AccountMeta::new_readonly(*liege_address, false));
+        accounts.push(AccountMeta::new(*holder_house_address, false));
+        accounts.push(AccountMeta::new(
+            get_holdings_address(holder_house_address),
+            false,
+        ));
+        accounts.push(AccountMeta::new(
+            get_holdings_address(recipient_address),
+            false,
+        ));
+        accounts.push(AccountMeta::new_readonly(
+            solana_program::system_program::id(),
+            false,
+        ));
+    }
+    Instruction {
+        program_id: id(),
+        accounts,
+        data: TitleInstruction::CollectTax.try_to_vec().unwrap(),
+    }
+}
+
+/// Create a new WithdrawFromHouse instruction.
+pub fn withdraw_from_house(
+    user_wallet_address: &Pubkey,
+    house_address: &Pubkey,
+    recipient_address: &Pubkey,
+    lamports: u64,
+) -> Instruction {
+    Instruction {
+        program_id: id(),
+        accounts: vec![
+            AccountMeta::new_readonly(*user_wallet_address, true),
+            AccountMeta::new(*house_address, false),
+            AccountMeta::new(*recipient_address, false),
+        ],
+        data: TitleInstruction::WithdrawFromHouse { lamports }
+            .try_to_vec()
+            .unwrap(),
+    }
+}
+
+/// Create a new UpdateHouse instruction.
+pub fn update_house(
+    user_wallet_address: &Pubkey,
+    house_address: &Pubkey,
+    coat_of_arms: String,
+) -> Instruction {
+    Instruction {
+        program_id: id(),
+        accounts: vec![
+            AccountMeta::new_readonly(*user_wallet_address, true),
+            AccountMeta::new(*house_address, false),
+        ],
+        data: TitleInstruction::UpdateHouse { coat_of_arms }
+            .try_to_vec()
+            .unwrap(),
+    }
+}
+
+/// Create a new UpdateTitleCoatOfArms instruction.
+pub fn update_title_coat_of_arms(
+    user_wallet_address: &Pubkey,
+    house_address: &Pubkey,
+    title_address: &Pubkey,
+    coat_of_arms: String,
+) -> Instruction {
+    Instruction {
+        program_id: id(),
+        accounts: vec![
+            AccountMeta::new_readonly(*user_wallet_address, true),
+            AccountMeta::new_readonly(*house_address, false),
+            AccountMeta::new(*title_address, false),
+        ],
+        data: TitleInstruction::UpdateTitleCoatOfArms { coat_of_arms }
+            .try_to_vec()
+            .unwrap(),
+    }
+}
+
+/// Create a new MigrateAccount instruction. vassal_page_count is the number of vassal
+/// pages needed by a version 1 or 2 title, the number of vassal pages of a title at a
+/// later version, and zero for houses.
+pub fn migrate_account(
+    funder_wallet_address: &Pubkey,
+    account_address: &Pubkey,
+    vassal_page_count: u16,
+) -> Instruction {
+    let mut accounts = vec![
+        AccountMeta::new(*funder_wallet_address, true),
+        AccountMeta::new(*account_address, false),
+        AccountMeta::new_readonly(solana_program::system_program::id(), false),
+    ];
+    for page_index in 0..vassal_page_count {
+        accounts.push(AccountMeta::new(
+            get_vassal_page_address(account_address, page_index),
+            false,
+        ));
+    }
+    Instruction {
+        program_id: id(),
+        accounts,
+        data: TitleInstruction::MigrateAccount.try_to_vec().unwrap(),
+    }
+}
+
+/// Create a new MigrateAccount instruction for a version 1 realm.
+pub fn migrate_realm(funder_wallet_address: &Pubkey, realm_address: &Pubkey) -> Instruction {
+    Instruction {
+        program_id: id(),
+        accounts: vec![
+            AccountMeta::new(*funder_wallet_address, true),
+            AccountMeta::new(*realm_address, false),
+            AccountMeta::new_readonly(solana_program::system_program::id(), false),
+            AccountMeta::new_readonly(get_root_title_address(realm_address), false),
+        ],
+        data: TitleInstruction::MigrateAccount.try_to_vec().unwrap(),
+    }
+}
+
+/// Create a new InitializeRealmConfig instruction, signed by the program's upgrade
+/// authority.
+pub fn initialize_realm_config(
+    upgrade_authority_address: &Pubkey,
+    admin: &Pubkey,
+    settings: RealmSettings,
+) -> Instruction {
+    let (program_data_address, _) = Pubkey::find_program_address(
+        &[id().as_ref()],
+        &solana_program::bpf_loader_upgradeable::id(),
+    );
+    Instruction {
+        program_id: id(),
+        accounts: vec![
+            AccountMeta::new(*upgrade_authority_address, true),
+            AccountMeta::new(get_realm_config_address(), false),
+            AccountMeta::new_readonly(program_data_address, false),
+            AccountMeta::new_readonly(solana_program::system_program::id(), false),
+        ],
+        data: TitleInstruction::InitializeRealmConfig {
+            admin: *admin,
+            settings,
+        }
+        .try_to_vec()
+        .unwrap(),
+    }
+}
+
+/// Create a new UpdateRealmConfig instruction.
+pub fn update_realm_config(
+    admin_address: &Pubkey,
+    new_admin: &Pubkey,
+    settings: RealmSettings,
+) -> Instruction {
+    Instruction {
+        program_id: id(),
+        accounts: vec![
+            AccountMeta::new_readonly(*admin_address, true),
+            AccountMeta::new(get_realm_config_address(), false),
+        ],
+        data: TitleInstruction::UpdateRealmConfig {
+            admin: *new_admin,
+            settings,
+        }
+        .try_to_vec()
+        .unwrap(),
+    }
+}
+
+/// Create a new WithdrawFromTreasury instruction. realm_address is all zeroes for the
+/// legacy realm.
+pub fn withdraw_from_treasury(
+    authority_wallet_address: &Pubkey,
+    realm_address: &Pubkey,
+    recipient_address: &Pubkey,
+    lamports: u64,
+) -> Instruction {
+    Instruction {
+        program_id: id(),
+        accounts: vec![
+            AccountMeta::new_readonly(*authority_wallet_address, true),
+            AccountMeta::new_readonly(get_realm_config_address(), false),
+            AccountMeta::new_readonly(*realm_address, false),
+            AccountMeta::new(get_treasury_address(realm_address), false),
+            AccountMeta::new(*recipient_address, false),
+            AccountMeta::new_readonly(solana_program::system_program::id(), false),
+        ],
+        data: TitleInstruction::WithdrawFromTreasury { lamports }
+            .try_to_vec()
+            .unwrap(),
+    }
+}
+
+/// Create a new CreateRealm instruction.
+pub fn create_realm(creator_wallet_address: &Pubkey, name: String) -> Instruction {
+    Instruction {
+        program_id: id(),
+        accounts: vec![
+            AccountMeta::new(*creator_wallet_address, true),
+            AccountMeta::new(get_realm_address(&name), false),
+            AccountMeta::new_readonly(solana_program::system_program::id(), false),
+        ],
+        data: TitleInstruction::CreateRealm { name }.try_to_vec().unwrap(),
+    }
+}
+
+/// Create a new PetitionVassalage instruction.
+pub fn petition_vassalage(
+    petitioner_wallet_address: &Pubkey,
+    petitioner_house_address: &Pubkey,
+    liege_address: &Pubkey,
+    params: TitleParams,
+) -> Instruction {
+    Instruction {
+        program_id: id(),
+        accounts: vec![
+            AccountMeta::new(*petitioner_wallet_address, true),
+            AccountMeta::new_readonly(*petitioner_house_address, false),
+            AccountMeta::new(
+                get_petition_address(liege_address, petitioner_house_address),
+                false,
+            ),
+            AccountMeta::new_readonly(*liege_address, false),
+            AccountMeta::new_readonly(solana_program::system_program::id(), false),
+            AccountMeta::new_readonly(get_realm_config_address(), false),
+        ],
+        data: TitleInstruction::PetitionVassalage {
+            rank: params.rank,
+            kind: params.kind,
+            required_stake_lamports: params.required_stake_lamports,
+            coat_of_arms: params.coat_of_arms,
+            display_name: params.display_name,
+        }
+        .try_to_vec()
+        .unwrap(),
+    }
+}
+
+/// Create a new AcceptPetition instruction. liege_vassal_index must be the liege's
+/// current vassal count.
+pub fn accept_petition(
+    liege_holder_wallet_address: &Pubkey,
+    liege_holder_house_address: &Pubkey,
+    liege_address: &Pubkey,
+    petitioner_wallet_address: &Pubkey,
+    petitioner_house_address: &Pubkey,
+    liege_vassal_index: u16,
+) -> Instruction {
+    let (page_index, _) = VassalPageData::locate(liege_vassal_index);
+    Instruction {
+        program_id: id(),
+        accounts: vec![
+            AccountMeta::new(*liege_holder_wallet_address, true),
+            AccountMeta::new_readonly(*liege_holder_house_address, false),
+            AccountMeta::new(*liege_address, false),
+            AccountMeta::new(
+                get_petition_address(liege_address, petitioner_house_address),
+                false,
+            ),
+            AccountMeta::new(*petitioner_wallet_address, false),
+            AccountMeta::new(get_title_address(liege_address, liege_vassal_index), false),
+            AccountMeta::new(get_vassal_page_address(liege_address, page_index), false),
+            AccountMeta::new_readonly(solana_program::system_program::id(), false),
+            AccountMeta::new_readonly(get_realm_config_address(), false),
+            AccountMeta::new(*petitioner_house_address, false),
+            AccountMeta::new(get_holdings_address(petitioner_house_address), false),
+        ],
+        data: TitleInstruction::AcceptPetition { liege_vassal_index }
+            .try_to_vec()
+            .unwrap(),
+    }
+}
+
+/// Create a new RejectPetition instruction. The signer is either the liege holder or
+/// the petitioner.
+pub fn reject_petition(
+    signer_wallet_address: &Pubkey,
+    signer_house_address: &Pubkey,
+    liege_address: &Pubkey,
+    petitioner_wallet_address: &Pubkey,
+    petitioner_house_address: &Pubkey,
+) -> Instruction {
+    Instruction {
+        program_id: id(),
+        accounts: vec![
+            AccountMeta::new_readonly(*signer_wallet_address, true),
+            AccountMeta::new_readonly(*signer_house_address, false),
+            AccountMeta::new_readonly(*liege_address, false),
+            AccountMeta::new(
+                get_petition_address(liege_address, petitioner_house_address),
+                false,
+            ),
+            AccountMeta::new(*petitioner_wallet_address, false),
+            AccountMeta::new_readonly(*petitioner_house_address, false),
+        ],
+        data: TitleInstruction::RejectPetition.try_to_vec().unwrap(),
+    }
+}
+
+/// Create a new ReparentTitle instruction. slot is the title's current place among its
+/// liege's vassals, new_slot's index must be the new liege's current vassal count.
+pub fn reparent_title(
+    holder_wallet_address: &Pubkey,
+    holder_house_address: &Pubkey,
+    title_address: &Pubkey,
+    slot: VassalSlot,
+    new_liege_holder_wallet_address: &Pubkey,
+    new_liege_holder_house_address: &Pubkey,
+    new_slot: VassalSlot,
+) -> Instruction {
+    let (page_index, _) = VassalPageData::locate(slot.liege_vassal_index);
+    let (new_page_index, _) = VassalPageData::locate(new_slot.liege_vassal_index);
+    Instruction {
+        program_id: id(),
+        accounts: vec![
+            AccountMeta::new_readonly(*holder_wallet_address, true),
+            AccountMeta::new_readonly(*holder_house_address, false),
+            AccountMeta::new(*title_address, false),
+            AccountMeta::new(
+                get_vassal_page_address(&slot.liege_address, page_index),
+                false,
+            ),
+            AccountMeta::new(*new_liege_holder_wallet_address, true),
+            AccountMeta::new_readonly(*new_liege_holder_house_address, false),
+            AccountMeta::new(new_slot.liege_address, false),
+            AccountMeta::new(
+                get_vassal_page_address(&new_slot.liege_address, new_page_index),
+                false,
+            ),
+            AccountMeta::new_readonly(solana_program::system_program::id(), false),
+            AccountMeta::new_readonly(get_realm_config_address(), false),
+            AccountMeta::new(slot.liege_address, false),
+        ],
+        data: TitleInstruction::ReparentTitle {
+            new_liege_vassal_index: new_slot.liege_vassal_index,
+        }
+        .try_to_vec()
+        .unwrap(),
+    }
+}
+
+/// Create a new EscheatTitle instruction. holder_house_address is the house holding
+/// the title. For active titles, stake_address and holder_wallet_address are the
+/// title's stake account and the wallet of its holder.
+pub fn escheat_title(
+    liege_holder_wallet_address: &Pubkey,
+    liege_holder_house_address: &Pubkey,
+    liege_address: &Pubkey,
+    title_address: &Pubkey,
+    holder_house_address: &Pubkey,
+    active: Option<(&Pubkey, &Pubkey)>,
+) -> Instruction {
+    let mut accounts = vec![
+        AccountMeta::new(*liege_holder_wallet_address, true),
+        AccountMeta::new(*liege_holder_house_address, false),
+        AccountMeta::new_readonly(*liege_address, false),
+        AccountMeta::new(*title_address, false),
+        AccountMeta::new(*holder_house_address, false),
+        AccountMeta::new_readonly(get_realm_config_address(), false),
+        AccountMeta::new(get_holdings_address(holder_house_address), false),
+        AccountMeta::new(get_holdings_address(liege_holder_house_address), false),
+        AccountMeta::new_readonly(solana_program::system_program::id(), false),
+    ];
+    if let Some((stake_address, holder_wallet_address)) = active {
+        accounts.push(AccountMeta::new(*stake_address, false));
+        accounts.push(AccountMeta::new(*holder_wallet_address, false));
+    }
+    Instruction {
+        program_id: id(),
+        accounts,
+        data: TitleInstruction::EscheatTitle.try_to_vec().unwrap(),
+    }
+}
+
+/// Create a new CloseHouse instruction.
+pub fn close_house(user_wallet_address: &Pubkey, house_address: &Pubkey) -> Instruction {
+    Instruction {
+        program_id: id(),
+        accounts: vec![
+            AccountMeta::new(*user_wallet_address, true),
+            AccountMeta::new(*house_address, false),
+            AccountMeta::new(get_holdings_address(house_address), false),
+        ],
+        data: TitleInstruction::CloseHouse.try_to_vec().unwrap(),
+    }
+}
+
+/// Create a new TransferHouseAuthority instruction.
+pub fn transfer_house_authority(
+    authority_wallet_address: &Pubkey,
+    house_address: &Pubkey,
+    new_authority: &Pubkey,
+) -> Instruction {
+    Instruction {
+        program_id: id(),
+        accounts: vec![
+            AccountMeta::new_readonly(*authority_wallet_address, true),
+            AccountMeta::new(*house_address, false),
+        ],
+        data: TitleInstruction::TransferHouseAuthority {
+            new_authority: *new_authority,
+        }
+        .try_to_vec()
+        .unwrap(),
+    }
+}
+
+/// Create a new CreateProposal instruction, proposing the given instruction of the
+/// program. It is signed by the house's governance wallet, see get_governance_address.
+pub fn create_proposal(
+    proposer_wallet_address: &Pubkey,
+    house_address: &Pubkey,
+    proposal_index: u32,
+    instruction: &Instruction,
+) -> Instruction {
+    let proposal_address = get_proposal_address(house_address, proposal_index);
+    let accounts = instruction
+        .accounts
+        .iter()
+        .map(|meta| ProposedAccount {
+            pubkey: meta.pubkey,
+            is_signer: meta.is_signer,
+            is_writable: meta.is_writable,
+        })
+        .collect();
+    Instruction {
+        program_id: id(),
+        accounts: vec![
+            AccountMeta::new(*proposer_wallet_address, true),
+            AccountMeta::new(*house_address, false),
+            AccountMeta::new(proposal_address, false),
+            AccountMeta::new(get_proposal_escrow_address(&proposal_address), false),
+            AccountMeta::new_readonly(get_governance_mint_address(house_address), false),
+            AccountMeta::new_readonly(solana_program::system_program::id(), false),
+            AccountMeta::new_readonly(spl_token::id(), false),
+            AccountMeta::new_readonly(sysvar::rent::id(), false),
+        ],
+        data: TitleInstruction::CreateProposal {
+            proposal_index,
+            accounts,
+            data: instruction.data.clone(),
+        }
+        .try_to_vec()
+        .unwrap(),
+    }
+}
+
+/// Create a new CastVote instruction.
+pub fn cast_vote(
+    voter_wallet_address: &Pubkey,
+    proposal_address: &Pubkey,
+    voter_token_account_address: &Pubkey,
+    tokens: u64,
+) -> Instruction {
+    Instruction {
+        program_id: id(),
+        accounts: vec![
+            AccountMeta::new(*voter_wallet_address, true),
+            AccountMeta::new(*proposal_address, false),
+            AccountMeta::new(
+                get_vote_address(proposal_address, voter_wallet_address),
+                false,
+            ),
+            AccountMeta::new(*voter_token_account_address, false),
+            AccountMeta::new(get_proposal_escrow_address(proposal_address), false),
+            AccountMeta::new_readonly(spl_token::id(), false),
+            AccountMeta::new_readonly(solana_program::system_program::id(), false),
+        ],
+        data: TitleInstruction::CastVote { tokens }.try_to_vec().unwrap(),
+    }
+}
+
+/// Create a new WithdrawVote instruction.
+pub fn withdraw_vote(
+    voter_wallet_address: &Pubkey,
+    proposal_address: &Pubkey,
+    token_account_address: &Pubkey,
+) -> Instruction {
+    Instruction {
+        program_id: id(),
+        accounts: vec![
+            AccountMeta::new(*voter_wallet_address, true),
+            AccountMeta::new(*proposal_address, false),
+            AccountMeta::new(
+                get_vote_address(proposal_address, voter_wallet_address),
+                false,
+            ),
+            AccountMeta::new(*token_account_address, false),
+            AccountMeta::new(get_proposal_escrow_address(proposal_address), false),
+            AccountMeta::new_readonly(spl_token::id(), false),
+        ],
+        data: TitleInstruction::WithdrawVote.try_to_vec().unwrap(),
+    }
+}
+
+/// Create a new SetHeir instruction.
+pub fn set_heir(
+    authority_wallet_address: &Pubkey,
+    house_address: &Pubkey,
+    heir_house_address: &Pubkey,
+    succession_period_slots: u64,
+) -> Instruction {
+    let mut accounts = vec![
+        AccountMeta::new_readonly(*authority_wallet_address, true),
+        AccountMeta::new(*house_address, false),
+    ];
+    if *heir_house_address != Pubkey::new(&[0; 32]) {
+        accounts.push(AccountMeta::new_readonly(*heir_house_address, false));
+    }
+    Instruction {
+        program_id: id(),
+        accounts,
+        data: TitleInstruction::SetHeir {
+            heir_house_address: *heir_house_address,
+            succession_period_slots,
+        }
+        .try_to_vec()
+        .unwrap(),
+    }
+}
+
+/// Create a new ProveLife instruction.
+pub fn prove_life(authority_wallet_address: &Pubkey, house_address: &Pubkey) -> Instruction {
+    Instruction {
+        program_id: id(),
+        accounts: vec![
+            AccountMeta::new_readonly(*authority_wallet_address, true),
+            AccountMeta::new(*house_address, false),
+        ],
+        data: TitleInstruction::ProveLife.try_to_vec().unwrap(),
+    }
+}
+
+/// Create a new TriggerSuccession instruction, moving the given titles of the house to
+/// its heir.
+pub fn trigger_succession(
+    funder_wallet_address: &Pubkey,
+    house_address: &Pubkey,
+    heir_house_address: &Pubkey,
+    title_addresses: &[Pubkey],
+) -> Instruction {
+    let mut accounts = vec![
+        AccountMeta::new(*funder_wallet_address, true),
+        AccountMeta::new(*house_address, false),
+        AccountMeta::new(get_holdings_address(house_address), false),
+        AccountMeta::new(*heir_house_address, false),
+        AccountMeta::new(get_holdings_address(heir_house_address), false),
+        AccountMeta::new_readonly(solana_program::system_program::id(), false),
+    ];
+    accounts.extend(
+        title_addresses
+            .iter()
+            .map(|title_address| AccountMeta::new(*title_address, false)),
+    );
+    Instruction {
+        program_id: id(),
+        accounts,
+        data: TitleInstruction::TriggerSuccession.try_to_vec().unwrap(),
+    }
+}
+
+/// Create a new ExecuteProposal instruction.
+pub fn execute_proposal(proposal_address: &Pubkey, proposal: &ProposalData) -> Instruction {
+    let governance_address = get_governance_address(&proposal.house_address);
+    let mut accounts = vec![
+        AccountMeta::new(*proposal_address, false),
+        AccountMeta::new_readonly(proposal.house_address, false),
+        AccountMeta::new_readonly(id(), false),
+    ];
+    accounts.extend(proposal.accounts.iter().map(|account| AccountMeta {
+        pubkey: account.pubkey,
+        is_signer: account.is_signer && account.pubkey != governance_address,
+        is_writable: account.is_writable,
+    }));
+    Instruction {
+        program_id: id(),
+        accounts,
+        data: TitleInstruction::ExecuteProposal.try_to_vec().unwrap(),
+    }
+}
+
+/// Create a new SetSuccessionLaw instruction.
+pub fn set_succession_law(
+    authority_wallet_address: &Pubkey,
+    holder_house_address: &Pubkey,
+    title_address: &Pubkey,
+    succession_law: SuccessionLaw,
+) -> Instruction {
+    Instruction {
+        program_id: id(),
+        accounts: vec![
+            AccountMeta::new_readonly(*authority_wallet_address, true),
+            AccountMeta::new_readonly(*holder_house_address, false),
+            AccountMeta::new(*title_address, false),
+        ],
+        data: TitleInstruction::SetSuccessionLaw { succession_law }
+            .try_to_vec()
+            .unwrap(),
+    }
+}
+
+/// Create a new OpenElection instruction.
+pub fn open_election(
+    funder_wallet_address: &Pubkey,
+    title_address: &Pubkey,
+    holder_house_address: &Pubkey,
+) -> Instruction {
+    Instruction {
+        program_id: id(),
+        accounts: vec![
+            AccountMeta::new(*funder_wallet_address, true),
+            AccountMeta::new_readonly(*title_address, false),
+            AccountMeta::new_readonly(*holder_house_address, false),
+            AccountMeta::new(get_election_address(title_address), false),
+            AccountMeta::new_readonly(solana_program::system_program::id(), false),
+            AccountMeta::new_readonly(get_realm_config_address(), false),
+        ],
+        data: TitleInstruction::OpenElection.try_to_vec().unwrap(),
+    }
+}
+
+/// Create a new CastBallot instruction.
+pub fn cast_ballot(
+    voter_wallet_address: &Pubkey,
+    voter_house_address: &Pubkey,
+    vassal_address: &Pubkey,
+    title_address: &Pubkey,
+    candidate_house_address: &Pubkey,
+) -> Instruction {
+    Instruction {
+        program_id: id(),
+        accounts: vec![
+            AccountMeta::new(*voter_wallet_address, true),
+            AccountMeta::new_readonly(*voter_house_address, false),
+            AccountMeta::new_readonly(*vassal_address, false),
+            AccountMeta::new(get_election_address(title_address), false),
+            AccountMeta::new_readonly(*candidate_house_address, false),
+            AccountMeta::new_readonly(get_realm_config_address(), false),
+            AccountMeta::new_readonly(solana_program::system_program::id(), false),
+        ],
+        data: TitleInstruction::CastBallot {
+            candidate_house_address: *candidate_house_address,
+        }
+        .try_to_vec()
+        .unwrap(),
+    }
+}
+
+/// Create a new ConcludeElection instruction. winner_house_address is the winner of the
+/// election, or the heir of the vacating house or the realm's treasury for an election
+/// that ended without ballots.
+pub fn conclude_election(
+    caller_wallet_address: &Pubkey,
+    title_address: &Pubkey,
+    holder_house_address: &Pubkey,
+    winner_house_address: &Pubkey,
+) -> Instruction {
+    Instruction {
+        program_id: id(),
+        accounts: vec![
+            AccountMeta::new(*caller_wallet_address, true),
+            AccountMeta::new(*title_address, false),
+            AccountMeta::new(get_election_address(title_address), false),
+            AccountMeta::new(*holder_house_address, false),
+            AccountMeta::new(get_holdings_address(holder_house_address), false),
+            AccountMeta::new(*winner_house_address, false),
+            AccountMeta::new(get_holdings_address(winner_house_address), false),
+            AccountMeta::new_readonly(get_realm_config_address(), false),
+            AccountMeta::new_readonly(solana_program::system_program::id(), false),
+            AccountMeta::new(get_stake_address(title_address), false),
+        ],
+        data: TitleInstruction::ConcludeElection.try_to_vec().unwrap(),
+    }
+}
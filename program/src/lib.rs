@@ -26,19 +26,228 @@ fn get_house_address_and_bump_seed_internal(
     Pubkey::find_program_address(&[&wallet_address.to_bytes()], noble_program_id)
 }
 
+/// Get the pubkey for the realm with the given name.
+pub fn get_realm_address(name: &str) -> Pubkey {
+    get_realm_address_and_bump_seed_internal(name, &id()).0
+}
+
+fn get_realm_address_and_bump_seed_internal(
+    name: &str,
+    noble_program_id: &Pubkey,
+) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[b"realm", name.as_bytes()], noble_program_id)
+}
+
+/// Get the pubkey for the root title of the given realm.
+pub fn get_root_title_address(realm_address: &Pubkey) -> Pubkey {
+    get_root_title_address_and_bump_seed_internal(realm_address, &id()).0
+}
+
+fn get_root_title_address_and_bump_seed_internal(
+    realm_address: &Pubkey,
+    noble_program_id: &Pubkey,
+) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[b"root", &realm_address.to_bytes()], noble_program_id)
+}
+
 /// Get the pubkey for the given title, using the Liege title and the vassal idnex.
-pub fn get_title_address(liege_address: &Pubkey, vassal_index: u8) -> Pubkey {
+/// A title that moved to another liege keeps the address of its first liege and
+/// index, see TitleData.seed_liege_address.
+pub fn get_title_address(liege_address: &Pubkey, vassal_index: u16) -> Pubkey {
     get_title_address_and_bump_seed_internal(liege_address, vassal_index, &id()).0
 }
 
 fn get_title_address_and_bump_seed_internal(
     liege_address: &Pubkey,
-    vassal_index: u8,
+    vassal_index: u16,
+    noble_program_id: &Pubkey,
+) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[&liege_address.to_bytes(), &vassal_index.to_le_bytes()],
+        noble_program_id,
+    )
+}
+
+/// Get the pubkey for the given page of a title's vassal list.
+pub fn get_vassal_page_address(title_address: &Pubkey, page_index: u16) -> Pubkey {
+    get_vassal_page_address_and_bump_seed_internal(title_address, page_index, &id()).0
+}
+
+fn get_vassal_page_address_and_bump_seed_internal(
+    title_address: &Pubkey,
+    page_index: u16,
     noble_program_id: &Pubkey,
 ) -> (Pubkey, u8) {
-    let vassal_index_seed: &[u8] = &[vassal_index; 32];
     Pubkey::find_program_address(
-        &[&liege_address.to_bytes(), vassal_index_seed],
+        &[
+            &title_address.to_bytes(),
+            b"vassals",
+            &page_index.to_le_bytes(),
+        ],
         noble_program_id,
     )
 }
+
+/// Get the pubkey for the stake vault of the given title.
+pub fn get_stake_address(title_address: &Pubkey) -> Pubkey {
+    get_stake_address_and_bump_seed_internal(title_address, &id()).0
+}
+
+fn get_stake_address_and_bump_seed_internal(
+    title_address: &Pubkey,
+    noble_program_id: &Pubkey,
+) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[&title_address.to_bytes(), b"stake"], noble_program_id)
+}
+
+/// Get the pubkey for the treasury of the given realm, which collects the root title's
+/// taxes and the protocol fees on the realm's title sales.
+pub fn get_treasury_address(realm_address: &Pubkey) -> Pubkey {
+    get_treasury_address_and_bump_seed_internal(realm_address, &id()).0
+}
+
+fn get_treasury_address_and_bump_seed_internal(
+    realm_address: &Pubkey,
+    noble_program_id: &Pubkey,
+) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[b"treasury", &realm_address.to_bytes()], noble_program_id)
+}
+
+/// Get the pubkey for the realm config, which holds the realm's tunable rules.
+pub fn get_realm_config_address() -> Pubkey {
+    get_realm_config_address_and_bump_seed_internal(&id()).0
+}
+
+fn get_realm_config_address_and_bump_seed_internal(noble_program_id: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[b"config"], noble_program_id)
+}
+
+/// Get the pubkey for the given house's petition to become a vassal of the given liege
+/// title.
+pub fn get_petition_address(liege_address: &Pubkey, petitioner_house_address: &Pubkey) -> Pubkey {
+    get_petition_address_and_bump_seed_internal(liege_address, petitioner_house_address, &id()).0
+}
+
+fn get_petition_address_and_bump_seed_internal(
+    liege_address: &Pubkey,
+    petitioner_house_address: &Pubkey,
+    noble_program_id: &Pubkey,
+) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[
+            b"petition",
+            &liege_address.to_bytes(),
+            &petitioner_house_address.to_bytes(),
+        ],
+        noble_program_id,
+    )
+}
+
+/// Get the pubkey for the holdings of the given house, which list the titles it holds.
+pub fn get_holdings_address(house_address: &Pubkey) -> Pubkey {
+    get_holdings_address_and_bump_seed_internal(house_address, &id()).0
+}
+
+fn get_holdings_address_and_bump_seed_internal(
+    house_address: &Pubkey,
+    noble_program_id: &Pubkey,
+) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[&house_address.to_bytes(), b"holdings"], noble_program_id)
+}
+
+/// Get the pubkey for the governance wallet of the given house. Houses governed by
+/// tokens have it as their authority: it signs the proposals approved by the token
+/// holders, and pays for them from the lamports sent to it.
+pub fn get_governance_address(house_address: &Pubkey) -> Pubkey {
+    get_governance_address_and_bump_seed_internal(house_address, &id()).0
+}
+
+fn get_governance_address_and_bump_seed_internal(
+    house_address: &Pubkey,
+    noble_program_id: &Pubkey,
+) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[&house_address.to_bytes(), b"governance"],
+        noble_program_id,
+    )
+}
+
+/// Get the pubkey for the mint of the tokens governing the given house.
+pub fn get_governance_mint_address(house_address: &Pubkey) -> Pubkey {
+    get_governance_mint_address_and_bump_seed_internal(house_address, &id()).0
+}
+
+fn get_governance_mint_address_and_bump_seed_internal(
+    house_address: &Pubkey,
+    noble_program_id: &Pubkey,
+) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[&house_address.to_bytes(), b"governance_mint"],
+        noble_program_id,
+    )
+}
+
+/// Get the pubkey for the given proposal to the token holders of a house.
+pub fn get_proposal_address(house_address: &Pubkey, proposal_index: u32) -> Pubkey {
+    get_proposal_address_and_bump_seed_internal(house_address, proposal_index, &id()).0
+}
+
+fn get_proposal_address_and_bump_seed_internal(
+    house_address: &Pubkey,
+    proposal_index: u32,
+    noble_program_id: &Pubkey,
+) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[
+            &house_address.to_bytes(),
+            b"proposal",
+            &proposal_index.to_le_bytes(),
+        ],
+        noble_program_id,
+    )
+}
+
+/// Get the pubkey for the token account holding the governance tokens voted for the
+/// given proposal.
+pub fn get_proposal_escrow_address(proposal_address: &Pubkey) -> Pubkey {
+    get_proposal_escrow_address_and_bump_seed_internal(proposal_address, &id()).0
+}
+
+fn get_proposal_escrow_address_and_bump_seed_internal(
+    proposal_address: &Pubkey,
+    noble_program_id: &Pubkey,
+) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[&proposal_address.to_bytes(), b"escrow"], noble_program_id)
+}
+
+/// Get the pubkey for the given wallet's vote on a proposal.
+pub fn get_vote_address(proposal_address: &Pubkey, voter_wallet_address: &Pubkey) -> Pubkey {
+    get_vote_address_and_bump_seed_internal(proposal_address, voter_wallet_address, &id()).0
+}
+
+fn get_vote_address_and_bump_seed_internal(
+    proposal_address: &Pubkey,
+    voter_wallet_address: &Pubkey,
+    noble_program_id: &Pubkey,
+) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[
+            &proposal_address.to_bytes(),
+            b"vote",
+            &voter_wallet_address.to_bytes(),
+        ],
+        noble_program_id,
+    )
+}
+
+/// Get the pubkey for the election of the next holder of the given elective title.
+pub fn get_election_address(title_address: &Pubkey) -> Pubkey {
+    get_election_address_and_bump_seed_internal(title_address, &id()).0
+}
+
+fn get_election_address_and_bump_seed_internal(
+    title_address: &Pubkey,
+    noble_program_id: &Pubkey,
+) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[&title_address.to_bytes(), b"election"], noble_program_id)
+}
@@ -3,25 +3,43 @@
 use {
     crate::{
         error::TitleError,
-        get_house_address_and_bump_seed_internal, get_title_address_and_bump_seed_internal,
+        get_election_address_and_bump_seed_internal, get_governance_address_and_bump_seed_internal,
+        get_governance_mint_address_and_bump_seed_internal,
+        get_holdings_address_and_bump_seed_internal, get_house_address_and_bump_seed_internal,
+        get_petition_address_and_bump_seed_internal, get_proposal_address_and_bump_seed_internal,
+        get_proposal_escrow_address_and_bump_seed_internal,
+        get_realm_address_and_bump_seed_internal, get_realm_config_address_and_bump_seed_internal,
+        get_root_title_address_and_bump_seed_internal, get_stake_address_and_bump_seed_internal,
+        get_title_address_and_bump_seed_internal, get_treasury_address_and_bump_seed_internal,
+        get_vassal_page_address_and_bump_seed_internal, get_vote_address_and_bump_seed_internal,
         instruction::TitleInstruction,
-        state::{HouseData, TitleData},
-        utils::try_from_slice_checked,
+        state::{
+            pack_string, validate_coat_of_arms, validate_display_name, validate_realm_name,
+            AccountType, Ballot, ElectionData, HoldingsData, HouseData, Kind, PetitionData,
+            ProposalData, ProposedAccount, Rank, RealmConfigData, RealmData, RealmSettings,
+            SuccessionLaw, TitleData, TitleDataMut, VassalPageData, VersionedAccount, VoteData,
+            STAKE_VAULT_SIZE,
+        },
+        utils::{check_account_owner, check_account_type, close_account, load_account},
     },
     borsh::{BorshDeserialize, BorshSerialize},
     solana_program::{
         account_info::{next_account_info, AccountInfo},
+        clock::Clock,
         entrypoint::ProgramResult,
+        instruction::{AccountMeta, Instruction},
         msg,
-        program::invoke_signed,
+        bpf_loader_upgradeable,
+        program::{invoke, invoke_signed},
         program_error::ProgramError,
-        program_pack::IsInitialized,
+        program_pack::{IsInitialized, Pack},
         pubkey::Pubkey,
         rent::Rent,
         system_instruction,
         system_program,
-        sysvar::Sysvar, // for Rent::get()
+        sysvar::Sysvar, // for Rent::get() and Clock::get()
     },
+    spl_token::state::{Account as TokenAccount, Mint},
 };
 
 /// Instruction processor
@@ -34,14 +52,25 @@ pub fn process_instruction(
         msg!("input: {}", input.len());
         msg!("input: {:x?}", input);
         msg!("failed to unpack instruction: {}", e);
-        ProgramError::InvalidInstructionData
+        // Rank and kind fail to deserialize with the matching TitleError.
+        match e.get_ref().and_then(|inner| inner.downcast_ref::<TitleError>()) {
+            Some(title_error) => title_error.clone().into(),
+            None => ProgramError::InvalidInstructionData,
+        }
     })?;
 
     let result = match instruction {
         TitleInstruction::CreateHouse {
             coat_of_arms,
             display_name,
-        } => process_create_house_account(_program_id, accounts, coat_of_arms, display_name),
+            governance_token_supply,
+        } => process_create_house_account(
+            _program_id,
+            accounts,
+            coat_of_arms,
+            display_name,
+            governance_token_supply,
+        ),
         TitleInstruction::CreateTitle {
             rank,
             kind,
@@ -61,6 +90,96 @@ pub fn process_instruction(
             liege_address,
             liege_vassal_index,
         ),
+        TitleInstruction::BuyTitle {
+            sale_price_lamports,
+        } => process_buy_title(_program_id, accounts, sale_price_lamports),
+        TitleInstruction::ActivateTitle => process_activate_title(_program_id, accounts),
+        TitleInstruction::SetSalePrice {
+            sale_price_lamports,
+        } => process_set_sale_price(_program_id, accounts, sale_price_lamports),
+        TitleInstruction::CollectTax => process_collect_tax(_program_id, accounts),
+        TitleInstruction::WithdrawFromHouse { lamports } => {
+            process_withdraw_from_house(_program_id, accounts, lamports)
+        }
+        TitleInstruction::UpdateHouse { coat_of_arms } => {
+            process_update_house(_program_id, accounts, coat_of_arms)
+        }
+        TitleInstruction::UpdateTitleCoatOfArms { coat_of_arms } => {
+            process_update_title_coat_of_arms(_program_id, accounts, coat_of_arms)
+        }
+        TitleInstruction::MigrateAccount => process_migrate_account(_program_id, accounts),
+        TitleInstruction::InitializeRealmConfig { admin, settings } => {
+            process_initialize_realm_config(_program_id, accounts, admin, settings)
+        }
+        TitleInstruction::UpdateRealmConfig { admin, settings } => {
+            process_update_realm_config(_program_id, accounts, admin, settings)
+        }
+        TitleInstruction::WithdrawFromTreasury { lamports } => {
+            process_withdraw_from_treasury(_program_id, accounts, lamports)
+        }
+        TitleInstruction::CreateRealm { name } => {
+            process_create_realm(_program_id, accounts, name)
+        }
+        TitleInstruction::PetitionVassalage {
+            rank,
+            kind,
+            required_stake_lamports,
+            coat_of_arms,
+            display_name,
+        } => process_petition_vassalage(
+            _program_id,
+            accounts,
+            rank,
+            kind,
+            required_stake_lamports,
+            coat_of_arms,
+            display_name,
+        ),
+        TitleInstruction::AcceptPetition { liege_vassal_index } => {
+            process_accept_petition(_program_id, accounts, liege_vassal_index)
+        }
+        TitleInstruction::RejectPetition => process_reject_petition(_program_id, accounts),
+        TitleInstruction::ReparentTitle {
+            new_liege_vassal_index,
+        } => process_reparent_title(_program_id, accounts, new_liege_vassal_index),
+        TitleInstruction::EscheatTitle => process_escheat_title(_program_id, accounts),
+        TitleInstruction::CloseHouse => process_close_house(_program_id, accounts),
+        TitleInstruction::TransferHouseAuthority { new_authority } => {
+            process_transfer_house_authority(_program_id, accounts, new_authority)
+        }
+        TitleInstruction::CreateProposal {
+            proposal_index,
+            accounts: proposed_accounts,
+            data,
+        } => process_create_proposal(
+            _program_id,
+            accounts,
+            proposal_index,
+            proposed_accounts,
+            data,
+        ),
+        TitleInstruction::CastVote { tokens } => process_cast_vote(_program_id, accounts, tokens),
+        TitleInstruction::WithdrawVote => process_withdraw_vote(_program_id, accounts),
+        TitleInstruction::ExecuteProposal => process_execute_proposal(_program_id, accounts),
+        TitleInstruction::SetHeir {
+            heir_house_address,
+            succession_period_slots,
+        } => process_set_heir(
+            _program_id,
+            accounts,
+            heir_house_address,
+            succession_period_slots,
+        ),
+        TitleInstruction::ProveLife => process_prove_life(_program_id, accounts),
+        TitleInstruction::TriggerSuccession => process_trigger_succession(_program_id, accounts),
+        TitleInstruction::SetSuccessionLaw { succession_law } => {
+            process_set_succession_law(_program_id, accounts, succession_law)
+        }
+        TitleInstruction::OpenElection => process_open_election(_program_id, accounts),
+        TitleInstruction::CastBallot {
+            candidate_house_address,
+        } => process_cast_ballot(_program_id, accounts, candidate_house_address),
+        TitleInstruction::ConcludeElection => process_conclude_election(_program_id, accounts),
     };
     result
 }
@@ -71,6 +190,7 @@ pub fn process_create_house_account(
     accounts: &[AccountInfo],
     coat_of_arms: String,
     display_name: String,
+    governance_token_supply: u16,
 ) -> ProgramResult {
     let account_info_iter = &mut accounts.iter();
 
@@ -82,9 +202,15 @@ pub fn process_create_house_account(
         return Err(ProgramError::MissingRequiredSignature);
     }
     if !owner_and_funder_wallet_info.is_writable || !house_account_info.is_writable {
-        return Err(ProgramError::InvalidArgument);
+        return Err(TitleError::AccountNotWritable.into());
     }
     check_system_program(owner_and_funder_wallet_info.owner)?;
+    check_coat_of_arms(&coat_of_arms)?;
+    check_display_name(&display_name)?;
+    if governance_token_supply == 0 {
+        msg!("Governance token supply must be at least 1");
+        return Err(TitleError::InvalidGovernanceTokenSupply.into());
+    }
 
     let rent = Rent::get().unwrap();
     // Verify house address derivation, get seed for signing.
@@ -117,15 +243,150 @@ pub fn process_create_house_account(
         &[house_account_signer_seeds],
     )?;
 
+    // A house governed by tokens is governed by its governance wallet, which signs the
+    // proposals approved by the token holders. The whole supply is minted to the
+    // creator, and no more can be minted.
+    let authority = if governance_token_supply > 1 {
+        let governance_account_info = next_account_info(account_info_iter)?;
+        let mint_account_info = next_account_info(account_info_iter)?;
+        let token_account_info = next_account_info(account_info_iter)?;
+        let token_program_info = next_account_info(account_info_iter)?;
+        let rent_sysvar_info = next_account_info(account_info_iter)?;
+
+        if !token_account_info.is_signer {
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+        check_token_program(token_program_info.key)?;
+        let (governance_address, governance_bump_seed) =
+            get_governance_address_and_bump_seed_internal(&house_address, program_id);
+        if governance_address != *governance_account_info.key {
+            msg!("Error: Governance address does not match seed derivation");
+            return Err(ProgramError::InvalidSeeds);
+        }
+        let (mint_address, mint_bump_seed) =
+            get_governance_mint_address_and_bump_seed_internal(&house_address, program_id);
+        if mint_address != *mint_account_info.key {
+            msg!("Error: Governance mint address does not match seed derivation");
+            return Err(ProgramError::InvalidSeeds);
+        }
+        let governance_signer_seeds: &[&[_]] = &[
+            &house_address.to_bytes(),
+            b"governance",
+            &[governance_bump_seed],
+        ];
+        let mint_signer_seeds: &[&[_]] = &[
+            &house_address.to_bytes(),
+            b"governance_mint",
+            &[mint_bump_seed],
+        ];
+
+        msg!("Creating governance mint: {}", mint_address);
+        create_pda_account(
+            program_id,
+            owner_and_funder_wallet_info,
+            mint_account_info,
+            Mint::LEN,
+            &spl_token::id(),
+            mint_signer_seeds,
+            system_account_info,
+        )?;
+        invoke(
+            &spl_token::instruction::initialize_mint(
+                &spl_token::id(),
+                &mint_address,
+                &governance_address,
+                None,
+                0,
+            )?,
+            &[
+                mint_account_info.clone(),
+                rent_sysvar_info.clone(),
+                token_program_info.clone(),
+            ],
+        )?;
+        invoke(
+            &system_instruction::create_account(
+                owner_and_funder_wallet_info.key,
+                token_account_info.key,
+                rent.minimum_balance(TokenAccount::LEN),
+                TokenAccount::LEN as u64,
+                &spl_token::id(),
+            ),
+            &[
+                owner_and_funder_wallet_info.clone(),
+                token_account_info.clone(),
+                system_account_info.clone(),
+            ],
+        )?;
+        invoke(
+            &spl_token::instruction::initialize_account(
+                &spl_token::id(),
+                token_account_info.key,
+                &mint_address,
+                owner_and_funder_wallet_info.key,
+            )?,
+            &[
+                token_account_info.clone(),
+                mint_account_info.clone(),
+                owner_and_funder_wallet_info.clone(),
+                rent_sysvar_info.clone(),
+                token_program_info.clone(),
+            ],
+        )?;
+        invoke_signed(
+            &spl_token::instruction::mint_to(
+                &spl_token::id(),
+                &mint_address,
+                token_account_info.key,
+                &governance_address,
+                &[],
+                governance_token_supply.into(),
+            )?,
+            &[
+                mint_account_info.clone(),
+                token_account_info.clone(),
+                governance_account_info.clone(),
+                token_program_info.clone(),
+            ],
+            &[governance_signer_seeds],
+        )?;
+        invoke_signed(
+            &spl_token::instruction::set_authority(
+                &spl_token::id(),
+                &mint_address,
+                None,
+                spl_token::instruction::AuthorityType::MintTokens,
+                &governance_address,
+                &[],
+            )?,
+            &[
+                mint_account_info.clone(),
+                governance_account_info.clone(),
+                token_program_info.clone(),
+            ],
+            &[governance_signer_seeds],
+        )?;
+        governance_address
+    } else {
+        *owner_and_funder_wallet_info.key
+    };
+
     {
         let dst: &mut [u8] = &mut house_account_info.data.borrow_mut();
         let house_data_struct: HouseData = HouseData {
+            account_type: AccountType::House,
             version: HouseData::CURRENT_VERSION,
-            governance_token_supply: 1,
+            governance_token_supply,
             coat_of_arms: coat_of_arms,
             display_name: display_name,
             prestige: 0,
             virtue: 0,
+            held_title_count: 0,
+            authority,
+            proposal_count: 0,
+            heir_house_address: Pubkey::new(&[0; 32]),
+            succession_period_slots: 0,
+            last_proof_of_life_slot: Clock::get()?.slot,
         };
         let data = house_data_struct.try_to_vec().unwrap();
         dst[..data.len()].copy_from_slice(&data);
@@ -138,13 +399,13 @@ pub fn process_create_house_account(
 pub fn process_create_title_account(
     program_id: &Pubkey,
     accounts: &[AccountInfo],
-    rank: u8,
-    kind: u8,
+    rank: Rank,
+    kind: Kind,
     required_stake_lamports: u64,
     coat_of_arms: String,
     display_name: String,
     liege_address: Pubkey,
-    liege_vassal_index: u8,
+    liege_vassal_index: u16,
 ) -> ProgramResult {
     let account_info_iter = &mut accounts.iter();
 
@@ -153,6 +414,8 @@ pub fn process_create_title_account(
     let new_title_account_info = next_account_info(account_info_iter)?;
     let liege_title_account_info = next_account_info(account_info_iter)?;
     let system_account_info = next_account_info(account_info_iter)?;
+    let realm_config_account_info = next_account_info(account_info_iter)?;
+    let realm_account_info = next_account_info(account_info_iter)?;
 
     let empty_liege = liege_address == Pubkey::new(&[0; 32]);
 
@@ -161,99 +424,164 @@ pub fn process_create_title_account(
         return Err(ProgramError::MissingRequiredSignature);
     }
     if !owner_and_funder_wallet_info.is_writable
+        || !house_account_info.is_writable
         || !new_title_account_info.is_writable
         || (!liege_title_account_info.is_writable && !empty_liege)
     {
         msg!(
-            "No write permission for accounts: {} {} {}",
+            "No write permission for accounts: {} {} {} {}",
             owner_and_funder_wallet_info.is_writable,
+            house_account_info.is_writable,
             new_title_account_info.is_writable,
             liege_title_account_info.is_writable
         );
-        return Err(ProgramError::InvalidArgument);
+        return Err(TitleError::AccountNotWritable.into());
     }
     check_system_program(owner_and_funder_wallet_info.owner)?;
 
-    // Check other arguments for validity
-    if rank < 1 || rank > 8 {
-        msg!("Invalid rank: {}", rank);
-        return Err(ProgramError::InvalidArgument);
-    }
-    if kind < 1 || kind > 2 {
-        msg!("Invalid kind: {}", rank);
-        return Err(ProgramError::InvalidArgument);
+    // Check other arguments for validity. Rank and kind were checked when the
+    // instruction was deserialized.
+    if rank == Rank::Deus && !empty_liege {
+        msg!("{} title must have no liege, got {}", rank, liege_address);
+        return Err(TitleError::RootTitleHasLiege.into());
     }
-    if rank == 1 && !empty_liege {
-        msg!("Rank 1 title must have no liege, got {}", liege_address);
-        return Err(ProgramError::InvalidArgument);
+    if rank != Rank::Deus && empty_liege {
+        msg!("{} title must have liege, got {}", rank, liege_address);
+        return Err(TitleError::MissingLiege.into());
     }
-    if rank > 1 && empty_liege {
-        msg!("Rank 2+ title must have liege, got {}", liege_address);
-        return Err(ProgramError::InvalidArgument);
+    if rank == Rank::Deus && liege_vassal_index != 0 {
+        msg!(
+            "Root title must have vassal index 0, got {}",
+            liege_vassal_index
+        );
+        return Err(TitleError::IncorrectVassalIndex.into());
     }
-
-    // Check house address matches owner/funder wallet, and get seeds for signing.
-    let (house_address, bump_seed) =
-        get_house_address_and_bump_seed_internal(owner_and_funder_wallet_info.key, program_id);
-    if house_address != *house_account_info.key {
-        msg!("Error: House address does not match seed derivation");
-        return Err(ProgramError::InvalidSeeds);
+    if liege_address != *liege_title_account_info.key {
+        msg!(
+            "Expected liege {}, got {}",
+            liege_address,
+            liege_title_account_info.key
+        );
+        return Err(TitleError::IncorrectLiege.into());
     }
-    // TODO: do we need the house account to sign anything here?
-    let house_account_signer_seeds: &[&[_]] =
-        &[&owner_and_funder_wallet_info.key.to_bytes(), &[bump_seed]];
+    let realm_config = load_realm_config(program_id, realm_config_account_info)?;
+    check_rank_and_kind(&realm_config.settings, rank, kind)?;
+    check_coat_of_arms(&coat_of_arms)?;
+    check_display_name(&display_name)?;
+    let coat_of_arms = pack_string(&coat_of_arms)?;
+    let display_name = pack_string(&display_name)?;
 
-    // Check that house data is initialized.
-    let housedata = try_from_slice_checked::<HouseData>(
-        &liege_title_account_info.data.borrow(),
-        HouseData::SIZE,
-    )?;
+    // Check that the owner/funder wallet governs the house, and that house data is
+    // initialized.
+    let housedata =
+        check_house_authority(program_id, owner_and_funder_wallet_info, house_account_info)?;
     if !housedata.is_initialized() {
         msg!("Error: house is uninitialized");
-        return Err(ProgramError::InvalidArgument);
+        return Err(TitleError::UninitializedAccount.into());
     }
 
-    // Check title address matches liege/vassal-index seeds. Get title address seeds for signing.
-    let (title_address, bump_seed) = get_title_address_and_bump_seed_internal(
-        liege_title_account_info.key,
-        liege_vassal_index,
-        program_id,
-    );
+    // Check title address matches the realm's root seeds for the root title, and the
+    // liege/vassal-index seeds otherwise. Get title address seeds for signing.
+    let realm_address = *realm_account_info.key;
+    let (title_address, bump_seed) = if rank == Rank::Deus {
+        get_root_title_address_and_bump_seed_internal(&realm_address, program_id)
+    } else {
+        get_title_address_and_bump_seed_internal(
+            liege_title_account_info.key,
+            liege_vassal_index,
+            program_id,
+        )
+    };
     if title_address != *new_title_account_info.key {
         msg!("Error: New title address does not match seed derivation");
         return Err(ProgramError::InvalidSeeds);
     }
-    let title_account_signer_seeds: &[&[_]] = &[
-        &liege_title_account_info.key.to_bytes(),
-        &[liege_vassal_index; 32],
-        &[bump_seed],
-    ];
-    // For rank 2+ titles, deserialize the liege, check that the current house holds that
-    // liege title, and if so, update the vassal list.
-    if rank > 1 {
-        let mut td = try_from_slice_checked::<TitleData>(
-            &liege_title_account_info.data.borrow(),
-            TitleData::SIZE,
-        )?;
+    let realm_address_bytes = realm_address.to_bytes();
+    let liege_address_bytes = liege_title_account_info.key.to_bytes();
+    let liege_vassal_index_bytes = liege_vassal_index.to_le_bytes();
+    let bump_seed = [bump_seed];
+    let title_account_signer_seeds: &[&[_]] = if rank == Rank::Deus {
+        &[b"root", &realm_address_bytes, &bump_seed]
+    } else {
+        &[&liege_address_bytes, &liege_vassal_index_bytes, &bump_seed]
+    };
+
+    // The root title needs an existing realm without a root, and may only be created by
+    // the realm authority or the realm config admin. It is recorded in the realm. Other
+    // titles join the realm of their liege.
+    if rank == Rank::Deus {
+        if !realm_account_info.is_writable {
+            msg!("No write permission for realm account");
+            return Err(TitleError::AccountNotWritable.into());
+        }
+        let mut realm = load_account::<RealmData>(program_id, realm_account_info)?;
+        if !realm.is_initialized() {
+            msg!("Error: realm is uninitialized");
+            return Err(TitleError::UninitializedAccount.into());
+        }
+        if *owner_and_funder_wallet_info.key != realm.authority
+            && *owner_and_funder_wallet_info.key != realm_config.admin
+        {
+            msg!(
+                "Root title of realm {} may only be created by {} or {}, got {}",
+                realm.name,
+                realm.authority,
+                realm_config.admin,
+                owner_and_funder_wallet_info.key
+            );
+            return Err(TitleError::NotRealmAuthority.into());
+        }
+        if realm.root_title_address != Pubkey::new(&[0; 32]) {
+            msg!(
+                "Realm {} already has root title {}",
+                realm.name,
+                realm.root_title_address
+            );
+            return Err(TitleError::RootTitleExists.into());
+        }
+        realm.root_title_address = title_address;
+        realm.serialize(&mut *realm_account_info.data.borrow_mut())?;
+    }
+    // For titles below Deus, deserialize the liege, check that the current house holds
+    // that liege title, and if so, update the vassal list.
+    if rank != Rank::Deus {
+        let vassal_page_account_info = next_account_info(account_info_iter)?;
+        let td = load_account::<TitleData>(program_id, liege_title_account_info)?;
         check_authority(house_account_info, &td.holder_house_address)?;
-        if td.vassal_addresses.len() != liege_vassal_index.into() {
+        if td.realm_address != realm_address {
+            msg!(
+                "Liege is in realm {}, got realm {}",
+                td.realm_address,
+                realm_address
+            );
+            return Err(TitleError::RealmMismatch.into());
+        }
+        if td.vassal_count != liege_vassal_index {
             msg!(
                 "Cannot add vassal #{}, liege has {} vassals",
                 liege_vassal_index,
-                td.vassal_addresses.len()
+                td.vassal_count
             );
-            return Err(ProgramError::InvalidArgument);
+            return Err(TitleError::IncorrectVassalIndex.into());
         }
         if td.rank >= rank {
             msg!(
-                "Rank of new title ({}) must be numerically greater than liege title ({})",
+                "Rank of new title ({}) must be below rank of liege title ({})",
                 rank,
                 td.rank
             );
-            return Err(ProgramError::InvalidArgument);
+            return Err(TitleError::RankNotBelowLiege.into());
         }
-        td.vassal_addresses.push(title_address);
-        td.serialize(&mut *liege_title_account_info.data.borrow_mut())?;
+        check_vassal_capacity(&realm_config.settings, &td)?;
+        append_vassal(
+            program_id,
+            &td,
+            liege_title_account_info,
+            vassal_page_account_info,
+            owner_and_funder_wallet_info,
+            system_account_info,
+            &title_address,
+        )?;
     }
 
     let rent = Rent::get().unwrap();
@@ -281,47 +609,5094 @@ pub fn process_create_title_account(
 
     // Finally, write the contents of the new title's account.
     let title_data_struct: TitleData = TitleData {
+        account_type: AccountType::Title,
         version: TitleData::CURRENT_VERSION,
         lifecycle_state: TitleData::INACTIVE_STATE,
         rank,
         kind,
         required_stake_lamports,
         sale_price_lamports: required_stake_lamports,
-        coat_of_arms,
-        display_name,
+        last_tax_paid_slot: Clock::get()?.slot,
         holder_house_address: *house_account_info.key,
         stake_address: Pubkey::new(&[0; 32]),
         liege_address: *liege_title_account_info.key,
         liege_vassal_index,
-        vassal_addresses: vec![],
+        vassal_count: 0,
+        coat_of_arms,
+        display_name,
+        realm_address,
+        live_vassal_count: 0,
+        seed_liege_address: *liege_title_account_info.key,
+        seed_liege_vassal_index: liege_vassal_index,
+        succession_law: SuccessionLaw::Heir,
     };
-    title_data_struct
-        .serialize(&mut *new_title_account_info.data.borrow_mut())
-        .map_err(|e| e.into())
+    title_data_struct.pack_into_slice(&mut new_title_account_info.data.borrow_mut());
+
+    let holdings_account_info = next_account_info(account_info_iter)?;
+    add_held_titles(
+        program_id,
+        house_account_info,
+        holdings_account_info,
+        &[title_address],
+        owner_and_funder_wallet_info,
+        system_account_info,
+    )
 }
 
-fn check_authority(authority_info: &AccountInfo, expected_authority: &Pubkey) -> ProgramResult {
-    if expected_authority != authority_info.key {
+/// Processes BuyTitle instruction
+pub fn process_buy_title(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    sale_price_lamports: u64,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+
+    let buyer_wallet_info = next_account_info(account_info_iter)?;
+    let buyer_house_account_info = next_account_info(account_info_iter)?;
+    let title_account_info = next_account_info(account_info_iter)?;
+    let holder_wallet_info = next_account_info(account_info_iter)?;
+    let system_account_info = next_account_info(account_info_iter)?;
+    let realm_config_account_info = next_account_info(account_info_iter)?;
+    let treasury_account_info = next_account_info(account_info_iter)?;
+    let holder_house_account_info = next_account_info(account_info_iter)?;
+    let buyer_holdings_account_info = next_account_info(account_info_iter)?;
+    let holder_holdings_account_info = next_account_info(account_info_iter)?;
+
+    if !buyer_wallet_info.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+    if !buyer_wallet_info.is_writable
+        || !buyer_house_account_info.is_writable
+        || !title_account_info.is_writable
+        || !holder_wallet_info.is_writable
+        || !treasury_account_info.is_writable
+        || !holder_house_account_info.is_writable
+    {
         msg!(
-            "Expected house {}, got house {}",
-            expected_authority,
-            authority_info.key
+            "No write permission for accounts: {} {} {} {} {} {}",
+            buyer_wallet_info.is_writable,
+            buyer_house_account_info.is_writable,
+            title_account_info.is_writable,
+            holder_wallet_info.is_writable,
+            treasury_account_info.is_writable,
+            holder_house_account_info.is_writable
         );
-        return Err(TitleError::IncorrectAuthority.into());
+        return Err(TitleError::AccountNotWritable.into());
+    }
+    check_system_program(buyer_wallet_info.owner)?;
+
+    // The buyer must already have an initialized house to hold the title.
+    let housedata = check_house_authority(program_id, buyer_wallet_info, buyer_house_account_info)?;
+    if !housedata.is_initialized() {
+        msg!("Error: buyer house is uninitialized");
+        return Err(TitleError::UninitializedAccount.into());
+    }
+
+    let td = load_account::<TitleData>(program_id, title_account_info)?;
+    if !td.is_initialized() {
+        msg!("Error: title is uninitialized");
+        return Err(TitleError::UninitializedAccount.into());
+    }
+    if td.holder_house_address == *buyer_house_account_info.key {
+        msg!("Error: buyer house already holds this title");
+        return Err(TitleError::AlreadyTitleHolder.into());
+    }
+    if td.sale_price_lamports == TitleData::NOT_FOR_SALE {
+        msg!("Error: title is not for sale");
+        return Err(TitleError::NotForSale.into());
+    }
+    if td.sale_price_lamports != sale_price_lamports {
+        msg!(
+            "Sale price is {} lamports, buyer offered {}",
+            td.sale_price_lamports,
+            sale_price_lamports
+        );
+        return Err(TitleError::SalePriceMismatch.into());
+    }
+    check_tax_settled(&td)?;
+
+    // The sale price goes to the wallet governing the current holder's house, or to the
+    // treasury holding the title.
+    check_authority(holder_house_account_info, &td.holder_house_address)?;
+    let held_by_treasury = is_held_by_treasury(program_id, &td);
+    if held_by_treasury {
+        check_authority(holder_wallet_info, &td.holder_house_address)?;
+    } else {
+        check_house_authority(program_id, holder_wallet_info, holder_house_account_info)?;
+    }
+
+    invoke(
+        &system_instruction::transfer(
+            buyer_wallet_info.key,
+            holder_wallet_info.key,
+            td.sale_price_lamports,
+        ),
+        &[
+            buyer_wallet_info.clone(),
+            holder_wallet_info.clone(),
+            system_account_info.clone(),
+        ],
+    )?;
+
+    // The protocol fee is paid on top of the sale price.
+    let realm_config = load_realm_config(program_id, realm_config_account_info)?;
+    let protocol_fee = realm_config
+        .settings
+        .protocol_fee_lamports(td.sale_price_lamports)
+        .ok_or(TitleError::Overflow)?;
+    if protocol_fee > 0 {
+        let (treasury_address, _) =
+            get_treasury_address_and_bump_seed_internal(&td.realm_address, program_id);
+        if treasury_address != *treasury_account_info.key {
+            msg!("Error: Treasury address does not match seed derivation");
+            return Err(ProgramError::InvalidSeeds);
+        }
+        msg!("Paying {} lamports protocol fee", protocol_fee);
+        invoke(
+            &system_instruction::transfer(
+                buyer_wallet_info.key,
+                treasury_account_info.key,
+                protocol_fee,
+            ),
+            &[
+                buyer_wallet_info.clone(),
+                treasury_account_info.clone(),
+                system_account_info.clone(),
+            ],
+        )?;
+    }
+
+    // The stake left in an active title's vault goes back to the seller, and the buyer
+    // stakes the full required stake in its place.
+    if td.lifecycle_state == TitleData::ACTIVE_STATE {
+        let stake_account_info = next_account_info(account_info_iter)?;
+        if !stake_account_info.is_writable {
+            msg!("No write permission for stake account");
+            return Err(TitleError::AccountNotWritable.into());
+        }
+        if td.stake_address != *stake_account_info.key {
+            msg!(
+                "Expected stake account {}, got {}",
+                td.stake_address,
+                stake_account_info.key
+            );
+            return Err(TitleError::IncorrectStakeAccount.into());
+        }
+        check_account_owner(program_id, stake_account_info)?;
+        check_account_type(&stake_account_info.data.borrow(), AccountType::StakeVault)?;
+
+        let refund = stake_account_info
+            .lamports()
+            .saturating_sub(Rent::get()?.minimum_balance(STAKE_VAULT_SIZE));
+        msg!("Refunding {} lamports of stake to the seller", refund);
+        **stake_account_info.lamports.borrow_mut() = stake_account_info
+            .lamports()
+            .checked_sub(refund)
+            .ok_or(TitleError::Overflow)?;
+        **holder_wallet_info.lamports.borrow_mut() = holder_wallet_info
+            .lamports()
+            .checked_add(refund)
+            .ok_or(TitleError::Overflow)?;
+        invoke(
+            &system_instruction::transfer(
+                buyer_wallet_info.key,
+                stake_account_info.key,
+                td.required_stake_lamports,
+            ),
+            &[
+                buyer_wallet_info.clone(),
+                stake_account_info.clone(),
+                system_account_info.clone(),
+            ],
+        )?;
+    }
+
+    let mut title_data = title_account_info.data.borrow_mut();
+    let mut title = TitleDataMut::new(&mut title_data)?;
+    title.set_holder_house_address(buyer_house_account_info.key);
+    // The escheat grace period of an inactive title starts over for the buyer.
+    if td.lifecycle_state != TitleData::ACTIVE_STATE {
+        title.set_last_tax_paid_slot(Clock::get()?.slot);
+    }
+    if !held_by_treasury {
+        remove_held_titles(
+            program_id,
+            holder_house_account_info,
+            holder_holdings_account_info,
+            &[*title_account_info.key],
+        )?;
+    }
+    add_held_titles(
+        program_id,
+        buyer_house_account_info,
+        buyer_holdings_account_info,
+        &[*title_account_info.key],
+        buyer_wallet_info,
+        system_account_info,
+    )
+}
+
+/// Processes ActivateTitle instruction
+pub fn process_activate_title(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+
+    let owner_and_funder_wallet_info = next_account_info(account_info_iter)?;
+    let house_account_info = next_account_info(account_info_iter)?;
+    let title_account_info = next_account_info(account_info_iter)?;
+    let stake_account_info = next_account_info(account_info_iter)?;
+    let system_account_info = next_account_info(account_info_iter)?;
+
+    if !owner_and_funder_wallet_info.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+    if !owner_and_funder_wallet_info.is_writable
+        || !title_account_info.is_writable
+        || !stake_account_info.is_writable
+    {
+        msg!(
+            "No write permission for accounts: {} {} {}",
+            owner_and_funder_wallet_info.is_writable,
+            title_account_info.is_writable,
+            stake_account_info.is_writable
+        );
+        return Err(TitleError::AccountNotWritable.into());
+    }
+    check_system_program(owner_and_funder_wallet_info.owner)?;
+
+    // Only the holder of the title may activate it.
+    check_house_authority(program_id, owner_and_funder_wallet_info, house_account_info)?;
+    let td = load_account::<TitleData>(program_id, title_account_info)?;
+    if !td.is_initialized() {
+        msg!("Error: title is uninitialized");
+        return Err(TitleError::UninitializedAccount.into());
+    }
+    check_authority(house_account_info, &td.holder_house_address)?;
+    if td.lifecycle_state != TitleData::INACTIVE_STATE {
+        msg!(
+            "Title is not inactive, lifecycle state {}",
+            td.lifecycle_state
+        );
+        return Err(TitleError::TitleAlreadyActive.into());
+    }
+
+    // Check stake address matches title seed, and get seeds for signing.
+    let (stake_address, bump_seed) =
+        get_stake_address_and_bump_seed_internal(title_account_info.key, program_id);
+    if stake_address != *stake_account_info.key {
+        msg!("Error: Stake address does not match seed derivation");
+        return Err(ProgramError::InvalidSeeds);
+    }
+    let stake_account_signer_seeds: &[&[_]] =
+        &[&title_account_info.key.to_bytes(), b"stake", &[bump_seed]];
+
+    // The vault holds only its account type; it is rent exempt on top of the required
+    // stake, so the full stake stays available to the program.
+    let rent = Rent::get().unwrap();
+    let required_lamports = rent
+        .minimum_balance(STAKE_VAULT_SIZE)
+        .checked_add(td.required_stake_lamports)
+        .ok_or(TitleError::Overflow)?;
+
+    msg!("Creating stake_address: {}", stake_address);
+    invoke_signed(
+        &system_instruction::create_account(
+            owner_and_funder_wallet_info.key,
+            stake_account_info.key,
+            required_lamports,
+            STAKE_VAULT_SIZE as u64,
+            program_id, // owner
+        ),
+        &[
+            owner_and_funder_wallet_info.clone(),
+            stake_account_info.clone(),
+            system_account_info.clone(),
+        ],
+        &[stake_account_signer_seeds],
+    )?;
+    stake_account_info.data.borrow_mut()[0] = AccountType::StakeVault as u8;
+
+    let mut title_data = title_account_info.data.borrow_mut();
+    let mut title = TitleDataMut::new(&mut title_data)?;
+    title.set_stake_address(&stake_address);
+    title.set_lifecycle_state(TitleData::ACTIVE_STATE);
+    title.set_last_tax_paid_slot(Clock::get()?.slot);
+    Ok(())
+}
+
+/// Processes SetSalePrice instruction
+pub fn process_set_sale_price(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    sale_price_lamports: Option<u64>,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+
+    let owner_wallet_info = next_account_info(account_info_iter)?;
+    let house_account_info = next_account_info(account_info_iter)?;
+    let title_account_info = next_account_info(account_info_iter)?;
+
+    if !owner_wallet_info.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+    if !title_account_info.is_writable {
+        msg!("No write permission for title account");
+        return Err(TitleError::AccountNotWritable.into());
+    }
+
+    // Only the holder of the title may change its price.
+    check_house_authority(program_id, owner_wallet_info, house_account_info)?;
+    let td = load_account::<TitleData>(program_id, title_account_info)?;
+    if !td.is_initialized() {
+        msg!("Error: title is uninitialized");
+        return Err(TitleError::UninitializedAccount.into());
     }
+    check_authority(house_account_info, &td.holder_house_address)?;
+    check_tax_settled(&td)?;
+
+    let sale_price_lamports = match sale_price_lamports {
+        Some(price) if price < td.required_stake_lamports => {
+            msg!(
+                "Sale price {} is below required stake {}",
+                price,
+                td.required_stake_lamports
+            );
+            return Err(TitleError::SalePriceBelowRequiredStake.into());
+        }
+        Some(price) => price,
+        None => TitleData::NOT_FOR_SALE,
+    };
+    TitleDataMut::new(&mut title_account_info.data.borrow_mut())?
+        .set_sale_price_lamports(sale_price_lamports);
     Ok(())
 }
 
-/// Check system program address
-fn check_system_program(program_id: &Pubkey) -> Result<(), ProgramError> {
-    if *program_id != system_program::id() {
+/// Processes CollectTax instruction
+pub fn process_collect_tax(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+
+    let title_account_info = next_account_info(account_info_iter)?;
+    let stake_account_info = next_account_info(account_info_iter)?;
+    let recipient_account_info = next_account_info(account_info_iter)?;
+    let realm_config_account_info = next_account_info(account_info_iter)?;
+
+    if !title_account_info.is_writable
+        || !stake_account_info.is_writable
+        || !recipient_account_info.is_writable
+    {
         msg!(
-            "Expected system program {}, received {}",
-            system_program::id(),
-            program_id
+            "No write permission for accounts: {} {} {}",
+            title_account_info.is_writable,
+            stake_account_info.is_writable,
+            recipient_account_info.is_writable
         );
-        Err(ProgramError::IncorrectProgramId)
+        return Err(TitleError::AccountNotWritable.into());
+    }
+
+    let td = load_account::<TitleData>(program_id, title_account_info)?;
+    if !td.is_initialized() {
+        msg!("Error: title is uninitialized");
+        return Err(TitleError::UninitializedAccount.into());
+    }
+    if td.lifecycle_state != TitleData::ACTIVE_STATE {
+        msg!(
+            "Title is not active, lifecycle state {}",
+            td.lifecycle_state
+        );
+        return Err(TitleError::TitleNotActive.into());
+    }
+    if td.stake_address != *stake_account_info.key {
+        msg!(
+            "Expected stake account {}, got {}",
+            td.stake_address,
+            stake_account_info.key
+        );
+        return Err(TitleError::IncorrectStakeAccount.into());
+    }
+    check_account_owner(program_id, stake_account_info)?;
+    check_account_type(&stake_account_info.data.borrow(), AccountType::StakeVault)?;
+
+    // Tax goes to whoever holds the liege title; the root title pays the treasury.
+    let empty_liege = td.liege_address == Pubkey::new(&[0; 32]);
+    let liege_holder_house = if empty_liege {
+        let (treasury_address, _) =
+            get_treasury_address_and_bump_seed_internal(&td.realm_address, program_id);
+        if treasury_address != *recipient_account_info.key {
+            msg!("Error: Treasury address does not match seed derivation");
+            return Err(ProgramError::InvalidSeeds);
+        }
+        None
     } else {
-        Ok(())
+        let liege_title_account_info = next_account_info(account_info_iter)?;
+        if td.liege_address != *liege_title_account_info.key {
+            msg!(
+                "Expected liege {}, got {}",
+                td.liege_address,
+                liege_title_account_info.key
+            );
+            return Err(TitleError::IncorrectLiege.into());
+        }
+        let liege_td = load_account::<TitleData>(program_id, liege_title_account_info)?;
+        check_authority(recipient_account_info, &liege_td.holder_house_address)?;
+        let liege_held_by_treasury = is_held_by_treasury(program_id, &liege_td);
+        // The title's holder house, which loses the title on foreclosure.
+        let holder_house_account_info = next_account_info(account_info_iter)?;
+        if !holder_house_account_info.is_writable {
+            msg!("No write permission for holder house account");
+            return Err(TitleError::AccountNotWritable.into());
+        }
+        check_authority(holder_house_account_info, &td.holder_house_address)?;
+        Some((
+            liege_td.holder_house_address,
+            liege_held_by_treasury,
+            holder_house_account_info,
+        ))
+    };
+    let holdings_account_infos = if empty_liege {
+        None
+    } else {
+        let holder_holdings_account_info = next_account_info(account_info_iter)?;
+        let recipient_holdings_account_info = next_account_info(account_info_iter)?;
+        let system_account_info = next_account_info(account_info_iter)?;
+        Some((
+            holder_holdings_account_info,
+            recipient_holdings_account_info,
+            system_account_info,
+        ))
+    };
+
+    let realm_config = load_realm_config(program_id, realm_config_account_info)?;
+    let slot = Clock::get()?.slot;
+    let tax_owed = td
+        .tax_owed_lamports(slot, &realm_config.settings)
+        .ok_or(TitleError::Overflow)?;
+    let rent = Rent::get().unwrap();
+    let available = stake_account_info
+        .lamports()
+        .saturating_sub(rent.minimum_balance(STAKE_VAULT_SIZE));
+
+    let mut title_data = title_account_info.data.borrow_mut();
+    let mut title = TitleDataMut::new(&mut title_data)?;
+    title.set_last_tax_paid_slot(slot);
+    let payment = if tax_owed <= available {
+        msg!("Collecting {} lamports of tax", tax_owed);
+        tax_owed
+    } else {
+        // Foreclose: the whole vault, including its rent, goes to the recipient, and the
+        // title reverts to an inactive title held by the liege's holder.
+        msg!(
+            "Foreclosing: {} lamports of tax owed, {} available",
+            tax_owed,
+            available
+        );
+        title.set_lifecycle_state(TitleData::INACTIVE_STATE);
+        title.set_stake_address(&Pubkey::new(&[0; 32]));
+        title.set_sale_price_lamports(td.required_stake_lamports);
+        if let (
+            Some((liege_holder_house_address, liege_held_by_treasury, holder_house_account_info)),
+            Some((
+                holder_holdings_account_info,
+                recipient_holdings_account_info,
+                system_account_info,
+            )),
+        ) = (liege_holder_house, holdings_account_infos)
+        {
+            title.set_holder_house_address(&liege_holder_house_address);
+            // The stake pays for the recipient's holdings, before going to the recipient.
+            // The treasury keeps no holdings.
+            if liege_holder_house_address != td.holder_house_address {
+                if !is_held_by_treasury(program_id, &td) {
+                    remove_held_titles(
+                        program_id,
+                        holder_house_account_info,
+                        holder_holdings_account_info,
+                        &[*title_account_info.key],
+                    )?;
+                }
+                if !liege_held_by_treasury {
+                    add_held_titles(
+                        program_id,
+                        recipient_account_info,
+                        recipient_holdings_account_info,
+                        &[*title_account_info.key],
+                        stake_account_info,
+                        system_account_info,
+                    )?;
+                }
+            }
+        }
+        stake_account_info.lamports()
+    };
+    **stake_account_info.lamports.borrow_mut() = stake_account_info
+        .lamports()
+        .checked_sub(payment)
+        .ok_or(TitleError::Overflow)?;
+    **recipient_account_info.lamports.borrow_mut() = recipient_account_info
+        .lamports()
+        .checked_add(payment)
+        .ok_or(TitleError::Overflow)?;
+    Ok(())
+}
+
+/// Processes WithdrawFromHouse instruction
+pub fn process_withdraw_from_house(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    lamports: u64,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+
+    let owner_wallet_info = next_account_info(account_info_iter)?;
+    let house_account_info = next_account_info(account_info_iter)?;
+    let recipient_account_info = next_account_info(account_info_iter)?;
+
+    if !owner_wallet_info.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+    if !house_account_info.is_writable || !recipient_account_info.is_writable {
+        msg!(
+            "No write permission for accounts: {} {}",
+            house_account_info.is_writable,
+            recipient_account_info.is_writable
+        );
+        return Err(TitleError::AccountNotWritable.into());
+    }
+
+    let housedata = check_house_authority(program_id, owner_wallet_info, house_account_info)?;
+    if !housedata.is_initialized() {
+        msg!("Error: house is uninitialized");
+        return Err(TitleError::UninitializedAccount.into());
+    }
+
+    let available = house_account_info
+        .lamports()
+        .saturating_sub(Rent::get()?.minimum_balance(house_account_info.data_len()));
+    if lamports > available {
+        msg!(
+            "House has {} lamports above rent exemption, withdrawing {}",
+            available,
+            lamports
+        );
+        return Err(TitleError::InsufficientLamports.into());
+    }
+    msg!("Withdrawing {} lamports from house", lamports);
+    **house_account_info.lamports.borrow_mut() = house_account_info
+        .lamports()
+        .checked_sub(lamports)
+        .ok_or(TitleError::Overflow)?;
+    **recipient_account_info.lamports.borrow_mut() = recipient_account_info
+        .lamports()
+        .checked_add(lamports)
+        .ok_or(TitleError::Overflow)?;
+    Ok(())
+}
+
+/// Processes UpdateHouse instruction
+pub fn process_update_house(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    coat_of_arms: String,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+
+    let owner_wallet_info = next_account_info(account_info_iter)?;
+    let house_account_info = next_account_info(account_info_iter)?;
+
+    if !owner_wallet_info.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+    if !house_account_info.is_writable {
+        msg!("No write permission for house account");
+        return Err(TitleError::AccountNotWritable.into());
+    }
+    check_coat_of_arms(&coat_of_arms)?;
+
+    let mut housedata = check_house_authority(program_id, owner_wallet_info, house_account_info)?;
+    if !housedata.is_initialized() {
+        msg!("Error: house is uninitialized");
+        return Err(TitleError::UninitializedAccount.into());
+    }
+
+    housedata.coat_of_arms = coat_of_arms;
+    housedata
+        .serialize(&mut *house_account_info.data.borrow_mut())
+        .map_err(|e| e.into())
+}
+
+/// Processes UpdateTitleCoatOfArms instruction
+pub fn process_update_title_coat_of_arms(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    coat_of_arms: String,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+
+    let owner_wallet_info = next_account_info(account_info_iter)?;
+    let house_account_info = next_account_info(account_info_iter)?;
+    let title_account_info = next_account_info(account_info_iter)?;
+
+    if !owner_wallet_info.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+    if !title_account_info.is_writable {
+        msg!("No write permission for title account");
+        return Err(TitleError::AccountNotWritable.into());
+    }
+    check_coat_of_arms(&coat_of_arms)?;
+    let coat_of_arms = pack_string(&coat_of_arms)?;
+
+    // Only the holder of the title may change its heraldry.
+    check_house_authority(program_id, owner_wallet_info, house_account_info)?;
+    let td = load_account::<TitleData>(program_id, title_account_info)?;
+    if !td.is_initialized() {
+        msg!("Error: title is uninitialized");
+        return Err(TitleError::UninitializedAccount.into());
+    }
+    check_authority(house_account_info, &td.holder_house_address)?;
+
+    TitleDataMut::new(&mut title_account_info.data.borrow_mut())?.set_coat_of_arms(&coat_of_arms);
+    Ok(())
+}
+
+/// Processes MigrateAccount instruction
+pub fn process_migrate_account(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+
+    let funder_wallet_info = next_account_info(account_info_iter)?;
+    let account_info = next_account_info(account_info_iter)?;
+    let system_account_info = next_account_info(account_info_iter)?;
+
+    if !funder_wallet_info.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+    if !funder_wallet_info.is_writable || !account_info.is_writable {
+        msg!(
+            "No write permission for accounts: {} {}",
+            funder_wallet_info.is_writable,
+            account_info.is_writable
+        );
+        return Err(TitleError::AccountNotWritable.into());
+    }
+    check_system_program(funder_wallet_info.owner)?;
+    check_account_owner(program_id, account_info)?;
+
+    let versioned = VersionedAccount::decode(&account_info.data.borrow())?;
+    if versioned.is_current() {
+        msg!("Account is already at the current version");
+        return Err(TitleError::AccountAlreadyCurrent.into());
+    }
+    let (data, new_len) = match versioned {
+        VersionedAccount::HouseV1(v1) => {
+            let mut house = HouseData::from(v1);
+            house.last_proof_of_life_slot = Clock::get()?.slot;
+            (house.try_to_vec()?, HouseData::SIZE)
+        }
+        VersionedAccount::HouseV2(mut house) => {
+            // The titles held by older houses aren't known.
+            house.version = HouseData::CURRENT_VERSION;
+            house.held_title_count = HouseData::UNKNOWN_TITLE_COUNT;
+            house.last_proof_of_life_slot = Clock::get()?.slot;
+            (house.try_to_vec()?, HouseData::SIZE)
+        }
+        VersionedAccount::HouseV5(mut house) => {
+            // Houses that predate proofs of life start their vacancy period now.
+            house.version = HouseData::CURRENT_VERSION;
+            house.last_proof_of_life_slot = Clock::get()?.slot;
+            (house.try_to_vec()?, HouseData::SIZE)
+        }
+        VersionedAccount::HouseV4(mut house) => {
+            house.version = HouseData::CURRENT_VERSION;
+            house.last_proof_of_life_slot = Clock::get()?.slot;
+            (house.try_to_vec()?, HouseData::SIZE)
+        }
+        VersionedAccount::HouseV3(mut house) => {
+            // Without an authority, the house stays governed by the wallet it is
+            // derived from.
+            house.version = HouseData::CURRENT_VERSION;
+            house.last_proof_of_life_slot = Clock::get()?.slot;
+            (house.try_to_vec()?, HouseData::SIZE)
+        }
+        VersionedAccount::TitleV1(v1) => {
+            let mut title = TitleData::from(&v1);
+            title.last_tax_paid_slot = Clock::get()?.slot;
+            create_migrated_vassal_pages(
+                program_id,
+                account_info.key,
+                &v1.vassal_addresses,
+                account_info_iter,
+                funder_wallet_info,
+                system_account_info,
+            )?;
+            let mut data = vec![0; TitleData::SIZE];
+            title.pack_into_slice(&mut data);
+            (data, TitleData::SIZE)
+        }
+        VersionedAccount::TitleV2(v2) => {
+            let mut title = TitleData::from(&v2);
+            // Inactive titles created before escheat get a full grace period.
+            if title.lifecycle_state != TitleData::ACTIVE_STATE {
+                title.last_tax_paid_slot = Clock::get()?.slot;
+            }
+            create_migrated_vassal_pages(
+                program_id,
+                account_info.key,
+                &v2.vassal_addresses,
+                account_info_iter,
+                funder_wallet_info,
+                system_account_info,
+            )?;
+            let mut data = vec![0; TitleData::SIZE];
+            title.pack_into_slice(&mut data);
+            (data, TitleData::SIZE)
+        }
+        VersionedAccount::TitleV5(mut title) => {
+            title.version = TitleData::CURRENT_VERSION;
+            let mut data = vec![0; TitleData::SIZE];
+            title.pack_into_slice(&mut data);
+            (data, TitleData::SIZE)
+        }
+        VersionedAccount::TitleV4(mut title) => {
+            title.version = TitleData::CURRENT_VERSION;
+            // Inactive titles created before escheat get a full grace period.
+            if title.lifecycle_state != TitleData::ACTIVE_STATE {
+                title.last_tax_paid_slot = Clock::get()?.slot;
+            }
+            // Titles couldn't move to another liege before version 5.
+            title.seed_liege_address = title.liege_address;
+            title.seed_liege_vassal_index = title.liege_vassal_index;
+            title.live_vassal_count = count_live_vassals(
+                program_id,
+                account_info.key,
+                title.vassal_count,
+                account_info_iter,
+            )?;
+            let mut data = vec![0; TitleData::SIZE];
+            title.pack_into_slice(&mut data);
+            (data, TitleData::SIZE)
+        }
+        VersionedAccount::TitleV3(mut title) => {
+            // Version 3 titles stay in the legacy realm. Inactive titles created before
+            // escheat get a full grace period.
+            title.version = TitleData::CURRENT_VERSION;
+            // Titles couldn't move to another liege before version 5.
+            title.seed_liege_address = title.liege_address;
+            title.seed_liege_vassal_index = title.liege_vassal_index;
+            title.live_vassal_count = count_live_vassals(
+                program_id,
+                account_info.key,
+                title.vassal_count,
+                account_info_iter,
+            )?;
+            if title.lifecycle_state != TitleData::ACTIVE_STATE {
+                title.last_tax_paid_slot = Clock::get()?.slot;
+            }
+            let mut data = vec![0; TitleData::SIZE];
+            title.pack_into_slice(&mut data);
+            (data, TitleData::SIZE)
+        }
+        VersionedAccount::RealmConfigV2(mut config) => {
+            config.version = RealmConfigData::CURRENT_VERSION;
+            config.settings.election_period_slots = RealmSettings::default().election_period_slots;
+            config.settings.vacancy_period_slots = RealmSettings::default().vacancy_period_slots;
+            (config.try_to_vec()?, RealmConfigData::SIZE)
+        }
+        VersionedAccount::RealmConfigV1(mut config) => {
+            config.version = RealmConfigData::CURRENT_VERSION;
+            config.settings.escheat_grace_period_slots =
+                RealmSettings::default().escheat_grace_period_slots;
+            config.settings.election_period_slots = RealmSettings::default().election_period_slots;
+            config.settings.vacancy_period_slots = RealmSettings::default().vacancy_period_slots;
+            (config.try_to_vec()?, RealmConfigData::SIZE)
+        }
+        VersionedAccount::RealmV1(v1) => {
+            // Record the root title, passed after the system program, if it was already
+            // created.
+            let mut realm = RealmData::from(v1);
+            let root_title_account_info = next_account_info(account_info_iter)?;
+            let (root_title_address, _) =
+                get_root_title_address_and_bump_seed_internal(account_info.key, program_id);
+            if root_title_address != *root_title_account_info.key {
+                msg!("Error: Root title address does not match seed derivation");
+                return Err(ProgramError::InvalidSeeds);
+            }
+            if root_title_account_info.owner == program_id
+                && !root_title_account_info.data_is_empty()
+            {
+                realm.root_title_address = root_title_address;
+            }
+            (realm.try_to_vec()?, RealmData::SIZE)
+        }
+        VersionedAccount::House(_)
+        | VersionedAccount::Title(_)
+        | VersionedAccount::RealmConfig(_)
+        | VersionedAccount::Realm(_) => unreachable!(),
+    };
+
+    // Top up rent for the new size before growing the account.
+    let rent = Rent::get().unwrap();
+    let shortfall = rent
+        .minimum_balance(new_len)
+        .saturating_sub(account_info.lamports());
+    if shortfall > 0 {
+        invoke(
+            &system_instruction::transfer(funder_wallet_info.key, account_info.key, shortfall),
+            &[
+                funder_wallet_info.clone(),
+                account_info.clone(),
+                system_account_info.clone(),
+            ],
+        )?;
+    }
+
+    msg!(
+        "Migrating account from {} to {} bytes",
+        account_info.data_len(),
+        new_len
+    );
+    account_info.realloc(new_len, false)?;
+    let dst: &mut [u8] = &mut account_info.data.borrow_mut();
+    dst.fill(0);
+    dst[..data.len()].copy_from_slice(&data);
+    Ok(())
+}
+
+/// Move the vassal list of a title written before vassal pages into new vassal pages,
+/// passed as the next accounts.
+fn create_migrated_vassal_pages<'a, 'b: 'a>(
+    program_id: &Pubkey,
+    title_address: &Pubkey,
+    vassal_addresses: &[Pubkey],
+    account_info_iter: &mut std::slice::Iter<'a, AccountInfo<'b>>,
+    funder_wallet_info: &AccountInfo<'b>,
+    system_account_info: &AccountInfo<'b>,
+) -> ProgramResult {
+    for (page_index, vassal_addresses) in vassal_addresses
+        .chunks(VassalPageData::VASSALS_PER_PAGE)
+        .enumerate()
+    {
+        let vassal_page_account_info = next_account_info(account_info_iter)?;
+        create_vassal_page(
+            program_id,
+            title_address,
+            page_index as u16,
+            vassal_page_account_info,
+            funder_wallet_info,
+            system_account_info,
+            vassal_addresses.to_vec(),
+        )?;
+    }
+    Ok(())
+}
+
+/// Processes InitializeRealmConfig instruction
+pub fn process_initialize_realm_config(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    admin: Pubkey,
+    settings: RealmSettings,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+
+    let upgrade_authority_info = next_account_info(account_info_iter)?;
+    let realm_config_account_info = next_account_info(account_info_iter)?;
+    let program_data_account_info = next_account_info(account_info_iter)?;
+    let system_account_info = next_account_info(account_info_iter)?;
+
+    if !upgrade_authority_info.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+    if !upgrade_authority_info.is_writable || !realm_config_account_info.is_writable {
+        return Err(TitleError::AccountNotWritable.into());
+    }
+    check_system_program(upgrade_authority_info.owner)?;
+    check_realm_settings(&settings)?;
+
+    // Only whoever may upgrade the program may set up its realm.
+    let (program_data_address, _) =
+        Pubkey::find_program_address(&[program_id.as_ref()], &bpf_loader_upgradeable::id());
+    if program_data_address != *program_data_account_info.key
+        || *program_data_account_info.owner != bpf_loader_upgradeable::id()
+    {
+        msg!("Error: Incorrect ProgramData account");
+        return Err(ProgramError::InvalidArgument);
+    }
+    let upgrade_authority = get_upgrade_authority(&program_data_account_info.data.borrow());
+    if upgrade_authority != Some(*upgrade_authority_info.key) {
+        msg!(
+            "Expected upgrade authority {:?}, got {}",
+            upgrade_authority,
+            upgrade_authority_info.key
+        );
+        return Err(TitleError::IncorrectAuthority.into());
+    }
+
+    let (realm_config_address, bump_seed) =
+        get_realm_config_address_and_bump_seed_internal(program_id);
+    if realm_config_address != *realm_config_account_info.key {
+        msg!("Error: Realm config address does not match seed derivation");
+        return Err(ProgramError::InvalidSeeds);
+    }
+    let realm_config_signer_seeds: &[&[_]] = &[b"config", &[bump_seed]];
+
+    // Creating the account fails if the config was already initialized.
+    let rent = Rent::get()?;
+    invoke_signed(
+        &system_instruction::create_account(
+            upgrade_authority_info.key,
+            realm_config_account_info.key,
+            rent.minimum_balance(RealmConfigData::SIZE).max(1),
+            RealmConfigData::SIZE as u64,
+            program_id,
+        ),
+        &[
+            upgrade_authority_info.clone(),
+            realm_config_account_info.clone(),
+            system_account_info.clone(),
+        ],
+        &[realm_config_signer_seeds],
+    )?;
+
+    RealmConfigData {
+        account_type: AccountType::RealmConfig,
+        version: RealmConfigData::CURRENT_VERSION,
+        admin,
+        settings,
+    }
+    .serialize(&mut *realm_config_account_info.data.borrow_mut())
+    .map_err(|e| e.into())
+}
+
+/// Processes UpdateRealmConfig instruction
+pub fn process_update_realm_config(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    admin: Pubkey,
+    settings: RealmSettings,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+
+    let admin_wallet_info = next_account_info(account_info_iter)?;
+    let realm_config_account_info = next_account_info(account_info_iter)?;
+
+    if !admin_wallet_info.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+    if !realm_config_account_info.is_writable {
+        msg!("No write permission for realm config account");
+        return Err(TitleError::AccountNotWritable.into());
+    }
+    check_realm_settings(&settings)?;
+
+    let mut realm_config = load_realm_config(program_id, realm_config_account_info)?;
+    if realm_config.admin != *admin_wallet_info.key {
+        msg!(
+            "Expected realm admin {}, got {}",
+            realm_config.admin,
+            admin_wallet_info.key
+        );
+        return Err(TitleError::IncorrectAuthority.into());
+    }
+
+    realm_config.admin = admin;
+    realm_config.settings = settings;
+    realm_config
+        .serialize(&mut *realm_config_account_info.data.borrow_mut())
+        .map_err(|e| e.into())
+}
+
+/// Processes CreateRealm instruction
+pub fn process_create_realm(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    name: String,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+
+    let creator_wallet_info = next_account_info(account_info_iter)?;
+    let realm_account_info = next_account_info(account_info_iter)?;
+    let system_account_info = next_account_info(account_info_iter)?;
+
+    if !creator_wallet_info.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+    if !creator_wallet_info.is_writable || !realm_account_info.is_writable {
+        return Err(TitleError::AccountNotWritable.into());
+    }
+    check_system_program(creator_wallet_info.owner)?;
+    validate_realm_name(name.as_bytes()).map_err(|e| {
+        msg!("Invalid realm name {:?}: {}", name, e);
+        e
+    })?;
+
+    let (realm_address, bump_seed) = get_realm_address_and_bump_seed_internal(&name, program_id);
+    if realm_address != *realm_account_info.key {
+        msg!("Error: Realm address does not match seed derivation");
+        return Err(ProgramError::InvalidSeeds);
+    }
+    let realm_account_signer_seeds: &[&[_]] = &[b"realm", name.as_bytes(), &[bump_seed]];
+
+    // Creating the account fails if a realm of that name already exists.
+    let rent = Rent::get()?;
+    invoke_signed(
+        &system_instruction::create_account(
+            creator_wallet_info.key,
+            realm_account_info.key,
+            rent.minimum_balance(RealmData::SIZE).max(1),
+            RealmData::SIZE as u64,
+            program_id,
+        ),
+        &[
+            creator_wallet_info.clone(),
+            realm_account_info.clone(),
+            system_account_info.clone(),
+        ],
+        &[realm_account_signer_seeds],
+    )?;
+
+    RealmData {
+        account_type: AccountType::Realm,
+        version: RealmData::CURRENT_VERSION,
+        authority: *creator_wallet_info.key,
+        root_title_address: Pubkey::new(&[0; 32]),
+        name,
+    }
+    .serialize(&mut *realm_account_info.data.borrow_mut())
+    .map_err(|e| e.into())
+}
+
+/// Processes WithdrawFromTreasury instruction
+pub fn process_withdraw_from_treasury(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    lamports: u64,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+
+    let authority_wallet_info = next_account_info(account_info_iter)?;
+    let realm_config_account_info = next_account_info(account_info_iter)?;
+    let realm_account_info = next_account_info(account_info_iter)?;
+    let treasury_account_info = next_account_info(account_info_iter)?;
+    let recipient_account_info = next_account_info(account_info_iter)?;
+    let system_account_info = next_account_info(account_info_iter)?;
+
+    if !authority_wallet_info.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+    if !treasury_account_info.is_writable || !recipient_account_info.is_writable {
+        msg!(
+            "No write permission for accounts: {} {}",
+            treasury_account_info.is_writable,
+            recipient_account_info.is_writable
+        );
+        return Err(TitleError::AccountNotWritable.into());
+    }
+
+    // The admin may withdraw from any treasury, the realm authority from its realm's.
+    // The legacy realm has no realm account, and so no authority.
+    let realm_config = load_realm_config(program_id, realm_config_account_info)?;
+    if *authority_wallet_info.key != realm_config.admin {
+        let realm = load_account::<RealmData>(program_id, realm_account_info)?;
+        if !realm.is_initialized() {
+            msg!("Error: realm is uninitialized");
+            return Err(TitleError::UninitializedAccount.into());
+        }
+        if *authority_wallet_info.key != realm.authority {
+            msg!(
+                "Treasury of realm {} may only be withdrawn by {} or {}, got {}",
+                realm.name,
+                realm.authority,
+                realm_config.admin,
+                authority_wallet_info.key
+            );
+            return Err(TitleError::NotRealmAuthority.into());
+        }
+    }
+
+    let (treasury_address, bump_seed) =
+        get_treasury_address_and_bump_seed_internal(realm_account_info.key, program_id);
+    if treasury_address != *treasury_account_info.key {
+        msg!("Error: Treasury address does not match seed derivation");
+        return Err(ProgramError::InvalidSeeds);
+    }
+    let treasury_account_signer_seeds: &[&[_]] = &[
+        b"treasury",
+        &realm_account_info.key.to_bytes(),
+        &[bump_seed],
+    ];
+
+    let available = treasury_account_info
+        .lamports()
+        .saturating_sub(Rent::get()?.minimum_balance(0));
+    if lamports > available {
+        msg!(
+            "Treasury has {} lamports above rent exemption, withdrawing {}",
+            available,
+            lamports
+        );
+        return Err(TitleError::InsufficientLamports.into());
+    }
+    msg!("Withdrawing {} lamports from treasury", lamports);
+    invoke_signed(
+        &system_instruction::transfer(
+            treasury_account_info.key,
+            recipient_account_info.key,
+            lamports,
+        ),
+        &[
+            treasury_account_info.clone(),
+            recipient_account_info.clone(),
+            system_account_info.clone(),
+        ],
+        &[treasury_account_signer_seeds],
+    )
+}
+
+/// Processes PetitionVassalage instruction
+pub fn process_petition_vassalage(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    rank: Rank,
+    kind: Kind,
+    required_stake_lamports: u64,
+    coat_of_arms: String,
+    display_name: String,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+
+    let petitioner_wallet_info = next_account_info(account_info_iter)?;
+    let petitioner_house_account_info = next_account_info(account_info_iter)?;
+    let petition_account_info = next_account_info(account_info_iter)?;
+    let liege_title_account_info = next_account_info(account_info_iter)?;
+    let system_account_info = next_account_info(account_info_iter)?;
+    let realm_config_account_info = next_account_info(account_info_iter)?;
+
+    if !petitioner_wallet_info.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+    if !petitioner_wallet_info.is_writable || !petition_account_info.is_writable {
+        return Err(TitleError::AccountNotWritable.into());
+    }
+    check_system_program(petitioner_wallet_info.owner)?;
+
+    let housedata = check_house_authority(
+        program_id,
+        petitioner_wallet_info,
+        petitioner_house_account_info,
+    )?;
+    if !housedata.is_initialized() {
+        msg!("Error: petitioner house is uninitialized");
+        return Err(TitleError::UninitializedAccount.into());
+    }
+    let td = load_account::<TitleData>(program_id, liege_title_account_info)?;
+    if !td.is_initialized() {
+        msg!("Error: liege title is uninitialized");
+        return Err(TitleError::UninitializedAccount.into());
+    }
+    if td.rank >= rank {
+        msg!(
+            "Rank of new title ({}) must be below rank of liege title ({})",
+            rank,
+            td.rank
+        );
+        return Err(TitleError::RankNotBelowLiege.into());
+    }
+    let realm_config = load_realm_config(program_id, realm_config_account_info)?;
+    check_rank_and_kind(&realm_config.settings, rank, kind)?;
+    check_coat_of_arms(&coat_of_arms)?;
+    check_display_name(&display_name)?;
+
+    let (petition_address, bump_seed) = get_petition_address_and_bump_seed_internal(
+        liege_title_account_info.key,
+        petitioner_house_account_info.key,
+        program_id,
+    );
+    if petition_address != *petition_account_info.key {
+        msg!("Error: Petition address does not match seed derivation");
+        return Err(ProgramError::InvalidSeeds);
+    }
+    let petition_account_signer_seeds: &[&[_]] = &[
+        b"petition",
+        &liege_title_account_info.key.to_bytes(),
+        &petitioner_house_account_info.key.to_bytes(),
+        &[bump_seed],
+    ];
+
+    // The petition also holds the rent of the new title, see process_accept_petition.
+    // Creating the account fails if the house already petitions this liege.
+    let rent = Rent::get()?;
+    let required_lamports = rent.minimum_balance(PetitionData::SIZE).max(1)
+        + rent.minimum_balance(TitleData::SIZE).max(1);
+    invoke_signed(
+        &system_instruction::create_account(
+            petitioner_wallet_info.key,
+            petition_account_info.key,
+            required_lamports,
+            PetitionData::SIZE as u64,
+            program_id,
+        ),
+        &[
+            petitioner_wallet_info.clone(),
+            petition_account_info.clone(),
+            system_account_info.clone(),
+        ],
+        &[petition_account_signer_seeds],
+    )?;
+
+    PetitionData {
+        account_type: AccountType::Petition,
+        version: PetitionData::CURRENT_VERSION,
+        petitioner_house_address: *petitioner_house_account_info.key,
+        liege_address: *liege_title_account_info.key,
+        rank,
+        kind,
+        required_stake_lamports,
+        coat_of_arms,
+        display_name,
+    }
+    .serialize(&mut *petition_account_info.data.borrow_mut())
+    .map_err(|e| e.into())
+}
+
+/// Processes AcceptPetition instruction
+pub fn process_accept_petition(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    liege_vassal_index: u16,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+
+    let liege_holder_wallet_info = next_account_info(account_info_iter)?;
+    let liege_holder_house_account_info = next_account_info(account_info_iter)?;
+    let liege_title_account_info = next_account_info(account_info_iter)?;
+    let petition_account_info = next_account_info(account_info_iter)?;
+    let petitioner_wallet_info = next_account_info(account_info_iter)?;
+    let new_title_account_info = next_account_info(account_info_iter)?;
+    let vassal_page_account_info = next_account_info(account_info_iter)?;
+    let system_account_info = next_account_info(account_info_iter)?;
+    let realm_config_account_info = next_account_info(account_info_iter)?;
+    let petitioner_house_account_info = next_account_info(account_info_iter)?;
+    let petitioner_holdings_account_info = next_account_info(account_info_iter)?;
+
+    if !liege_holder_wallet_info.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+    if !liege_holder_wallet_info.is_writable
+        || !liege_title_account_info.is_writable
+        || !petition_account_info.is_writable
+        || !petitioner_wallet_info.is_writable
+        || !new_title_account_info.is_writable
+        || !petitioner_house_account_info.is_writable
+    {
+        msg!(
+            "No write permission for accounts: {} {} {} {} {} {}",
+            liege_holder_wallet_info.is_writable,
+            liege_title_account_info.is_writable,
+            petition_account_info.is_writable,
+            petitioner_wallet_info.is_writable,
+            new_title_account_info.is_writable,
+            petitioner_house_account_info.is_writable
+        );
+        return Err(TitleError::AccountNotWritable.into());
+    }
+    check_system_program(liege_holder_wallet_info.owner)?;
+
+    check_house_authority(
+        program_id,
+        liege_holder_wallet_info,
+        liege_holder_house_account_info,
+    )?;
+    let td = load_account::<TitleData>(program_id, liege_title_account_info)?;
+    check_authority(liege_holder_house_account_info, &td.holder_house_address)?;
+    let petition = load_petition(program_id, petition_account_info, liege_title_account_info)?;
+    check_petitioner(
+        program_id,
+        petitioner_wallet_info,
+        petitioner_house_account_info,
+        &petition,
+    )?;
+
+    if td.vassal_count != liege_vassal_index {
+        msg!(
+            "Cannot add vassal #{}, liege has {} vassals",
+            liege_vassal_index,
+            td.vassal_count
+        );
+        return Err(TitleError::IncorrectVassalIndex.into());
+    }
+    // The realm's rules may have changed since the petition was made.
+    let realm_config = load_realm_config(program_id, realm_config_account_info)?;
+    check_rank_and_kind(&realm_config.settings, petition.rank, petition.kind)?;
+    check_vassal_capacity(&realm_config.settings, &td)?;
+
+    let (title_address, bump_seed) = get_title_address_and_bump_seed_internal(
+        liege_title_account_info.key,
+        liege_vassal_index,
+        program_id,
+    );
+    if title_address != *new_title_account_info.key {
+        msg!("Error: New title address does not match seed derivation");
+        return Err(ProgramError::InvalidSeeds);
+    }
+    let title_account_signer_seeds: &[&[_]] = &[
+        &liege_title_account_info.key.to_bytes(),
+        &liege_vassal_index.to_le_bytes(),
+        &[bump_seed],
+    ];
+
+    append_vassal(
+        program_id,
+        &td,
+        liege_title_account_info,
+        vassal_page_account_info,
+        liege_holder_wallet_info,
+        system_account_info,
+        &title_address,
+    )?;
+
+    // Fund the new title from the rent held by the petition. The system program can't
+    // create an account funded by a program-owned one, so the title is funded directly
+    // and then allocated and assigned. This fails if the title address is in use.
+    let title_lamports = Rent::get()?.minimum_balance(TitleData::SIZE).max(1);
+    let petition_lamports = petition_account_info
+        .lamports()
+        .checked_sub(title_lamports)
+        .ok_or(TitleError::Overflow)?;
+    let new_title_lamports = new_title_account_info
+        .lamports()
+        .checked_add(title_lamports)
+        .ok_or(TitleError::Overflow)?;
+    **petition_account_info.try_borrow_mut_lamports()? = petition_lamports;
+    **new_title_account_info.try_borrow_mut_lamports()? = new_title_lamports;
+
+    msg!("Creating title_address: {}", title_address);
+    invoke_signed(
+        &system_instruction::allocate(new_title_account_info.key, TitleData::SIZE as u64),
+        &[new_title_account_info.clone(), system_account_info.clone()],
+        &[title_account_signer_seeds],
+    )?;
+    invoke_signed(
+        &system_instruction::assign(new_title_account_info.key, program_id),
+        &[new_title_account_info.clone(), system_account_info.clone()],
+        &[title_account_signer_seeds],
+    )?;
+
+    let PetitionData {
+        petitioner_house_address,
+        rank,
+        kind,
+        required_stake_lamports,
+        coat_of_arms,
+        display_name,
+        ..
+    } = petition;
+    let title_data_struct: TitleData = TitleData {
+        account_type: AccountType::Title,
+        version: TitleData::CURRENT_VERSION,
+        lifecycle_state: TitleData::INACTIVE_STATE,
+        rank,
+        kind,
+        required_stake_lamports,
+        sale_price_lamports: required_stake_lamports,
+        last_tax_paid_slot: Clock::get()?.slot,
+        holder_house_address: petitioner_house_address,
+        stake_address: Pubkey::new(&[0; 32]),
+        liege_address: *liege_title_account_info.key,
+        liege_vassal_index,
+        vassal_count: 0,
+        coat_of_arms: pack_string(&coat_of_arms)?,
+        display_name: pack_string(&display_name)?,
+        realm_address: td.realm_address,
+        live_vassal_count: 0,
+        seed_liege_address: *liege_title_account_info.key,
+        seed_liege_vassal_index: liege_vassal_index,
+        succession_law: SuccessionLaw::Heir,
+    };
+    title_data_struct.pack_into_slice(&mut new_title_account_info.data.borrow_mut());
+    // The petitioner's holdings are paid from the rest of the petition's rent.
+    add_held_titles(
+        program_id,
+        petitioner_house_account_info,
+        petitioner_holdings_account_info,
+        &[title_address],
+        petition_account_info,
+        system_account_info,
+    )?;
+
+    close_account(petition_account_info, petitioner_wallet_info)
+}
+
+/// Processes RejectPetition instruction
+pub fn process_reject_petition(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+
+    let signer_wallet_info = next_account_info(account_info_iter)?;
+    let signer_house_account_info = next_account_info(account_info_iter)?;
+    let liege_title_account_info = next_account_info(account_info_iter)?;
+    let petition_account_info = next_account_info(account_info_iter)?;
+    let petitioner_wallet_info = next_account_info(account_info_iter)?;
+    let petitioner_house_account_info = next_account_info(account_info_iter)?;
+
+    if !signer_wallet_info.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+    if !petition_account_info.is_writable || !petitioner_wallet_info.is_writable {
+        return Err(TitleError::AccountNotWritable.into());
+    }
+
+    check_house_authority(program_id, signer_wallet_info, signer_house_account_info)?;
+    let td = load_account::<TitleData>(program_id, liege_title_account_info)?;
+    let petition = load_petition(program_id, petition_account_info, liege_title_account_info)?;
+    check_petitioner(
+        program_id,
+        petitioner_wallet_info,
+        petitioner_house_account_info,
+        &petition,
+    )?;
+    // The liege holder rejects the petition, the petitioner withdraws it.
+    if *signer_house_account_info.key != td.holder_house_address
+        && *signer_house_account_info.key != petition.petitioner_house_address
+    {
+        msg!(
+            "Expected house {} or {}, got house {}",
+            td.holder_house_address,
+            petition.petitioner_house_address,
+            signer_house_account_info.key
+        );
+        return Err(TitleError::IncorrectAuthority.into());
+    }
+
+    close_account(petition_account_info, petitioner_wallet_info)
+}
+
+/// Processes ReparentTitle instruction
+pub fn process_reparent_title(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    new_liege_vassal_index: u16,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+
+    let holder_wallet_info = next_account_info(account_info_iter)?;
+    let holder_house_account_info = next_account_info(account_info_iter)?;
+    let title_account_info = next_account_info(account_info_iter)?;
+    let vassal_page_account_info = next_account_info(account_info_iter)?;
+    let new_liege_holder_wallet_info = next_account_info(account_info_iter)?;
+    let new_liege_holder_house_account_info = next_account_info(account_info_iter)?;
+    let new_liege_title_account_info = next_account_info(account_info_iter)?;
+    let new_vassal_page_account_info = next_account_info(account_info_iter)?;
+    let system_account_info = next_account_info(account_info_iter)?;
+    let realm_config_account_info = next_account_info(account_info_iter)?;
+    let liege_title_account_info = next_account_info(account_info_iter)?;
+
+    if !holder_wallet_info.is_signer || !new_liege_holder_wallet_info.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+    if !title_account_info.is_writable
+        || !vassal_page_account_info.is_writable
+        || !new_liege_holder_wallet_info.is_writable
+        || !new_liege_title_account_info.is_writable
+        || !liege_title_account_info.is_writable
+    {
+        msg!(
+            "No write permission for accounts: {} {} {} {} {}",
+            title_account_info.is_writable,
+            vassal_page_account_info.is_writable,
+            new_liege_holder_wallet_info.is_writable,
+            new_liege_title_account_info.is_writable,
+            liege_title_account_info.is_writable
+        );
+        return Err(TitleError::AccountNotWritable.into());
+    }
+    check_system_program(new_liege_holder_wallet_info.owner)?;
+
+    // Both the title's holder and the new liege's holder consent.
+    check_house_authority(program_id, holder_wallet_info, holder_house_account_info)?;
+    let td = load_account::<TitleData>(program_id, title_account_info)?;
+    if !td.is_initialized() {
+        msg!("Error: title is uninitialized");
+        return Err(TitleError::UninitializedAccount.into());
+    }
+    check_authority(holder_house_account_info, &td.holder_house_address)?;
+    check_house_authority(
+        program_id,
+        new_liege_holder_wallet_info,
+        new_liege_holder_house_account_info,
+    )?;
+    let new_liege_td = load_account::<TitleData>(program_id, new_liege_title_account_info)?;
+    if !new_liege_td.is_initialized() {
+        msg!("Error: new liege title is uninitialized");
+        return Err(TitleError::UninitializedAccount.into());
+    }
+    check_authority(
+        new_liege_holder_house_account_info,
+        &new_liege_td.holder_house_address,
+    )?;
+
+    if td.liege_address == Pubkey::new(&[0; 32]) {
+        msg!("Root title can't have a liege");
+        return Err(TitleError::RootTitleHasLiege.into());
+    }
+    if td.liege_address == *new_liege_title_account_info.key {
+        msg!("Title is already a vassal of {}", td.liege_address);
+        return Err(TitleError::AlreadyVassal.into());
+    }
+    if td.liege_address != *liege_title_account_info.key {
+        msg!(
+            "Expected liege {}, got {}",
+            td.liege_address,
+            liege_title_account_info.key
+        );
+        return Err(TitleError::IncorrectLiege.into());
+    }
+    let liege_td = load_account::<TitleData>(program_id, liege_title_account_info)?;
+    // A liege outranks all of its vassals, so this also keeps the title from becoming
+    // a vassal of itself or of one of its own vassals.
+    if new_liege_td.rank >= td.rank {
+        msg!(
+            "Rank of title ({}) must be below rank of new liege title ({})",
+            td.rank,
+            new_liege_td.rank
+        );
+        return Err(TitleError::RankNotBelowLiege.into());
+    }
+    if new_liege_td.realm_address != td.realm_address {
+        msg!(
+            "New liege is in realm {}, title is in realm {}",
+            new_liege_td.realm_address,
+            td.realm_address
+        );
+        return Err(TitleError::RealmMismatch.into());
+    }
+    if new_liege_td.vassal_count != new_liege_vassal_index {
+        msg!(
+            "Cannot add vassal #{}, new liege has {} vassals",
+            new_liege_vassal_index,
+            new_liege_td.vassal_count
+        );
+        return Err(TitleError::IncorrectVassalIndex.into());
+    }
+    // Tax accrued so far is owed to the old liege.
+    check_tax_settled(&td)?;
+    let realm_config = load_realm_config(program_id, realm_config_account_info)?;
+    check_vassal_capacity(&realm_config.settings, &new_liege_td)?;
+
+    // Leave the title's entry in the old liege's list all zeroes, so that the old
+    // liege's other vassals keep their index.
+    let (page_index, position) = VassalPageData::locate(td.liege_vassal_index);
+    let (page_address, _) =
+        get_vassal_page_address_and_bump_seed_internal(&td.liege_address, page_index, program_id);
+    if page_address != *vassal_page_account_info.key {
+        msg!("Error: Vassal page address does not match seed derivation");
+        return Err(ProgramError::InvalidSeeds);
+    }
+    let mut page = load_account::<VassalPageData>(program_id, vassal_page_account_info)?;
+    match page.vassal_addresses.get_mut(position) {
+        Some(vassal_address) if vassal_address == title_account_info.key => {
+            *vassal_address = Pubkey::new(&[0; 32]);
+        }
+        _ => {
+            msg!(
+                "Title is not vassal #{} of {}",
+                td.liege_vassal_index,
+                td.liege_address
+            );
+            return Err(TitleError::IncorrectVassalIndex.into());
+        }
+    }
+    page.serialize(&mut *vassal_page_account_info.data.borrow_mut())?;
+    TitleDataMut::new(&mut liege_title_account_info.data.borrow_mut())?
+        .set_live_vassal_count(liege_td.live_vassal_count.saturating_sub(1));
+
+    append_vassal(
+        program_id,
+        &new_liege_td,
+        new_liege_title_account_info,
+        new_vassal_page_account_info,
+        new_liege_holder_wallet_info,
+        system_account_info,
+        title_account_info.key,
+    )?;
+    TitleDataMut::new(&mut title_account_info.data.borrow_mut())?
+        .set_liege(new_liege_title_account_info.key, new_liege_vassal_index);
+    Ok(())
+}
+
+/// Processes EscheatTitle instruction
+pub fn process_escheat_title(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+
+    let liege_holder_wallet_info = next_account_info(account_info_iter)?;
+    let liege_holder_house_account_info = next_account_info(account_info_iter)?;
+    let liege_title_account_info = next_account_info(account_info_iter)?;
+    let title_account_info = next_account_info(account_info_iter)?;
+    let holder_house_account_info = next_account_info(account_info_iter)?;
+    let realm_config_account_info = next_account_info(account_info_iter)?;
+    let holder_holdings_account_info = next_account_info(account_info_iter)?;
+    let liege_holder_holdings_account_info = next_account_info(account_info_iter)?;
+    let system_account_info = next_account_info(account_info_iter)?;
+
+    if !liege_holder_wallet_info.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+    if !liege_holder_wallet_info.is_writable
+        || !liege_holder_house_account_info.is_writable
+        || !title_account_info.is_writable
+        || !holder_house_account_info.is_writable
+    {
+        msg!(
+            "No write permission for accounts: {} {} {} {}",
+            liege_holder_wallet_info.is_writable,
+            liege_holder_house_account_info.is_writable,
+            title_account_info.is_writable,
+            holder_house_account_info.is_writable
+        );
+        return Err(TitleError::AccountNotWritable.into());
+    }
+
+    // Only the holder of the title's liege may escheat it.
+    check_house_authority(
+        program_id,
+        liege_holder_wallet_info,
+        liege_holder_house_account_info,
+    )?;
+    let td = load_account::<TitleData>(program_id, title_account_info)?;
+    if !td.is_initialized() {
+        msg!("Error: title is uninitialized");
+        return Err(TitleError::UninitializedAccount.into());
+    }
+    if td.liege_address != *liege_title_account_info.key {
+        msg!(
+            "Expected liege {}, got {}",
+            td.liege_address,
+            liege_title_account_info.key
+        );
+        return Err(TitleError::IncorrectLiege.into());
+    }
+    let liege_td = load_account::<TitleData>(program_id, liege_title_account_info)?;
+    check_authority(
+        liege_holder_house_account_info,
+        &liege_td.holder_house_address,
+    )?;
+    if td.holder_house_address == liege_td.holder_house_address {
+        msg!("Error: liege house already holds this title");
+        return Err(TitleError::AlreadyTitleHolder.into());
+    }
+    check_authority(holder_house_account_info, &td.holder_house_address)?;
+
+    let realm_config = load_realm_config(program_id, realm_config_account_info)?;
+    let slot = Clock::get()?.slot;
+    if td.lifecycle_state == TitleData::ACTIVE_STATE {
+        // An active title is delinquent once tax has brought its stake below its
+        // required stake. The remaining stake goes back to the holder.
+        let stake_account_info = next_account_info(account_info_iter)?;
+        let holder_wallet_info = next_account_info(account_info_iter)?;
+        if !stake_account_info.is_writable || !holder_wallet_info.is_writable {
+            msg!(
+                "No write permission for accounts: {} {}",
+                stake_account_info.is_writable,
+                holder_wallet_info.is_writable
+            );
+            return Err(TitleError::AccountNotWritable.into());
+        }
+        if td.stake_address != *stake_account_info.key {
+            msg!(
+                "Expected stake account {}, got {}",
+                td.stake_address,
+                stake_account_info.key
+            );
+            return Err(TitleError::IncorrectStakeAccount.into());
+        }
+        check_account_owner(program_id, stake_account_info)?;
+        check_account_type(&stake_account_info.data.borrow(), AccountType::StakeVault)?;
+        check_house_authority(program_id, holder_wallet_info, holder_house_account_info)?;
+        // Tax accrued so far must be collected first, so that the stake is current.
+        check_tax_settled(&td)?;
+        let stake_lamports = stake_account_info
+            .lamports()
+            .saturating_sub(Rent::get()?.minimum_balance(STAKE_VAULT_SIZE));
+        if !td.is_stake_delinquent(stake_lamports) {
+            msg!(
+                "Title has {} lamports of stake, {} required",
+                stake_lamports,
+                td.required_stake_lamports
+            );
+            return Err(TitleError::NotDelinquent.into());
+        }
+        msg!(
+            "Escheating title: stake of {} lamports is delinquent, {} required",
+            stake_lamports,
+            td.required_stake_lamports
+        );
+        close_account(stake_account_info, holder_wallet_info)?;
+    } else {
+        let inactive_slots = slot.saturating_sub(td.last_tax_paid_slot);
+        if inactive_slots < realm_config.settings.escheat_grace_period_slots {
+            msg!(
+                "Title has been inactive for {} slots, grace period is {}",
+                inactive_slots,
+                realm_config.settings.escheat_grace_period_slots
+            );
+            return Err(TitleError::NotDelinquent.into());
+        }
+        msg!(
+            "Escheating title: inactive for {} slots, since slot {}",
+            inactive_slots,
+            td.last_tax_paid_slot
+        );
+    }
+
+    // The title reverts to an inactive title held by the liege's holder, as on
+    // foreclosure.
+    let mut title_data = title_account_info.data.borrow_mut();
+    let mut title = TitleDataMut::new(&mut title_data)?;
+    title.set_lifecycle_state(TitleData::INACTIVE_STATE);
+    title.set_stake_address(&Pubkey::new(&[0; 32]));
+    title.set_sale_price_lamports(td.required_stake_lamports);
+    title.set_holder_house_address(&liege_td.holder_house_address);
+    title.set_last_tax_paid_slot(slot);
+    if !is_held_by_treasury(program_id, &td) {
+        remove_held_titles(
+            program_id,
+            holder_house_account_info,
+            holder_holdings_account_info,
+            &[*title_account_info.key],
+        )?;
+    }
+    add_held_titles(
+        program_id,
+        liege_holder_house_account_info,
+        liege_holder_holdings_account_info,
+        &[*title_account_info.key],
+        liege_holder_wallet_info,
+        system_account_info,
+    )
+}
+
+/// Processes CloseHouse instruction
+pub fn process_close_house(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+
+    let owner_wallet_info = next_account_info(account_info_iter)?;
+    let house_account_info = next_account_info(account_info_iter)?;
+    let holdings_account_info = next_account_info(account_info_iter)?;
+
+    if !owner_wallet_info.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+    if !owner_wallet_info.is_writable || !house_account_info.is_writable {
+        msg!(
+            "No write permission for accounts: {} {}",
+            owner_wallet_info.is_writable,
+            house_account_info.is_writable
+        );
+        return Err(TitleError::AccountNotWritable.into());
+    }
+
+    let housedata = check_house_authority(program_id, owner_wallet_info, house_account_info)?;
+    if !housedata.is_initialized() {
+        msg!("Error: house is uninitialized");
+        return Err(TitleError::UninitializedAccount.into());
+    }
+    if housedata.held_title_count == HouseData::UNKNOWN_TITLE_COUNT {
+        msg!("House predates title counts, the titles it holds are unknown");
+        return Err(TitleError::HouseHoldsTitles.into());
+    }
+    if housedata.held_title_count > 0 {
+        msg!("House still holds {} titles", housedata.held_title_count);
+        return Err(TitleError::HouseHoldsTitles.into());
+    }
+
+    // The holdings of a house that held titles are left empty, close them too.
+    check_holdings_account(program_id, house_account_info.key, holdings_account_info)?;
+    if !holdings_account_info.data_is_empty() {
+        close_account(holdings_account_info, owner_wallet_info)?;
+    }
+    close_account(house_account_info, owner_wallet_info)
+}
+
+/// Processes TransferHouseAuthority instruction
+pub fn process_transfer_house_authority(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    new_authority: Pubkey,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+
+    let authority_wallet_info = next_account_info(account_info_iter)?;
+    let house_account_info = next_account_info(account_info_iter)?;
+
+    if !authority_wallet_info.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+    if !house_account_info.is_writable {
+        msg!("No write permission for house account");
+        return Err(TitleError::AccountNotWritable.into());
+    }
+    // An all-zero authority would hand the house back to the wallet it is derived from.
+    if new_authority == Pubkey::new(&[0; 32]) {
+        msg!("New authority must not be all zeroes");
+        return Err(ProgramError::InvalidArgument);
+    }
+
+    let mut housedata =
+        check_house_authority(program_id, authority_wallet_info, house_account_info)?;
+    if !housedata.is_initialized() {
+        msg!("Error: house is uninitialized");
+        return Err(TitleError::UninitializedAccount.into());
+    }
+
+    msg!("Transferring house authority to {}", new_authority);
+    housedata.authority = new_authority;
+    housedata
+        .serialize(&mut *house_account_info.data.borrow_mut())
+        .map_err(|e| e.into())
+}
+
+/// Processes CreateProposal instruction
+pub fn process_create_proposal(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    proposal_index: u32,
+    proposed_accounts: Vec<ProposedAccount>,
+    data: Vec<u8>,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+
+    let proposer_wallet_info = next_account_info(account_info_iter)?;
+    let house_account_info = next_account_info(account_info_iter)?;
+    let proposal_account_info = next_account_info(account_info_iter)?;
+    let escrow_account_info = next_account_info(account_info_iter)?;
+    let mint_account_info = next_account_info(account_info_iter)?;
+    let system_account_info = next_account_info(account_info_iter)?;
+    let token_program_info = next_account_info(account_info_iter)?;
+    let rent_sysvar_info = next_account_info(account_info_iter)?;
+
+    if !proposer_wallet_info.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+    if !proposer_wallet_info.is_writable
+        || !house_account_info.is_writable
+        || !proposal_account_info.is_writable
+        || !escrow_account_info.is_writable
+    {
+        msg!(
+            "No write permission for accounts: {} {} {} {}",
+            proposer_wallet_info.is_writable,
+            house_account_info.is_writable,
+            proposal_account_info.is_writable,
+            escrow_account_info.is_writable
+        );
+        return Err(TitleError::AccountNotWritable.into());
+    }
+    check_token_program(token_program_info.key)?;
+    // Only instructions of the program may be proposed.
+    if TitleInstruction::try_from_slice(&data).is_err() {
+        msg!("Proposed data is not an instruction of the program");
+        return Err(ProgramError::InvalidInstructionData);
+    }
+
+    let mut housedata = load_account::<HouseData>(program_id, house_account_info)?;
+    if !housedata.is_initialized() {
+        msg!("Error: house is uninitialized");
+        return Err(TitleError::UninitializedAccount.into());
+    }
+    if !housedata.is_token_governed() {
+        msg!(
+            "House {} is governed by its authority alone",
+            house_account_info.key
+        );
+        return Err(TitleError::NotTokenGoverned.into());
+    }
+    if housedata.proposal_count != proposal_index {
+        msg!(
+            "Cannot add proposal #{}, house has {} proposals",
+            proposal_index,
+            housedata.proposal_count
+        );
+        return Err(TitleError::IncorrectProposalIndex.into());
+    }
+    let (mint_address, _) =
+        get_governance_mint_address_and_bump_seed_internal(house_account_info.key, program_id);
+    if mint_address != *mint_account_info.key {
+        msg!("Error: Governance mint address does not match seed derivation");
+        return Err(ProgramError::InvalidSeeds);
+    }
+    let (proposal_address, proposal_bump_seed) = get_proposal_address_and_bump_seed_internal(
+        house_account_info.key,
+        proposal_index,
+        program_id,
+    );
+    if proposal_address != *proposal_account_info.key {
+        msg!("Error: Proposal address does not match seed derivation");
+        return Err(ProgramError::InvalidSeeds);
+    }
+    let escrow_bump_seed =
+        check_proposal_escrow(program_id, &proposal_address, escrow_account_info)?;
+
+    let proposal = ProposalData {
+        account_type: AccountType::Proposal,
+        version: ProposalData::CURRENT_VERSION,
+        house_address: *house_account_info.key,
+        proposal_index,
+        approving_tokens: 0,
+        executed: false,
+        voting_deadline_slot: Clock::get()?
+            .slot
+            .saturating_add(ProposalData::VOTING_PERIOD_SLOTS),
+        accounts: proposed_accounts,
+        data,
+    };
+    msg!("Creating proposal {}: {}", proposal_index, proposal_address);
+    create_pda_account(
+        program_id,
+        proposer_wallet_info,
+        proposal_account_info,
+        ProposalData::size(proposal.accounts.len(), proposal.data.len()),
+        program_id,
+        &[
+            &house_account_info.key.to_bytes(),
+            b"proposal",
+            &proposal_index.to_le_bytes(),
+            &[proposal_bump_seed],
+        ],
+        system_account_info,
+    )?;
+    proposal.serialize(&mut *proposal_account_info.data.borrow_mut())?;
+
+    // The escrow is owned by the proposal, which signs the withdrawals of votes.
+    create_pda_account(
+        program_id,
+        proposer_wallet_info,
+        escrow_account_info,
+        TokenAccount::LEN,
+        &spl_token::id(),
+        &[&proposal_address.to_bytes(), b"escrow", &[escrow_bump_seed]],
+        system_account_info,
+    )?;
+    invoke(
+        &spl_token::instruction::initialize_account(
+            &spl_token::id(),
+            escrow_account_info.key,
+            &mint_address,
+            &proposal_address,
+        )?,
+        &[
+            escrow_account_info.clone(),
+            mint_account_info.clone(),
+            proposal_account_info.clone(),
+            rent_sysvar_info.clone(),
+            token_program_info.clone(),
+        ],
+    )?;
+
+    housedata.proposal_count = housedata
+        .proposal_count
+        .checked_add(1)
+        .ok_or(TitleError::Overflow)?;
+    housedata
+        .serialize(&mut *house_account_info.data.borrow_mut())
+        .map_err(|e| e.into())
+}
+
+/// Processes CastVote instruction
+pub fn process_cast_vote(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    tokens: u64,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+
+    let voter_wallet_info = next_account_info(account_info_iter)?;
+    let proposal_account_info = next_account_info(account_info_iter)?;
+    let vote_account_info = next_account_info(account_info_iter)?;
+    let voter_token_account_info = next_account_info(account_info_iter)?;
+    let escrow_account_info = next_account_info(account_info_iter)?;
+    let token_program_info = next_account_info(account_info_iter)?;
+    let system_account_info = next_account_info(account_info_iter)?;
+
+    if !voter_wallet_info.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+    if !voter_wallet_info.is_writable
+        || !proposal_account_info.is_writable
+        || !vote_account_info.is_writable
+    {
+        msg!(
+            "No write permission for accounts: {} {} {}",
+            voter_wallet_info.is_writable,
+            proposal_account_info.is_writable,
+            vote_account_info.is_writable
+        );
+        return Err(TitleError::AccountNotWritable.into());
+    }
+    check_token_program(token_program_info.key)?;
+    if tokens == 0 {
+        msg!("Vote must deposit at least one token");
+        return Err(ProgramError::InvalidArgument);
+    }
+
+    let mut proposal = load_proposal(program_id, proposal_account_info)?;
+    if proposal.executed {
+        msg!("Proposal was already executed");
+        return Err(TitleError::ProposalExecuted.into());
+    }
+    if proposal.has_ended(Clock::get()?.slot) {
+        msg!("Proposal voting ended at slot {}", proposal.voting_deadline_slot);
+        return Err(TitleError::VotingEnded.into());
+    }
+    check_proposal_escrow(program_id, proposal_account_info.key, escrow_account_info)?;
+    let (vote_address, vote_bump_seed) = get_vote_address_and_bump_seed_internal(
+        proposal_account_info.key,
+        voter_wallet_info.key,
+        program_id,
+    );
+    if vote_address != *vote_account_info.key {
+        msg!("Error: Vote address does not match seed derivation");
+        return Err(ProgramError::InvalidSeeds);
+    }
+    let mut vote = if vote_account_info.data_is_empty() {
+        msg!("Creating vote: {}", vote_address);
+        create_pda_account(
+            program_id,
+            voter_wallet_info,
+            vote_account_info,
+            VoteData::SIZE,
+            program_id,
+            &[
+                &proposal_account_info.key.to_bytes(),
+                b"vote",
+                &voter_wallet_info.key.to_bytes(),
+                &[vote_bump_seed],
+            ],
+            system_account_info,
+        )?;
+        VoteData {
+            account_type: AccountType::Vote,
+            version: VoteData::CURRENT_VERSION,
+            proposal_address: *proposal_account_info.key,
+            voter_address: *voter_wallet_info.key,
+            deposited_tokens: 0,
+        }
+    } else {
+        load_account::<VoteData>(program_id, vote_account_info)?
+    };
+
+    // The token program checks that the tokens are the house's governance tokens, held
+    // in the escrow.
+    invoke(
+        &spl_token::instruction::transfer(
+            &spl_token::id(),
+            voter_token_account_info.key,
+            escrow_account_info.key,
+            voter_wallet_info.key,
+            &[],
+            tokens,
+        )?,
+        &[
+            voter_token_account_info.clone(),
+            escrow_account_info.clone(),
+            voter_wallet_info.clone(),
+            token_program_info.clone(),
+        ],
+    )?;
+
+    vote.deposited_tokens = vote
+        .deposited_tokens
+        .checked_add(tokens)
+        .ok_or(TitleError::Overflow)?;
+    vote.serialize(&mut *vote_account_info.data.borrow_mut())?;
+    proposal.approving_tokens = proposal
+        .approving_tokens
+        .checked_add(tokens)
+        .ok_or(TitleError::Overflow)?;
+    proposal
+        .serialize(&mut *proposal_account_info.data.borrow_mut())
+        .map_err(|e| e.into())
+}
+
+/// Processes WithdrawVote instruction
+pub fn process_withdraw_vote(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+
+    let voter_wallet_info = next_account_info(account_info_iter)?;
+    let proposal_account_info = next_account_info(account_info_iter)?;
+    let vote_account_info = next_account_info(account_info_iter)?;
+    let token_account_info = next_account_info(account_info_iter)?;
+    let escrow_account_info = next_account_info(account_info_iter)?;
+    let token_program_info = next_account_info(account_info_iter)?;
+
+    if !voter_wallet_info.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+    if !voter_wallet_info.is_writable
+        || !proposal_account_info.is_writable
+        || !vote_account_info.is_writable
+    {
+        msg!(
+            "No write permission for accounts: {} {} {}",
+            voter_wallet_info.is_writable,
+            proposal_account_info.is_writable,
+            vote_account_info.is_writable
+        );
+        return Err(TitleError::AccountNotWritable.into());
+    }
+    check_token_program(token_program_info.key)?;
+
+    let mut proposal = load_proposal(program_id, proposal_account_info)?;
+    check_proposal_escrow(program_id, proposal_account_info.key, escrow_account_info)?;
+    let (vote_address, _) = get_vote_address_and_bump_seed_internal(
+        proposal_account_info.key,
+        voter_wallet_info.key,
+        program_id,
+    );
+    if vote_address != *vote_account_info.key {
+        msg!("Error: Vote address does not match seed derivation");
+        return Err(ProgramError::InvalidSeeds);
+    }
+    let vote = load_account::<VoteData>(program_id, vote_account_info)?;
+    let (_, proposal_bump_seed) = get_proposal_address_and_bump_seed_internal(
+        &proposal.house_address,
+        proposal.proposal_index,
+        program_id,
+    );
+
+    invoke_signed(
+        &spl_token::instruction::transfer(
+            &spl_token::id(),
+            escrow_account_info.key,
+            token_account_info.key,
+            proposal_account_info.key,
+            &[],
+            vote.deposited_tokens,
+        )?,
+        &[
+            escrow_account_info.clone(),
+            token_account_info.clone(),
+            proposal_account_info.clone(),
+            token_program_info.clone(),
+        ],
+        &[&[
+            &proposal.house_address.to_bytes(),
+            b"proposal",
+            &proposal.proposal_index.to_le_bytes(),
+            &[proposal_bump_seed],
+        ]],
+    )?;
+
+    // Executed and ended proposals keep the approval they were executed or ended with.
+    if !proposal.executed && !proposal.has_ended(Clock::get()?.slot) {
+        proposal.approving_tokens = proposal
+            .approving_tokens
+            .checked_sub(vote.deposited_tokens)
+            .ok_or(TitleError::Overflow)?;
+        proposal.serialize(&mut *proposal_account_info.data.borrow_mut())?;
+    }
+    close_account(vote_account_info, voter_wallet_info)
+}
+
+/// Processes ExecuteProposal instruction
+pub fn process_execute_proposal(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+
+    let proposal_account_info = next_account_info(account_info_iter)?;
+    let house_account_info = next_account_info(account_info_iter)?;
+    let program_info = next_account_info(account_info_iter)?;
+    let proposed_account_infos = account_info_iter.as_slice();
+
+    if !proposal_account_info.is_writable {
+        msg!("No write permission for proposal account");
+        return Err(TitleError::AccountNotWritable.into());
+    }
+    if program_info.key != program_id {
+        msg!(
+            "Expected program {}, received {}",
+            program_id,
+            program_info.key
+        );
+        return Err(ProgramError::IncorrectProgramId);
+    }
+
+    let mut proposal = load_proposal(program_id, proposal_account_info)?;
+    if proposal.house_address != *house_account_info.key {
+        msg!(
+            "Proposal is to house {}, got house {}",
+            proposal.house_address,
+            house_account_info.key
+        );
+        return Err(TitleError::IncorrectAuthority.into());
+    }
+    let housedata = load_account::<HouseData>(program_id, house_account_info)?;
+    if !housedata.is_initialized() {
+        msg!("Error: house is uninitialized");
+        return Err(TitleError::UninitializedAccount.into());
+    }
+    if proposal.executed {
+        msg!("Proposal was already executed");
+        return Err(TitleError::ProposalExecuted.into());
+    }
+    if proposal.has_ended(Clock::get()?.slot) {
+        msg!("Proposal voting ended at slot {}", proposal.voting_deadline_slot);
+        return Err(TitleError::VotingEnded.into());
+    }
+    // The house may have handed its government to a wallet since the proposal was made.
+    let (governance_address, governance_bump_seed) =
+        get_governance_address_and_bump_seed_internal(house_account_info.key, program_id);
+    if !housedata.is_token_governed() || housedata.authority != governance_address {
+        msg!(
+            "House {} is governed by {}, not its token holders",
+            house_account_info.key,
+            housedata.authority
+        );
+        return Err(TitleError::NotTokenGoverned.into());
+    }
+    if !proposal.is_approved(housedata.governance_token_supply) {
+        msg!(
+            "Proposal approved by {} of {} tokens",
+            proposal.approving_tokens,
+            housedata.governance_token_supply
+        );
+        return Err(TitleError::ProposalNotApproved.into());
+    }
+    if proposed_account_infos.len() != proposal.accounts.len() {
+        msg!(
+            "Expected {} proposed accounts, got {}",
+            proposal.accounts.len(),
+            proposed_account_infos.len()
+        );
+        return Err(ProgramError::NotEnoughAccountKeys);
+    }
+    for (account, account_info) in proposal.accounts.iter().zip(proposed_account_infos) {
+        if account.pubkey != *account_info.key {
+            msg!(
+                "Expected proposed account {}, got {}",
+                account.pubkey,
+                account_info.key
+            );
+            return Err(ProgramError::InvalidArgument);
+        }
+    }
+
+    // Marked executed first, so that the proposed instruction can't execute it again.
+    proposal.executed = true;
+    proposal.serialize(&mut *proposal_account_info.data.borrow_mut())?;
+
+    let instruction = Instruction {
+        program_id: *program_id,
+        accounts: proposal
+            .accounts
+            .iter()
+            .map(|account| AccountMeta {
+                pubkey: account.pubkey,
+                is_signer: account.is_signer,
+                is_writable: account.is_writable,
+            })
+            .collect(),
+        data: proposal.data,
+    };
+    let mut account_infos = proposed_account_infos.to_vec();
+    account_infos.push(program_info.clone());
+    msg!("Executing proposal {}", proposal.proposal_index);
+    invoke_signed(
+        &instruction,
+        &account_infos,
+        &[&[
+            &house_account_info.key.to_bytes(),
+            b"governance",
+            &[governance_bump_seed],
+        ]],
+    )
+}
+
+/// Processes SetHeir instruction
+pub fn process_set_heir(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    heir_house_address: Pubkey,
+    succession_period_slots: u64,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+
+    let authority_wallet_info = next_account_info(account_info_iter)?;
+    let house_account_info = next_account_info(account_info_iter)?;
+
+    if !authority_wallet_info.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+    if !house_account_info.is_writable {
+        msg!("No write permission for house account");
+        return Err(TitleError::AccountNotWritable.into());
+    }
+    // A zero period would let anybody hand the titles to the heir at once.
+    if succession_period_slots == 0 {
+        msg!("Succession period must not be zero");
+        return Err(ProgramError::InvalidArgument);
+    }
+
+    let mut housedata =
+        check_house_authority(program_id, authority_wallet_info, house_account_info)?;
+    if !housedata.is_initialized() {
+        msg!("Error: house is uninitialized");
+        return Err(TitleError::UninitializedAccount.into());
+    }
+    if heir_house_address != Pubkey::new(&[0; 32]) {
+        let heir_house_account_info = next_account_info(account_info_iter)?;
+        if heir_house_address != *heir_house_account_info.key {
+            msg!(
+                "Expected heir house {}, got {}",
+                heir_house_address,
+                heir_house_account_info.key
+            );
+            return Err(ProgramError::InvalidArgument);
+        }
+        if heir_house_address == *house_account_info.key {
+            msg!("Error: house can't be its own heir");
+            return Err(ProgramError::InvalidArgument);
+        }
+        let heir_housedata = load_account::<HouseData>(program_id, heir_house_account_info)?;
+        if !heir_housedata.is_initialized() {
+            msg!("Error: heir house is uninitialized");
+            return Err(TitleError::UninitializedAccount.into());
+        }
+    }
+
+    msg!(
+        "Naming heir {}, succession after {} slots",
+        heir_house_address,
+        succession_period_slots
+    );
+    housedata.heir_house_address = heir_house_address;
+    housedata.succession_period_slots = succession_period_slots;
+    housedata.last_proof_of_life_slot = Clock::get()?.slot;
+    housedata
+        .serialize(&mut *house_account_info.data.borrow_mut())
+        .map_err(|e| e.into())
+}
+
+/// Processes ProveLife instruction
+pub fn process_prove_life(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+
+    let authority_wallet_info = next_account_info(account_info_iter)?;
+    let house_account_info = next_account_info(account_info_iter)?;
+
+    if !authority_wallet_info.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+    if !house_account_info.is_writable {
+        msg!("No write permission for house account");
+        return Err(TitleError::AccountNotWritable.into());
+    }
+
+    let mut housedata =
+        check_house_authority(program_id, authority_wallet_info, house_account_info)?;
+    if !housedata.is_initialized() {
+        msg!("Error: house is uninitialized");
+        return Err(TitleError::UninitializedAccount.into());
+    }
+
+    housedata.last_proof_of_life_slot = Clock::get()?.slot;
+    housedata
+        .serialize(&mut *house_account_info.data.borrow_mut())
+        .map_err(|e| e.into())
+}
+
+/// Processes TriggerSuccession instruction
+pub fn process_trigger_succession(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+
+    let funder_wallet_info = next_account_info(account_info_iter)?;
+    let house_account_info = next_account_info(account_info_iter)?;
+    let holdings_account_info = next_account_info(account_info_iter)?;
+    let heir_house_account_info = next_account_info(account_info_iter)?;
+    let heir_holdings_account_info = next_account_info(account_info_iter)?;
+    let system_account_info = next_account_info(account_info_iter)?;
+    let title_account_infos = account_info_iter.as_slice();
+
+    if !funder_wallet_info.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+    if !funder_wallet_info.is_writable
+        || !house_account_info.is_writable
+        || !heir_house_account_info.is_writable
+    {
+        msg!(
+            "No write permission for accounts: {} {} {}",
+            funder_wallet_info.is_writable,
+            house_account_info.is_writable,
+            heir_house_account_info.is_writable
+        );
+        return Err(TitleError::AccountNotWritable.into());
+    }
+    if title_account_infos.is_empty() {
+        msg!("No titles to move to the heir");
+        return Err(ProgramError::NotEnoughAccountKeys);
+    }
+
+    let housedata = load_account::<HouseData>(program_id, house_account_info)?;
+    if !housedata.is_initialized() {
+        msg!("Error: house is uninitialized");
+        return Err(TitleError::UninitializedAccount.into());
+    }
+    if housedata.heir_house_address == Pubkey::new(&[0; 32]) {
+        msg!("Error: house has not named an heir");
+        return Err(TitleError::NoHeir.into());
+    }
+    check_authority(heir_house_account_info, &housedata.heir_house_address)?;
+    let heir_housedata = load_account::<HouseData>(program_id, heir_house_account_info)?;
+    if !heir_housedata.is_initialized() {
+        msg!("Error: heir house is uninitialized");
+        return Err(TitleError::UninitializedAccount.into());
+    }
+    let slot = Clock::get()?.slot;
+    if !housedata.is_succession_due(slot) {
+        msg!(
+            "House last proved life at slot {}, succession period is {} slots",
+            housedata.last_proof_of_life_slot,
+            housedata.succession_period_slots
+        );
+        return Err(TitleError::SuccessionNotDue.into());
+    }
+    check_holdings_account(program_id, house_account_info.key, holdings_account_info)?;
+    if holdings_account_info.data_is_empty() {
+        msg!("House {} has no holdings", house_account_info.key);
+        return Err(TitleError::TitleNotInHoldings.into());
+    }
+    let holdings = load_account::<HoldingsData>(program_id, holdings_account_info)?;
+
+    // Titles pass as they are, with their stake and sale price unless their succession
+    // law lists them for sale. Inactive titles restart their escheat grace period, so
+    // that the heir has time to activate them.
+    let mut title_addresses = Vec::with_capacity(title_account_infos.len());
+    for title_account_info in title_account_infos {
+        if !title_account_info.is_writable {
+            msg!("No write permission for title {}", title_account_info.key);
+            return Err(TitleError::AccountNotWritable.into());
+        }
+        if title_addresses.contains(title_account_info.key) {
+            msg!("Title {} is listed more than once", title_account_info.key);
+            return Err(TitleError::DuplicateTitle.into());
+        }
+        let td = load_account::<TitleData>(program_id, title_account_info)?;
+        if !td.is_initialized() {
+            msg!("Error: title is uninitialized");
+            return Err(TitleError::UninitializedAccount.into());
+        }
+        check_authority(house_account_info, &td.holder_house_address)?;
+        // Keep the holdings in step with the titles that move.
+        if !holdings.title_addresses.contains(title_account_info.key) {
+            msg!(
+                "Title {} is missing from the house's holdings",
+                title_account_info.key
+            );
+            return Err(TitleError::TitleNotInHoldings.into());
+        }
+        if td.succession_law == SuccessionLaw::Elective && td.live_vassal_count > 0 {
+            msg!(
+                "Title {} is elected by the holders of its vassals",
+                title_account_info.key
+            );
+            return Err(TitleError::TitleIsElective.into());
+        }
+        let mut title_data = title_account_info.data.borrow_mut();
+        let mut title = TitleDataMut::new(&mut title_data)?;
+        title.set_holder_house_address(heir_house_account_info.key);
+        if td.succession_law == SuccessionLaw::Sale {
+            title.set_sale_price_lamports(td.required_stake_lamports);
+        }
+        if td.lifecycle_state == TitleData::INACTIVE_STATE {
+            title.set_last_tax_paid_slot(slot);
+        }
+        title_addresses.push(*title_account_info.key);
+    }
+
+    msg!(
+        "Moving {} titles to heir {}",
+        title_addresses.len(),
+        heir_house_account_info.key
+    );
+    remove_held_titles(
+        program_id,
+        house_account_info,
+        holdings_account_info,
+        &title_addresses,
+    )?;
+    add_held_titles(
+        program_id,
+        heir_house_account_info,
+        heir_holdings_account_info,
+        &title_addresses,
+        funder_wallet_info,
+        system_account_info,
+    )
+}
+
+/// Processes SetSuccessionLaw instruction
+pub fn process_set_succession_law(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    succession_law: SuccessionLaw,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+
+    let owner_wallet_info = next_account_info(account_info_iter)?;
+    let house_account_info = next_account_info(account_info_iter)?;
+    let title_account_info = next_account_info(account_info_iter)?;
+
+    if !owner_wallet_info.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+    if !title_account_info.is_writable {
+        msg!("No write permission for title account");
+        return Err(TitleError::AccountNotWritable.into());
+    }
+
+    // Only the holder of the title may change its succession law.
+    check_house_authority(program_id, owner_wallet_info, house_account_info)?;
+    let td = load_account::<TitleData>(program_id, title_account_info)?;
+    if !td.is_initialized() {
+        msg!("Error: title is uninitialized");
+        return Err(TitleError::UninitializedAccount.into());
+    }
+    check_authority(house_account_info, &td.holder_house_address)?;
+
+    msg!("Setting succession law: {}", succession_law);
+    TitleDataMut::new(&mut title_account_info.data.borrow_mut())?
+        .set_succession_law(succession_law);
+    Ok(())
+}
+
+/// Processes OpenElection instruction
+pub fn process_open_election(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+
+    let funder_wallet_info = next_account_info(account_info_iter)?;
+    let title_account_info = next_account_info(account_info_iter)?;
+    let holder_house_account_info = next_account_info(account_info_iter)?;
+    let election_account_info = next_account_info(account_info_iter)?;
+    let system_account_info = next_account_info(account_info_iter)?;
+    let realm_config_account_info = next_account_info(account_info_iter)?;
+
+    if !funder_wallet_info.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+    if !funder_wallet_info.is_writable || !election_account_info.is_writable {
+        msg!(
+            "No write permission for accounts: {} {}",
+            funder_wallet_info.is_writable,
+            election_account_info.is_writable
+        );
+        return Err(TitleError::AccountNotWritable.into());
+    }
+
+    let td = load_account::<TitleData>(program_id, title_account_info)?;
+    if !td.is_initialized() {
+        msg!("Error: title is uninitialized");
+        return Err(TitleError::UninitializedAccount.into());
+    }
+    if td.succession_law != SuccessionLaw::Elective {
+        msg!("Title passes by the {} succession law", td.succession_law);
+        return Err(TitleError::NotElective.into());
+    }
+    // Without vassals to vote, the title passes to the heir instead.
+    if td.live_vassal_count == 0 {
+        msg!("Title has no vassals to elect its holder");
+        return Err(TitleError::NotElective.into());
+    }
+    check_authority(holder_house_account_info, &td.holder_house_address)?;
+    let housedata = load_account::<HouseData>(program_id, holder_house_account_info)?;
+    let realm_config = load_realm_config(program_id, realm_config_account_info)?;
+    let slot = Clock::get()?.slot;
+    if !housedata.has_vacated(slot, &realm_config.settings) {
+        msg!(
+            "House last proved life at slot {}, vacancy period is {} slots",
+            housedata.last_proof_of_life_slot,
+            realm_config.settings.vacancy_period_slots
+        );
+        return Err(TitleError::SuccessionNotDue.into());
+    }
+
+    let (election_address, bump_seed) =
+        get_election_address_and_bump_seed_internal(title_account_info.key, program_id);
+    if election_address != *election_account_info.key {
+        msg!("Error: Election address does not match seed derivation");
+        return Err(ProgramError::InvalidSeeds);
+    }
+    if !election_account_info.data_is_empty() {
+        msg!("Error: an election is already open for this title");
+        return Err(ProgramError::AccountAlreadyInitialized);
+    }
+
+    let election = ElectionData {
+        account_type: AccountType::Election,
+        version: ElectionData::CURRENT_VERSION,
+        title_address: *title_account_info.key,
+        holder_house_address: td.holder_house_address,
+        opened_slot: slot,
+        ballots: vec![],
+    };
+    msg!("Opening election: {}", election_address);
+    create_pda_account(
+        program_id,
+        funder_wallet_info,
+        election_account_info,
+        ElectionData::size(0),
+        program_id,
+        &[
+            &title_account_info.key.to_bytes(),
+            b"election",
+            &[bump_seed],
+        ],
+        system_account_info,
+    )?;
+    election
+        .serialize(&mut *election_account_info.data.borrow_mut())
+        .map_err(|e| e.into())
+}
+
+/// Processes CastBallot instruction
+pub fn process_cast_ballot(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    candidate_house_address: Pubkey,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+
+    let voter_wallet_info = next_account_info(account_info_iter)?;
+    let voter_house_account_info = next_account_info(account_info_iter)?;
+    let vassal_account_info = next_account_info(account_info_iter)?;
+    let election_account_info = next_account_info(account_info_iter)?;
+    let candidate_house_account_info = next_account_info(account_info_iter)?;
+    let realm_config_account_info = next_account_info(account_info_iter)?;
+    let system_account_info = next_account_info(account_info_iter)?;
+
+    if !voter_wallet_info.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+    if !voter_wallet_info.is_writable || !election_account_info.is_writable {
+        msg!(
+            "No write permission for accounts: {} {}",
+            voter_wallet_info.is_writable,
+            election_account_info.is_writable
+        );
+        return Err(TitleError::AccountNotWritable.into());
+    }
+
+    let mut election = load_account::<ElectionData>(program_id, election_account_info)?;
+    if !election.is_initialized() {
+        msg!("Error: election is uninitialized");
+        return Err(TitleError::UninitializedAccount.into());
+    }
+    let realm_config = load_realm_config(program_id, realm_config_account_info)?;
+    let election_slots = Clock::get()?.slot.saturating_sub(election.opened_slot);
+    if election_slots >= realm_config.settings.election_period_slots {
+        msg!(
+            "Election opened {} slots ago, election period is {}",
+            election_slots,
+            realm_config.settings.election_period_slots
+        );
+        return Err(TitleError::ElectionEnded.into());
+    }
+
+    // Only the holder of a vassal of the title may vote, once per vassal.
+    check_house_authority(program_id, voter_wallet_info, voter_house_account_info)?;
+    let vassal_td = load_account::<TitleData>(program_id, vassal_account_info)?;
+    if !vassal_td.is_initialized() {
+        msg!("Error: vassal title is uninitialized");
+        return Err(TitleError::UninitializedAccount.into());
+    }
+    if vassal_td.liege_address != election.title_address {
+        msg!(
+            "Expected liege {}, got {}",
+            election.title_address,
+            vassal_td.liege_address
+        );
+        return Err(TitleError::IncorrectLiege.into());
+    }
+    check_authority(voter_house_account_info, &vassal_td.holder_house_address)?;
+
+    if candidate_house_address != *candidate_house_account_info.key {
+        msg!(
+            "Expected candidate house {}, got {}",
+            candidate_house_address,
+            candidate_house_account_info.key
+        );
+        return Err(ProgramError::InvalidArgument);
+    }
+    if candidate_house_address == election.holder_house_address {
+        msg!("Error: the house vacating the title can't be elected");
+        return Err(ProgramError::InvalidArgument);
+    }
+    let candidate_housedata = load_account::<HouseData>(program_id, candidate_house_account_info)?;
+    if !candidate_housedata.is_initialized() {
+        msg!("Error: candidate house is uninitialized");
+        return Err(TitleError::UninitializedAccount.into());
+    }
+
+    msg!(
+        "Vassal {} votes for {}",
+        vassal_account_info.key,
+        candidate_house_address
+    );
+    match election
+        .ballots
+        .iter_mut()
+        .find(|ballot| ballot.vassal_address == *vassal_account_info.key)
+    {
+        Some(ballot) => ballot.candidate_house_address = candidate_house_address,
+        None => {
+            let new_len = ElectionData::size(election.ballots.len() + 1);
+            fund_rent(
+                program_id,
+                voter_wallet_info,
+                election_account_info,
+                new_len,
+                system_account_info,
+            )?;
+            election_account_info.realloc(new_len, false)?;
+            election.ballots.push(Ballot {
+                vassal_address: *vassal_account_info.key,
+                candidate_house_address,
+            });
+        }
+    }
+    election
+        .serialize(&mut *election_account_info.data.borrow_mut())
+        .map_err(|e| e.into())
+}
+
+/// Processes ConcludeElection instruction
+pub fn process_conclude_election(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+
+    let caller_wallet_info = next_account_info(account_info_iter)?;
+    let title_account_info = next_account_info(account_info_iter)?;
+    let election_account_info = next_account_info(account_info_iter)?;
+    let holder_house_account_info = next_account_info(account_info_iter)?;
+    let holder_holdings_account_info = next_account_info(account_info_iter)?;
+    let winner_house_account_info = next_account_info(account_info_iter)?;
+    let winner_holdings_account_info = next_account_info(account_info_iter)?;
+    let realm_config_account_info = next_account_info(account_info_iter)?;
+    let system_account_info = next_account_info(account_info_iter)?;
+
+    if !caller_wallet_info.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+    if !caller_wallet_info.is_writable
+        || !title_account_info.is_writable
+        || !election_account_info.is_writable
+        || !holder_house_account_info.is_writable
+        || !winner_house_account_info.is_writable
+    {
+        msg!(
+            "No write permission for accounts: {} {} {} {} {}",
+            caller_wallet_info.is_writable,
+            title_account_info.is_writable,
+            election_account_info.is_writable,
+            holder_house_account_info.is_writable,
+            winner_house_account_info.is_writable
+        );
+        return Err(TitleError::AccountNotWritable.into());
+    }
+
+    let (election_address, _) =
+        get_election_address_and_bump_seed_internal(title_account_info.key, program_id);
+    if election_address != *election_account_info.key {
+        msg!("Error: Election address does not match seed derivation");
+        return Err(ProgramError::InvalidSeeds);
+    }
+    let election = load_account::<ElectionData>(program_id, election_account_info)?;
+    if !election.is_initialized() {
+        msg!("Error: election is uninitialized");
+        return Err(TitleError::UninitializedAccount.into());
+    }
+    let td = load_account::<TitleData>(program_id, title_account_info)?;
+    if !td.is_initialized() {
+        msg!("Error: title is uninitialized");
+        return Err(TitleError::UninitializedAccount.into());
+    }
+    check_authority(holder_house_account_info, &election.holder_house_address)?;
+    let slot = Clock::get()?.slot;
+
+    // The title is no longer vacant once it changed hands or its holder's house proved
+    // life again.
+    let housedata = load_account::<HouseData>(program_id, holder_house_account_info)?;
+    let realm_config = load_realm_config(program_id, realm_config_account_info)?;
+    if td.holder_house_address != election.holder_house_address
+        || !housedata.has_vacated(slot, &realm_config.settings)
+    {
+        msg!("Election lapsed, the title is no longer vacant");
+        return close_account(election_account_info, caller_wallet_info);
+    }
+
+    let winner_house_address =
+        match election.winner(td.live_vassal_count, slot, &realm_config.settings) {
+            Some(winner_house_address) => winner_house_address,
+            None if !election.has_ended(slot, &realm_config.settings) => {
+                msg!(
+                    "No candidate has won yet, election opened at slot {} with {} ballots",
+                    election.opened_slot,
+                    election.ballots.len()
+                );
+                return Err(TitleError::ElectionUndecided.into());
+            }
+            // Nobody voted, the title passes to the heir as by the Heir succession law.
+            None if housedata.heir_house_address != Pubkey::new(&[0; 32]) => {
+                housedata.heir_house_address
+            }
+            None => {
+                vacate_title_to_treasury(
+                    program_id,
+                    &td,
+                    title_account_info,
+                    winner_house_account_info,
+                    account_info_iter,
+                    slot,
+                )?;
+                close_account(election_account_info, caller_wallet_info)?;
+                return remove_held_titles(
+                    program_id,
+                    holder_house_account_info,
+                    holder_holdings_account_info,
+                    &[*title_account_info.key],
+                );
+            }
+        };
+    check_authority(winner_house_account_info, &winner_house_address)?;
+    let winner_housedata = load_account::<HouseData>(program_id, winner_house_account_info)?;
+    if !winner_housedata.is_initialized() {
+        msg!("Error: winner house is uninitialized");
+        return Err(TitleError::UninitializedAccount.into());
+    }
+
+    msg!("Handing title to house {}", winner_house_address);
+    close_account(election_account_info, caller_wallet_info)?;
+    // The title passes as it would to an heir, with its stake and sale price.
+    {
+        let mut title_data = title_account_info.data.borrow_mut();
+        let mut title = TitleDataMut::new(&mut title_data)?;
+        title.set_holder_house_address(&winner_house_address);
+        if td.lifecycle_state == TitleData::INACTIVE_STATE {
+            title.set_last_tax_paid_slot(slot);
+        }
+    }
+    remove_held_titles(
+        program_id,
+        holder_house_account_info,
+        holder_holdings_account_info,
+        &[*title_account_info.key],
+    )?;
+    add_held_titles(
+        program_id,
+        winner_house_account_info,
+        winner_holdings_account_info,
+        &[*title_account_info.key],
+        caller_wallet_info,
+        system_account_info,
+    )
+}
+
+/// Revert a vacant title to an inactive title held by its realm's treasury, listed for
+/// sale at its required stake as on escheat. The remaining stake of an active title,
+/// the next account of the iterator, goes to the treasury.
+fn vacate_title_to_treasury<'a, 'b>(
+    program_id: &Pubkey,
+    td: &TitleData,
+    title_account_info: &AccountInfo<'b>,
+    treasury_account_info: &AccountInfo<'b>,
+    account_info_iter: &mut std::slice::Iter<'a, AccountInfo<'b>>,
+    slot: u64,
+) -> ProgramResult {
+    let (treasury_address, _) =
+        get_treasury_address_and_bump_seed_internal(&td.realm_address, program_id);
+    if treasury_address != *treasury_account_info.key {
+        msg!("Error: Treasury address does not match seed derivation");
+        return Err(ProgramError::InvalidSeeds);
+    }
+    if td.lifecycle_state == TitleData::ACTIVE_STATE {
+        let stake_account_info = next_account_info(account_info_iter)?;
+        if !stake_account_info.is_writable {
+            msg!("No write permission for stake account");
+            return Err(TitleError::AccountNotWritable.into());
+        }
+        if td.stake_address != *stake_account_info.key {
+            msg!(
+                "Expected stake account {}, got {}",
+                td.stake_address,
+                stake_account_info.key
+            );
+            return Err(TitleError::IncorrectStakeAccount.into());
+        }
+        check_account_owner(program_id, stake_account_info)?;
+        check_account_type(&stake_account_info.data.borrow(), AccountType::StakeVault)?;
+        close_account(stake_account_info, treasury_account_info)?;
+    }
+
+    msg!("Election ended without ballots or heir, title passes to the treasury");
+    let mut title_data = title_account_info.data.borrow_mut();
+    let mut title = TitleDataMut::new(&mut title_data)?;
+    title.set_lifecycle_state(TitleData::INACTIVE_STATE);
+    title.set_stake_address(&Pubkey::new(&[0; 32]));
+    title.set_sale_price_lamports(td.required_stake_lamports);
+    title.set_holder_house_address(&treasury_address);
+    title.set_last_tax_paid_slot(slot);
+    Ok(())
+}
+
+/// Whether a title is held by its realm's treasury, see ConcludeElection. The treasury
+/// keeps no holdings.
+fn is_held_by_treasury(program_id: &Pubkey, td: &TitleData) -> bool {
+    let (treasury_address, _) =
+        get_treasury_address_and_bump_seed_internal(&td.realm_address, program_id);
+    td.holder_house_address == treasury_address
+}
+
+/// Append a vassal to a liege title's vassal list, creating the next vassal page if the
+/// last one is full. The caller must check that the liege may take another vassal, see
+/// check_vassal_capacity.
+fn append_vassal<'a>(
+    program_id: &Pubkey,
+    liege_td: &TitleData,
+    liege_title_account_info: &AccountInfo<'a>,
+    vassal_page_account_info: &AccountInfo<'a>,
+    funder_wallet_info: &AccountInfo<'a>,
+    system_account_info: &AccountInfo<'a>,
+    vassal_address: &Pubkey,
+) -> ProgramResult {
+    let (page_index, position) = VassalPageData::locate(liege_td.vassal_count);
+    if position == 0 {
+        create_vassal_page(
+            program_id,
+            liege_title_account_info.key,
+            page_index,
+            vassal_page_account_info,
+            funder_wallet_info,
+            system_account_info,
+            vec![*vassal_address],
+        )?;
+    } else {
+        let (page_address, _) = get_vassal_page_address_and_bump_seed_internal(
+            liege_title_account_info.key,
+            page_index,
+            program_id,
+        );
+        if page_address != *vassal_page_account_info.key {
+            msg!("Error: Vassal page address does not match seed derivation");
+            return Err(ProgramError::InvalidSeeds);
+        }
+        let mut page = load_account::<VassalPageData>(program_id, vassal_page_account_info)?;
+        page.vassal_addresses.push(*vassal_address);
+        page.serialize(&mut *vassal_page_account_info.data.borrow_mut())?;
+    }
+    let mut liege_title_data = liege_title_account_info.data.borrow_mut();
+    let mut liege_title = TitleDataMut::new(&mut liege_title_data)?;
+    liege_title.set_vassal_count(
+        liege_td
+            .vassal_count
+            .checked_add(1)
+            .ok_or(TitleError::Overflow)?,
+    );
+    liege_title.set_live_vassal_count(liege_td.live_vassal_count + 1);
+    Ok(())
+}
+
+/// Count the vassals of a title left in its vassal pages, which are the next accounts
+/// of the iterator.
+fn count_live_vassals<'a, 'b>(
+    program_id: &Pubkey,
+    title_address: &Pubkey,
+    vassal_count: u16,
+    account_info_iter: &mut std::slice::Iter<'a, AccountInfo<'b>>,
+) -> Result<u16, ProgramError> {
+    let page_count = (vassal_count as usize).div_ceil(VassalPageData::VASSALS_PER_PAGE);
+    let mut live_vassal_count = 0;
+    for page_index in 0..page_count as u16 {
+        let vassal_page_account_info = next_account_info(account_info_iter)?;
+        let (page_address, _) =
+            get_vassal_page_address_and_bump_seed_internal(title_address, page_index, program_id);
+        if page_address != *vassal_page_account_info.key {
+            msg!("Error: Vassal page address does not match seed derivation");
+            return Err(ProgramError::InvalidSeeds);
+        }
+        let page = load_account::<VassalPageData>(program_id, vassal_page_account_info)?;
+        live_vassal_count += page
+            .vassal_addresses
+            .iter()
+            .filter(|address| **address != Pubkey::new(&[0; 32]))
+            .count() as u16;
+    }
+    Ok(live_vassal_count)
+}
+
+/// Create the given page of a title's vassal list, holding the given vassal addresses.
+fn create_vassal_page<'a>(
+    program_id: &Pubkey,
+    title_address: &Pubkey,
+    page_index: u16,
+    vassal_page_account_info: &AccountInfo<'a>,
+    funder_wallet_info: &AccountInfo<'a>,
+    system_account_info: &AccountInfo<'a>,
+    vassal_addresses: Vec<Pubkey>,
+) -> ProgramResult {
+    let (page_address, bump_seed) =
+        get_vassal_page_address_and_bump_seed_internal(title_address, page_index, program_id);
+    if page_address != *vassal_page_account_info.key {
+        msg!("Error: Vassal page address does not match seed derivation");
+        return Err(ProgramError::InvalidSeeds);
+    }
+    let vassal_page_signer_seeds: &[&[_]] = &[
+        &title_address.to_bytes(),
+        b"vassals",
+        &page_index.to_le_bytes(),
+        &[bump_seed],
+    ];
+
+    let rent = Rent::get().unwrap();
+    msg!("Creating vassal page {}: {}", page_index, page_address);
+    invoke_signed(
+        &system_instruction::create_account(
+            funder_wallet_info.key,
+            vassal_page_account_info.key,
+            rent.minimum_balance(VassalPageData::SIZE).max(1),
+            VassalPageData::SIZE as u64,
+            program_id, // owner
+        ),
+        &[
+            funder_wallet_info.clone(),
+            vassal_page_account_info.clone(),
+            system_account_info.clone(),
+        ],
+        &[vassal_page_signer_seeds],
+    )?;
+
+    VassalPageData {
+        account_type: AccountType::VassalPage,
+        version: VassalPageData::CURRENT_VERSION,
+        title_address: *title_address,
+        page_index,
+        vassal_addresses,
+    }
+    .serialize(&mut *vassal_page_account_info.data.borrow_mut())
+    .map_err(|e| e.into())
+}
+
+/// Check that an active title has had its tax collected in the current slot, so that the
+/// current holder pays for the time they held the title at its current price.
+fn check_tax_settled(td: &TitleData) -> ProgramResult {
+    if td.lifecycle_state == TitleData::ACTIVE_STATE && td.last_tax_paid_slot != Clock::get()?.slot
+    {
+        msg!(
+            "Tax last paid at slot {}, collect tax first",
+            td.last_tax_paid_slot
+        );
+        return Err(TitleError::TaxNotSettled.into());
+    }
+    Ok(())
+}
+
+fn check_authority(authority_info: &AccountInfo, expected_authority: &Pubkey) -> ProgramResult {
+    if expected_authority != authority_info.key {
+        msg!(
+            "Expected house {}, got house {}",
+            expected_authority,
+            authority_info.key
+        );
+        return Err(TitleError::IncorrectAuthority.into());
+    }
+    Ok(())
+}
+
+/// Check that the wallet governs the house: the house's authority, or for houses
+/// without one, the wallet the house is derived from. Returns the house data.
+fn check_house_authority(
+    program_id: &Pubkey,
+    wallet_info: &AccountInfo,
+    house_account_info: &AccountInfo,
+) -> Result<HouseData, ProgramError> {
+    let housedata = load_account::<HouseData>(program_id, house_account_info)?;
+    if housedata.authority == Pubkey::new(&[0; 32]) {
+        let (house_address, _) =
+            get_house_address_and_bump_seed_internal(wallet_info.key, program_id);
+        if house_address != *house_account_info.key {
+            msg!("Error: House address does not match seed derivation");
+            return Err(ProgramError::InvalidSeeds);
+        }
+    } else if housedata.authority != *wallet_info.key {
+        msg!(
+            "House is governed by {}, got wallet {}",
+            housedata.authority,
+            wallet_info.key
+        );
+        return Err(TitleError::IncorrectAuthority.into());
+    }
+    Ok(housedata)
+}
+
+/// Record titles that the house came to hold: count them, and list them in the house's
+/// holdings, creating or growing them at the funder's expense. The caller must check
+/// that the house is the titles' new holder.
+fn add_held_titles<'a>(
+    program_id: &Pubkey,
+    house_account_info: &AccountInfo<'a>,
+    holdings_account_info: &AccountInfo<'a>,
+    title_addresses: &[Pubkey],
+    funder_info: &AccountInfo<'a>,
+    system_account_info: &AccountInfo<'a>,
+) -> ProgramResult {
+    let mut housedata = load_account::<HouseData>(program_id, house_account_info)?;
+    for _ in title_addresses {
+        housedata.add_held_title()?;
+    }
+    housedata.serialize(&mut *house_account_info.data.borrow_mut())?;
+
+    let bump_seed =
+        check_holdings_account(program_id, house_account_info.key, holdings_account_info)?;
+    let mut holdings = if holdings_account_info.data_is_empty() {
+        let holdings_account_signer_seeds: &[&[_]] = &[
+            &house_account_info.key.to_bytes(),
+            b"holdings",
+            &[bump_seed],
+        ];
+        let new_len = HoldingsData::size(title_addresses.len());
+        fund_rent(
+            program_id,
+            funder_info,
+            holdings_account_info,
+            new_len,
+            system_account_info,
+        )?;
+        // Funded directly, like the titles of accepted petitions, since the funder may
+        // be owned by the program.
+        msg!("Creating holdings: {}", holdings_account_info.key);
+        invoke_signed(
+            &system_instruction::allocate(holdings_account_info.key, new_len as u64),
+            &[holdings_account_info.clone(), system_account_info.clone()],
+            &[holdings_account_signer_seeds],
+        )?;
+        invoke_signed(
+            &system_instruction::assign(holdings_account_info.key, program_id),
+            &[holdings_account_info.clone(), system_account_info.clone()],
+            &[holdings_account_signer_seeds],
+        )?;
+        HoldingsData {
+            account_type: AccountType::Holdings,
+            version: HoldingsData::CURRENT_VERSION,
+            house_address: *house_account_info.key,
+            title_addresses: vec![],
+        }
+    } else {
+        let holdings = load_account::<HoldingsData>(program_id, holdings_account_info)?;
+        let new_len = HoldingsData::size(holdings.title_addresses.len() + title_addresses.len());
+        fund_rent(
+            program_id,
+            funder_info,
+            holdings_account_info,
+            new_len,
+            system_account_info,
+        )?;
+        holdings_account_info.realloc(new_len, false)?;
+        holdings
+    };
+    holdings.title_addresses.extend_from_slice(title_addresses);
+    holdings
+        .serialize(&mut *holdings_account_info.data.borrow_mut())
+        .map_err(|e| e.into())
+}
+
+/// Record that the house no longer holds titles: uncount them, and drop them from the
+/// house's holdings, handing the rent they no longer need to the house. Titles held
+/// since before holdings were recorded are missing from them. The caller must check
+/// that the house was the titles' holder.
+fn remove_held_titles(
+    program_id: &Pubkey,
+    house_account_info: &AccountInfo,
+    holdings_account_info: &AccountInfo,
+    title_addresses: &[Pubkey],
+) -> ProgramResult {
+    let mut housedata = load_account::<HouseData>(program_id, house_account_info)?;
+    for _ in title_addresses {
+        housedata.remove_held_title()?;
+    }
+    housedata.serialize(&mut *house_account_info.data.borrow_mut())?;
+
+    check_holdings_account(program_id, house_account_info.key, holdings_account_info)?;
+    if holdings_account_info.data_is_empty() {
+        msg!("House {} has no holdings", house_account_info.key);
+        return Ok(());
+    }
+    let mut holdings = load_account::<HoldingsData>(program_id, holdings_account_info)?;
+    for title_address in title_addresses {
+        match holdings
+            .title_addresses
+            .iter()
+            .position(|address| address == title_address)
+        {
+            Some(position) => {
+                holdings.title_addresses.swap_remove(position);
+            }
+            None => msg!(
+                "Title {} is missing from the house's holdings",
+                title_address
+            ),
+        }
+    }
+    let new_len = HoldingsData::size(holdings.title_addresses.len());
+    holdings_account_info.realloc(new_len, false)?;
+    holdings.serialize(&mut *holdings_account_info.data.borrow_mut())?;
+
+    let excess_lamports = holdings_account_info
+        .lamports()
+        .saturating_sub(Rent::get()?.minimum_balance(new_len).max(1));
+    let house_lamports = house_account_info
+        .lamports()
+        .checked_add(excess_lamports)
+        .ok_or(TitleError::Overflow)?;
+    **holdings_account_info.try_borrow_mut_lamports()? -= excess_lamports;
+    **house_account_info.try_borrow_mut_lamports()? = house_lamports;
+    Ok(())
+}
+
+/// Check that the holdings account is the writable holdings of the given house, and
+/// return its bump seed.
+fn check_holdings_account(
+    program_id: &Pubkey,
+    house_address: &Pubkey,
+    holdings_account_info: &AccountInfo,
+) -> Result<u8, ProgramError> {
+    let (holdings_address, bump_seed) =
+        get_holdings_address_and_bump_seed_internal(house_address, program_id);
+    if holdings_address != *holdings_account_info.key {
+        msg!("Error: Holdings address does not match seed derivation");
+        return Err(ProgramError::InvalidSeeds);
+    }
+    if !holdings_account_info.is_writable {
+        msg!("No write permission for holdings account");
+        return Err(TitleError::AccountNotWritable.into());
+    }
+    Ok(bump_seed)
+}
+
+/// Top up an account to the rent exemption of the given data length. A funder owned by
+/// the program pays directly, a wallet pays through the system program.
+fn fund_rent<'a>(
+    program_id: &Pubkey,
+    funder_info: &AccountInfo<'a>,
+    account_info: &AccountInfo<'a>,
+    data_len: usize,
+    system_account_info: &AccountInfo<'a>,
+) -> ProgramResult {
+    let required_lamports = Rent::get()?
+        .minimum_balance(data_len)
+        .max(1)
+        .saturating_sub(account_info.lamports());
+    if required_lamports == 0 {
+        return Ok(());
+    }
+    if funder_info.owner != program_id {
+        return invoke(
+            &system_instruction::transfer(funder_info.key, account_info.key, required_lamports),
+            &[
+                funder_info.clone(),
+                account_info.clone(),
+                system_account_info.clone(),
+            ],
+        );
+    }
+    let funder_lamports = funder_info
+        .lamports()
+        .checked_sub(required_lamports)
+        .ok_or(TitleError::Overflow)?;
+    let account_lamports = account_info
+        .lamports()
+        .checked_add(required_lamports)
+        .ok_or(TitleError::Overflow)?;
+    **funder_info.try_borrow_mut_lamports()? = funder_lamports;
+    **account_info.try_borrow_mut_lamports()? = account_lamports;
+    Ok(())
+}
+
+/// Create an account at a program address, funded to rent exemption by the funder and
+/// owned by the given program. Funded before it is allocated, so that lamports sent to
+/// the address beforehand can't keep it from being created.
+fn create_pda_account<'a>(
+    program_id: &Pubkey,
+    funder_info: &AccountInfo<'a>,
+    account_info: &AccountInfo<'a>,
+    data_len: usize,
+    owner: &Pubkey,
+    signer_seeds: &[&[u8]],
+    system_account_info: &AccountInfo<'a>,
+) -> ProgramResult {
+    fund_rent(
+        program_id,
+        funder_info,
+        account_info,
+        data_len,
+        system_account_info,
+    )?;
+    invoke_signed(
+        &system_instruction::allocate(account_info.key, data_len as u64),
+        &[account_info.clone(), system_account_info.clone()],
+        &[signer_seeds],
+    )?;
+    invoke_signed(
+        &system_instruction::assign(account_info.key, owner),
+        &[account_info.clone(), system_account_info.clone()],
+        &[signer_seeds],
+    )
+}
+
+/// Load an initialized proposal
+fn load_proposal(
+    program_id: &Pubkey,
+    proposal_account_info: &AccountInfo,
+) -> Result<ProposalData, ProgramError> {
+    let proposal = load_account::<ProposalData>(program_id, proposal_account_info)?;
+    if !proposal.is_initialized() {
+        msg!("Error: proposal is uninitialized");
+        return Err(TitleError::UninitializedAccount.into());
+    }
+    Ok(proposal)
+}
+
+/// Check that the escrow account is the escrow of the given proposal, and return its
+/// bump seed.
+fn check_proposal_escrow(
+    program_id: &Pubkey,
+    proposal_address: &Pubkey,
+    escrow_account_info: &AccountInfo,
+) -> Result<u8, ProgramError> {
+    let (escrow_address, bump_seed) =
+        get_proposal_escrow_address_and_bump_seed_internal(proposal_address, program_id);
+    if escrow_address != *escrow_account_info.key {
+        msg!("Error: Escrow address does not match seed derivation");
+        return Err(ProgramError::InvalidSeeds);
+    }
+    if !escrow_account_info.is_writable {
+        msg!("No write permission for escrow account");
+        return Err(TitleError::AccountNotWritable.into());
+    }
+    Ok(bump_seed)
+}
+
+/// Check that the realm allows titles of the given rank and kind
+fn check_rank_and_kind(settings: &RealmSettings, rank: Rank, kind: Kind) -> ProgramResult {
+    if rank > settings.max_rank {
+        msg!(
+            "Rank {} is below the lowest rank allowed, {}",
+            rank,
+            settings.max_rank
+        );
+        return Err(TitleError::RankNotAllowed.into());
+    }
+    if kind as u8 > settings.max_kind as u8 {
+        msg!("Kind {} is not allowed", kind);
+        return Err(TitleError::KindNotAllowed.into());
+    }
+    Ok(())
+}
+
+/// Load a petition to become a vassal of the given liege title
+fn load_petition(
+    program_id: &Pubkey,
+    petition_account_info: &AccountInfo,
+    liege_title_account_info: &AccountInfo,
+) -> Result<PetitionData, ProgramError> {
+    let petition = load_account::<PetitionData>(program_id, petition_account_info)?;
+    if !petition.is_initialized() {
+        msg!("Error: petition is uninitialized");
+        return Err(TitleError::UninitializedAccount.into());
+    }
+    if petition.liege_address != *liege_title_account_info.key {
+        msg!(
+            "Petition is to liege {}, got liege {}",
+            petition.liege_address,
+            liege_title_account_info.key
+        );
+        return Err(TitleError::IncorrectLiege.into());
+    }
+    Ok(petition)
+}
+
+/// Check that the house is the petitioning house, and that the wallet governs it
+fn check_petitioner(
+    program_id: &Pubkey,
+    petitioner_wallet_info: &AccountInfo,
+    petitioner_house_account_info: &AccountInfo,
+    petition: &PetitionData,
+) -> ProgramResult {
+    if *petitioner_house_account_info.key != petition.petitioner_house_address {
+        msg!(
+            "Petition is by house {}, got house {}",
+            petition.petitioner_house_address,
+            petitioner_house_account_info.key
+        );
+        return Err(TitleError::IncorrectPetitioner.into());
+    }
+    check_house_authority(
+        program_id,
+        petitioner_wallet_info,
+        petitioner_house_account_info,
+    )?;
+    Ok(())
+}
+
+/// Check that a liege title may take another vassal
+fn check_vassal_capacity(settings: &RealmSettings, liege_td: &TitleData) -> ProgramResult {
+    if liege_td.live_vassal_count >= settings.max_vassals {
+        msg!("Liege already has {} vassals", liege_td.live_vassal_count);
+        return Err(TitleError::TooManyVassals.into());
+    }
+    Ok(())
+}
+
+/// Upgrade authority recorded in a program's ProgramData account. The account data is
+/// bincode encoded: a u32 tag (3 for ProgramData), the deployment slot, then an
+/// optional authority.
+fn get_upgrade_authority(program_data: &[u8]) -> Option<Pubkey> {
+    if program_data.len() < 45 || program_data[..4] != 3u32.to_le_bytes() || program_data[12] != 1
+    {
+        return None;
+    }
+    Some(Pubkey::new(&program_data[13..45]))
+}
+
+/// Load the realm config, checking its address
+fn load_realm_config(
+    program_id: &Pubkey,
+    realm_config_account_info: &AccountInfo,
+) -> Result<RealmConfigData, ProgramError> {
+    let (realm_config_address, _) = get_realm_config_address_and_bump_seed_internal(program_id);
+    if realm_config_address != *realm_config_account_info.key {
+        msg!("Error: Realm config address does not match seed derivation");
+        return Err(ProgramError::InvalidSeeds);
+    }
+    let realm_config = load_account::<RealmConfigData>(program_id, realm_config_account_info)?;
+    if !realm_config.is_initialized() {
+        msg!("Error: realm config is uninitialized");
+        return Err(TitleError::UninitializedAccount.into());
+    }
+    Ok(realm_config)
+}
+
+/// Check realm settings given in an instruction
+fn check_realm_settings(settings: &RealmSettings) -> ProgramResult {
+    settings.validate().map_err(|e| {
+        msg!("Invalid realm settings {:?}: {}", settings, e);
+        e
+    })?;
+    Ok(())
+}
+
+/// Check a display name given in an instruction
+fn check_display_name(display_name: &str) -> ProgramResult {
+    validate_display_name(display_name.as_bytes()).map_err(|e| {
+        msg!("Invalid display name {:?}: {}", display_name, e);
+        e
+    })?;
+    Ok(())
+}
+
+/// Check a coat of arms URI given in an instruction
+fn check_coat_of_arms(coat_of_arms: &str) -> ProgramResult {
+    validate_coat_of_arms(coat_of_arms.as_bytes()).map_err(|e| {
+        msg!("Invalid coat of arms {:?}: {}", coat_of_arms, e);
+        e
+    })?;
+    Ok(())
+}
+
+/// Check SPL token program address
+fn check_token_program(program_id: &Pubkey) -> Result<(), ProgramError> {
+    if *program_id != spl_token::id() {
+        msg!(
+            "Expected token program {}, received {}",
+            spl_token::id(),
+            program_id
+        );
+        Err(ProgramError::IncorrectProgramId)
+    } else {
+        Ok(())
+    }
+}
+
+/// Check system program address
+fn check_system_program(program_id: &Pubkey) -> Result<(), ProgramError> {
+    if *program_id != system_program::id() {
+        msg!(
+            "Expected system program {}, received {}",
+            system_program::id(),
+            program_id
+        );
+        Err(ProgramError::IncorrectProgramId)
+    } else {
+        Ok(())
+    }
+}
+
+/// Tests
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        get_election_address, get_governance_address, get_holdings_address, get_house_address,
+        get_realm_address, get_realm_config_address, get_stake_address, get_treasury_address,
+        get_vassal_page_address,
+        instruction::{self, TitleParams, VassalSlot},
+        state::{
+            pack_string,
+            tests::{
+                test_holdings_data, test_house_data, test_realm_config_data, test_realm_data,
+                test_title_data, test_vassal_page_data,
+            },
+            MAX_STRING_LENGTH,
+        },
+        utils::try_from_account_data,
+    };
+    use solana_program::{
+        decode_error::DecodeError,
+        entrypoint::MAX_PERMITTED_DATA_INCREASE,
+        instruction::{AccountMeta, Instruction},
+        program_stubs,
+    };
+    use std::{collections::HashMap, convert::TryInto};
+
+    /// Slot of the clock in processor tests
+    const TEST_SLOT: u64 = 5000;
+
+    /// Syscalls for processor tests: the clock at TEST_SLOT, default rent, and transfers
+    /// and account creation of the system program between the instruction's accounts.
+    struct TestSyscallStubs;
+
+    impl program_stubs::SyscallStubs for TestSyscallStubs {
+        fn sol_get_clock_sysvar(&self, var_addr: *mut u8) -> u64 {
+            let clock = Clock {
+                slot: TEST_SLOT,
+                ..Clock::default()
+            };
+            unsafe { *(var_addr as *mut Clock) = clock };
+            solana_program::entrypoint::SUCCESS
+        }
+
+        fn sol_get_rent_sysvar(&self, var_addr: *mut u8) -> u64 {
+            unsafe { *(var_addr as *mut Rent) = Rent::default() };
+            solana_program::entrypoint::SUCCESS
+        }
+
+        fn sol_invoke_signed(
+            &self,
+            instruction: &Instruction,
+            account_infos: &[AccountInfo],
+            _signers_seeds: &[&[&[u8]]],
+        ) -> ProgramResult {
+            assert_eq!(instruction.program_id, system_program::id());
+            // Only SystemInstruction::CreateAccount and Transfer, variants 0 and 2, are
+            // supported.
+            let variant = u32::from_le_bytes(instruction.data[..4].try_into().unwrap());
+            assert!(variant == 0 || variant == 2);
+            let lamports = u64::from_le_bytes(instruction.data[4..12].try_into().unwrap());
+            let find = |meta: &AccountMeta| {
+                account_infos
+                    .iter()
+                    .find(|account_info| *account_info.key == meta.pubkey)
+                    .unwrap()
+            };
+            let from = find(&instruction.accounts[0]);
+            let to = find(&instruction.accounts[1]);
+            **from.lamports.borrow_mut() = from
+                .lamports()
+                .checked_sub(lamports)
+                .ok_or(ProgramError::InsufficientFunds)?;
+            **to.lamports.borrow_mut() += lamports;
+            if variant == 0 {
+                // Accounts can't be resized here, so tests pass in the new account with
+                // its final size and owner.
+                let space = u64::from_le_bytes(instruction.data[12..20].try_into().unwrap());
+                assert_eq!(to.data_len() as u64, space);
+                assert_eq!(to.owner.as_ref(), &instruction.data[20..52]);
+            }
+            Ok(())
+        }
+    }
+
+    /// Account for processor tests. Like accounts serialized by the runtime, the data is
+    /// preceded by its length and followed by room to grow.
+    struct TestAccount {
+        key: Pubkey,
+        lamports: u64,
+        owner: Pubkey,
+        buffer: Vec<u8>,
+    }
+
+    impl TestAccount {
+        fn new(key: Pubkey, lamports: u64, owner: Pubkey, data: &[u8]) -> Self {
+            let mut buffer = vec![0; 8 + data.len() + MAX_PERMITTED_DATA_INCREASE];
+            buffer[..8].copy_from_slice(&(data.len() as u64).to_le_bytes());
+            buffer[8..8 + data.len()].copy_from_slice(data);
+            TestAccount {
+                key,
+                lamports,
+                owner,
+                buffer,
+            }
+        }
+
+        fn wallet(key: Pubkey, lamports: u64) -> Self {
+            Self::new(key, lamports, system_program::id(), &[])
+        }
+
+        /// Account of the program holding the given state, funded for rent exemption.
+        fn program(key: Pubkey, state: &impl TestState) -> Self {
+            let data = state.account_data();
+            let lamports = Rent::default().minimum_balance(data.len());
+            Self::new(key, lamports, crate::id(), &data)
+        }
+
+        fn data(&self) -> &[u8] {
+            let len = u64::from_le_bytes(self.buffer[..8].try_into().unwrap()) as usize;
+            &self.buffer[8..8 + len]
+        }
+
+        fn info(&mut self) -> AccountInfo<'_> {
+            let len = self.data().len();
+            AccountInfo::new(
+                &self.key,
+                false,
+                false,
+                &mut self.lamports,
+                &mut self.buffer[8..8 + len],
+                &self.owner,
+                false,
+                0,
+            )
+        }
+    }
+
+    /// State the program keeps in its accounts.
+    trait TestState {
+        /// Account data holding the state, at the size the program allocates.
+        fn account_data(&self) -> Vec<u8>;
+    }
+
+    impl TestState for HouseData {
+        fn account_data(&self) -> Vec<u8> {
+            let mut data = self.try_to_vec().unwrap();
+            data.resize(HouseData::SIZE, 0);
+            data
+        }
+    }
+
+    impl TestState for TitleData {
+        fn account_data(&self) -> Vec<u8> {
+            let mut data = vec![0; TitleData::SIZE];
+            self.pack_into_slice(&mut data);
+            data
+        }
+    }
+
+    impl TestState for RealmData {
+        fn account_data(&self) -> Vec<u8> {
+            let mut data = self.try_to_vec().unwrap();
+            data.resize(RealmData::SIZE, 0);
+            data
+        }
+    }
+
+    impl TestState for HoldingsData {
+        fn account_data(&self) -> Vec<u8> {
+            self.try_to_vec().unwrap()
+        }
+    }
+
+    impl TestState for VassalPageData {
+        fn account_data(&self) -> Vec<u8> {
+            let mut data = self.try_to_vec().unwrap();
+            data.resize(VassalPageData::SIZE, 0);
+            data
+        }
+    }
+
+    impl TestState for RealmConfigData {
+        fn account_data(&self) -> Vec<u8> {
+            self.try_to_vec().unwrap()
+        }
+    }
+
+    impl TestState for ProposalData {
+        fn account_data(&self) -> Vec<u8> {
+            self.try_to_vec().unwrap()
+        }
+    }
+
+    impl TestState for ElectionData {
+        fn account_data(&self) -> Vec<u8> {
+            self.try_to_vec().unwrap()
+        }
+    }
+
+    /// Process the instruction with the given accounts, which must include each account
+    /// the instruction names.
+    fn process_test_instruction(
+        instruction: &Instruction,
+        accounts: &mut [TestAccount],
+    ) -> ProgramResult {
+        program_stubs::set_syscall_stubs(Box::new(TestSyscallStubs));
+        let account_infos: HashMap<Pubkey, AccountInfo> = accounts
+            .iter_mut()
+            .map(|account| (account.key, account.info()))
+            .collect();
+        let account_infos: Vec<AccountInfo> = instruction
+            .accounts
+            .iter()
+            .map(|meta| {
+                let mut account_info = account_infos[&meta.pubkey].clone();
+                account_info.is_signer = meta.is_signer;
+                account_info.is_writable = meta.is_writable;
+                account_info
+            })
+            .collect();
+        process_instruction(&instruction.program_id, &account_infos, &instruction.data)
+    }
+
+    #[test]
+    fn buy_title() {
+        let seller_wallet_address = Pubkey::new_unique();
+        let seller_house_address = get_house_address(&seller_wallet_address);
+        let buyer_wallet_address = Pubkey::new_unique();
+        let buyer_house_address = get_house_address(&buyer_wallet_address);
+        let title_address = Pubkey::new_unique();
+        let title = TitleData {
+            lifecycle_state: TitleData::INACTIVE_STATE,
+            holder_house_address: seller_house_address,
+            ..test_title_data()
+        };
+        let mut accounts = vec![
+            TestAccount::wallet(buyer_wallet_address, 100_000_000),
+            TestAccount::program(buyer_house_address, &test_house_data()),
+            TestAccount::program(title_address, &title),
+            TestAccount::wallet(seller_wallet_address, 0),
+            TestAccount::wallet(system_program::id(), 0),
+            TestAccount::program(
+                get_realm_config_address(),
+                &test_realm_config_data(Pubkey::new_unique(), RealmSettings::default()),
+            ),
+            TestAccount::wallet(get_treasury_address(&title.realm_address), 0),
+            TestAccount::program(
+                seller_house_address,
+                &HouseData {
+                    held_title_count: 1,
+                    ..test_house_data()
+                },
+            ),
+            TestAccount::program(
+                get_holdings_address(&buyer_house_address),
+                &test_holdings_data(buyer_house_address, vec![]),
+            ),
+            TestAccount::program(
+                get_holdings_address(&seller_house_address),
+                &test_holdings_data(seller_house_address, vec![title_address]),
+            ),
+            TestAccount::wallet(get_stake_address(&title_address), 0),
+        ];
+
+        // The buyer pays the advertised price, no less.
+        let instruction = instruction::buy_title(
+            &buyer_wallet_address,
+            &buyer_house_address,
+            &title_address,
+            &seller_wallet_address,
+            &seller_house_address,
+            &title.realm_address,
+            title.sale_price_lamports - 1,
+        );
+        assert_eq!(
+            process_test_instruction(&instruction, &mut accounts),
+            Err(TitleError::SalePriceMismatch.into())
+        );
+        let instruction = instruction::buy_title(
+            &buyer_wallet_address,
+            &buyer_house_address,
+            &title_address,
+            &seller_wallet_address,
+            &seller_house_address,
+            &title.realm_address,
+            title.sale_price_lamports,
+        );
+        process_test_instruction(&instruction, &mut accounts).unwrap();
+
+        // The buyer also pays for listing the title in their holdings.
+        let holdings_rent = Rent::default().minimum_balance(HoldingsData::size(1))
+            - Rent::default().minimum_balance(HoldingsData::size(0));
+        assert_eq!(
+            accounts[0].lamports,
+            100_000_000 - title.sale_price_lamports - holdings_rent
+        );
+        assert_eq!(accounts[3].lamports, title.sale_price_lamports);
+        let bought = try_from_account_data::<TitleData>(accounts[2].data()).unwrap();
+        assert_eq!(bought.holder_house_address, buyer_house_address);
+        let buyer_house = try_from_account_data::<HouseData>(accounts[1].data()).unwrap();
+        assert_eq!(buyer_house.held_title_count, 1);
+        let seller_house = try_from_account_data::<HouseData>(accounts[7].data()).unwrap();
+        assert_eq!(seller_house.held_title_count, 0);
+        let buyer_holdings = try_from_account_data::<HoldingsData>(accounts[8].data()).unwrap();
+        assert_eq!(buyer_holdings.title_addresses, vec![title_address]);
+        let seller_holdings = try_from_account_data::<HoldingsData>(accounts[9].data()).unwrap();
+        assert!(seller_holdings.title_addresses.is_empty());
+    }
+
+    #[test]
+    fn buy_title_without_house() {
+        let seller_wallet_address = Pubkey::new_unique();
+        let seller_house_address = get_house_address(&seller_wallet_address);
+        let buyer_wallet_address = Pubkey::new_unique();
+        let buyer_house_address = get_house_address(&buyer_wallet_address);
+        let title_address = Pubkey::new_unique();
+        let title = TitleData {
+            lifecycle_state: TitleData::INACTIVE_STATE,
+            holder_house_address: seller_house_address,
+            ..test_title_data()
+        };
+        let mut accounts = vec![
+            TestAccount::wallet(buyer_wallet_address, 100_000_000),
+            TestAccount::wallet(buyer_house_address, 0),
+            TestAccount::program(title_address, &title),
+            TestAccount::wallet(seller_wallet_address, 0),
+            TestAccount::wallet(system_program::id(), 0),
+            TestAccount::program(
+                get_realm_config_address(),
+                &test_realm_config_data(Pubkey::new_unique(), RealmSettings::default()),
+            ),
+            TestAccount::wallet(get_treasury_address(&title.realm_address), 0),
+            TestAccount::program(
+                seller_house_address,
+                &HouseData {
+                    held_title_count: 1,
+                    ..test_house_data()
+                },
+            ),
+            TestAccount::program(
+                get_holdings_address(&buyer_house_address),
+                &test_holdings_data(buyer_house_address, vec![]),
+            ),
+            TestAccount::program(
+                get_holdings_address(&seller_house_address),
+                &test_holdings_data(seller_house_address, vec![title_address]),
+            ),
+            TestAccount::wallet(get_stake_address(&title_address), 0),
+        ];
+        let instruction = instruction::buy_title(
+            &buyer_wallet_address,
+            &buyer_house_address,
+            &title_address,
+            &seller_wallet_address,
+            &seller_house_address,
+            &title.realm_address,
+            title.sale_price_lamports,
+        );
+        assert_eq!(
+            process_test_instruction(&instruction, &mut accounts),
+            Err(TitleError::IncorrectAccountOwner.into())
+        );
+        assert_eq!(accounts[0].lamports, 100_000_000);
+        assert_eq!(accounts[3].lamports, 0);
+        let title_after = try_from_account_data::<TitleData>(accounts[2].data()).unwrap();
+        assert_eq!(title_after, title);
+    }
+
+    #[test]
+    fn buy_active_title_refunds_stake() {
+        let rent = Rent::default();
+        let seller_wallet_address = Pubkey::new_unique();
+        let seller_house_address = get_house_address(&seller_wallet_address);
+        let buyer_wallet_address = Pubkey::new_unique();
+        let buyer_house_address = get_house_address(&buyer_wallet_address);
+        let title_address = Pubkey::new_unique();
+        let title = TitleData {
+            holder_house_address: seller_house_address,
+            stake_address: get_stake_address(&title_address),
+            last_tax_paid_slot: TEST_SLOT,
+            ..test_title_data()
+        };
+        // The seller topped up their stake.
+        let stake_lamports = title.required_stake_lamports + 100_000;
+        let vault_rent = rent.minimum_balance(STAKE_VAULT_SIZE);
+
+        let mut accounts = vec![
+            TestAccount::wallet(buyer_wallet_address, 100_000_000),
+            TestAccount::program(buyer_house_address, &test_house_data()),
+            TestAccount::program(title_address, &title),
+            TestAccount::wallet(seller_wallet_address, 0),
+            TestAccount::wallet(system_program::id(), 0),
+            TestAccount::program(
+                get_realm_config_address(),
+                &test_realm_config_data(Pubkey::new_unique(), RealmSettings::default()),
+            ),
+            TestAccount::wallet(get_treasury_address(&title.realm_address), 0),
+            TestAccount::program(
+                seller_house_address,
+                &HouseData {
+                    held_title_count: 1,
+                    ..test_house_data()
+                },
+            ),
+            TestAccount::program(
+                get_holdings_address(&buyer_house_address),
+                &test_holdings_data(buyer_house_address, vec![]),
+            ),
+            TestAccount::program(
+                get_holdings_address(&seller_house_address),
+                &test_holdings_data(seller_house_address, vec![title_address]),
+            ),
+            TestAccount::new(
+                title.stake_address,
+                vault_rent + stake_lamports,
+                crate::id(),
+                &[AccountType::StakeVault as u8],
+            ),
+        ];
+        let instruction = instruction::buy_title(
+            &buyer_wallet_address,
+            &buyer_house_address,
+            &title_address,
+            &seller_wallet_address,
+            &seller_house_address,
+            &title.realm_address,
+            title.sale_price_lamports,
+        );
+        process_test_instruction(&instruction, &mut accounts).unwrap();
+
+        // The seller gets the sale price and their remaining stake, and the buyer funds
+        // the full required stake and their holdings.
+        assert_eq!(
+            accounts[3].lamports,
+            title.sale_price_lamports + stake_lamports
+        );
+        let holdings_rent = rent.minimum_balance(HoldingsData::size(1))
+            - rent.minimum_balance(HoldingsData::size(0));
+        assert_eq!(
+            accounts[0].lamports,
+            100_000_000 - title.sale_price_lamports - title.required_stake_lamports - holdings_rent
+        );
+        assert_eq!(
+            accounts[10].lamports,
+            vault_rent + title.required_stake_lamports
+        );
+        let bought = try_from_account_data::<TitleData>(accounts[2].data()).unwrap();
+        assert_eq!(bought.holder_house_address, buyer_house_address);
+    }
+
+    #[test]
+    fn activate_title() {
+        let rent = Rent::default();
+        let wallet_address = Pubkey::new_unique();
+        let house_address = get_house_address(&wallet_address);
+        let title_address = Pubkey::new_unique();
+        let stake_address = get_stake_address(&title_address);
+        let title = TitleData {
+            lifecycle_state: TitleData::INACTIVE_STATE,
+            holder_house_address: house_address,
+            stake_address: Pubkey::new(&[0; 32]),
+            ..test_title_data()
+        };
+        let mut accounts = vec![
+            TestAccount::wallet(wallet_address, 100_000_000),
+            TestAccount::program(house_address, &test_house_data()),
+            TestAccount::program(title_address, &title),
+            TestAccount::new(stake_address, 0, crate::id(), &[0; STAKE_VAULT_SIZE]),
+            TestAccount::wallet(system_program::id(), 0),
+        ];
+        let instruction = instruction::activate_title(
+            &wallet_address,
+            &house_address,
+            &title_address,
+            &stake_address,
+        );
+        process_test_instruction(&instruction, &mut accounts).unwrap();
+
+        // The holder funds the required stake in a rent exempt vault.
+        let vault_lamports = rent.minimum_balance(STAKE_VAULT_SIZE) + title.required_stake_lamports;
+        assert_eq!(accounts[0].lamports, 100_000_000 - vault_lamports);
+        assert_eq!(accounts[3].lamports, vault_lamports);
+        assert_eq!(accounts[3].data(), &[AccountType::StakeVault as u8]);
+        let activated = try_from_account_data::<TitleData>(accounts[2].data()).unwrap();
+        assert_eq!(activated.lifecycle_state, TitleData::ACTIVE_STATE);
+        assert_eq!(activated.stake_address, stake_address);
+        assert_eq!(activated.last_tax_paid_slot, TEST_SLOT);
+
+        // An active title can't be activated again.
+        assert_eq!(
+            process_test_instruction(&instruction, &mut accounts),
+            Err(TitleError::TitleAlreadyActive.into())
+        );
+    }
+
+    #[test]
+    fn set_sale_price() {
+        let wallet_address = Pubkey::new_unique();
+        let house_address = get_house_address(&wallet_address);
+        let title_address = Pubkey::new_unique();
+        let title = TitleData {
+            holder_house_address: house_address,
+            ..test_title_data()
+        };
+        let mut accounts = vec![
+            TestAccount::wallet(wallet_address, 0),
+            TestAccount::program(house_address, &test_house_data()),
+            TestAccount::program(title_address, &title),
+        ];
+        let set_price = |sale_price_lamports| {
+            instruction::set_sale_price(
+                &wallet_address,
+                &house_address,
+                &title_address,
+                sale_price_lamports,
+            )
+        };
+
+        // Tax must be collected before the price changes.
+        assert_eq!(
+            process_test_instruction(&set_price(Some(20_000_000)), &mut accounts),
+            Err(TitleError::TaxNotSettled.into())
+        );
+        accounts[2] = TestAccount::program(
+            title_address,
+            &TitleData {
+                last_tax_paid_slot: TEST_SLOT,
+                ..title.clone()
+            },
+        );
+        process_test_instruction(&set_price(Some(20_000_000)), &mut accounts).unwrap();
+        let listed = try_from_account_data::<TitleData>(accounts[2].data()).unwrap();
+        assert_eq!(listed.sale_price_lamports, 20_000_000);
+
+        // The price may not fall below the required stake, and None delists the title.
+        assert_eq!(
+            process_test_instruction(
+                &set_price(Some(title.required_stake_lamports - 1)),
+                &mut accounts
+            ),
+            Err(TitleError::SalePriceBelowRequiredStake.into())
+        );
+        process_test_instruction(&set_price(None), &mut accounts).unwrap();
+        let delisted = try_from_account_data::<TitleData>(accounts[2].data()).unwrap();
+        assert_eq!(delisted.sale_price_lamports, TitleData::NOT_FOR_SALE);
+    }
+
+    #[test]
+    fn withdraw_above_rent_exemption() {
+        let wallet_address = Pubkey::new_unique();
+        let house_address = get_house_address(&wallet_address);
+        let recipient_address = Pubkey::new_unique();
+        let house_rent = Rent::default().minimum_balance(HouseData::SIZE);
+        let mut house = TestAccount::program(house_address, &test_house_data());
+        house.lamports = house_rent + 500;
+        let mut accounts = vec![
+            TestAccount::wallet(wallet_address, 0),
+            house,
+            TestAccount::wallet(recipient_address, 0),
+        ];
+
+        let instruction = instruction::withdraw_from_house(
+            &wallet_address,
+            &house_address,
+            &recipient_address,
+            501,
+        );
+        assert_eq!(
+            process_test_instruction(&instruction, &mut accounts),
+            Err(TitleError::InsufficientLamports.into())
+        );
+        let instruction = instruction::withdraw_from_house(
+            &wallet_address,
+            &house_address,
+            &recipient_address,
+            500,
+        );
+        process_test_instruction(&instruction, &mut accounts).unwrap();
+        assert_eq!(accounts[1].lamports, house_rent);
+        assert_eq!(accounts[2].lamports, 500);
+
+        // The treasury of the legacy realm may be withdrawn by the admin only.
+        let admin_address = Pubkey::new_unique();
+        let legacy_realm_address = Pubkey::new(&[0; 32]);
+        let treasury_address = get_treasury_address(&legacy_realm_address);
+        let treasury_rent = Rent::default().minimum_balance(0);
+        let mut accounts = vec![
+            TestAccount::wallet(admin_address, 0),
+            TestAccount::wallet(wallet_address, 0),
+            TestAccount::program(
+                get_realm_config_address(),
+                &test_realm_config_data(admin_address, RealmSettings::default()),
+            ),
+            TestAccount::wallet(system_program::id(), 0),
+            TestAccount::wallet(treasury_address, treasury_rent + 700),
+            TestAccount::wallet(recipient_address, 0),
+        ];
+        let instruction = instruction::withdraw_from_treasury(
+            &wallet_address,
+            &legacy_realm_address,
+            &recipient_address,
+            700,
+        );
+        assert_eq!(
+            process_test_instruction(&instruction, &mut accounts),
+            Err(TitleError::IncorrectAccountOwner.into())
+        );
+        let instruction = instruction::withdraw_from_treasury(
+            &admin_address,
+            &legacy_realm_address,
+            &recipient_address,
+            701,
+        );
+        assert_eq!(
+            process_test_instruction(&instruction, &mut accounts),
+            Err(TitleError::InsufficientLamports.into())
+        );
+        let instruction = instruction::withdraw_from_treasury(
+            &admin_address,
+            &legacy_realm_address,
+            &recipient_address,
+            700,
+        );
+        process_test_instruction(&instruction, &mut accounts).unwrap();
+        assert_eq!(accounts[4].lamports, treasury_rent);
+        assert_eq!(accounts[5].lamports, 700);
+
+        // The authority of a realm may withdraw from its treasury.
+        let realm_address = get_realm_address("Avalon");
+        let treasury_address = get_treasury_address(&realm_address);
+        let mut accounts = vec![
+            TestAccount::wallet(admin_address, 0),
+            TestAccount::wallet(wallet_address, 0),
+            TestAccount::program(
+                get_realm_config_address(),
+                &test_realm_config_data(admin_address, RealmSettings::default()),
+            ),
+            TestAccount::program(
+                get_realm_address("Avalon"),
+                &test_realm_data("Avalon", wallet_address),
+            ),
+            TestAccount::wallet(treasury_address, treasury_rent + 700),
+            TestAccount::wallet(recipient_address, 0),
+            TestAccount::wallet(system_program::id(), 0),
+        ];
+        let instruction = instruction::withdraw_from_treasury(
+            &recipient_address,
+            &realm_address,
+            &recipient_address,
+            700,
+        );
+        assert_eq!(
+            process_test_instruction(&instruction, &mut accounts),
+            Err(TitleError::NotRealmAuthority.into())
+        );
+        let instruction = instruction::withdraw_from_treasury(
+            &wallet_address,
+            &realm_address,
+            &recipient_address,
+            700,
+        );
+        process_test_instruction(&instruction, &mut accounts).unwrap();
+        assert_eq!(accounts[4].lamports, treasury_rent);
+        assert_eq!(accounts[5].lamports, 700);
+    }
+
+    #[test]
+    fn close_house() {
+        let wallet_address = Pubkey::new_unique();
+        let house_address = get_house_address(&wallet_address);
+        let house_with_count = |held_title_count| HouseData {
+            held_title_count,
+            ..test_house_data()
+        };
+        let mut accounts = vec![
+            TestAccount::wallet(wallet_address, 0),
+            TestAccount::program(house_address, &house_with_count(1)),
+            TestAccount::program(
+                get_holdings_address(&house_address),
+                &test_holdings_data(house_address, vec![]),
+            ),
+        ];
+        let instruction = instruction::close_house(&wallet_address, &house_address);
+
+        // Houses holding titles, or that predate title counts, stay open.
+        assert_eq!(
+            process_test_instruction(&instruction, &mut accounts),
+            Err(TitleError::HouseHoldsTitles.into())
+        );
+        accounts[1] = TestAccount::program(
+            house_address,
+            &house_with_count(HouseData::UNKNOWN_TITLE_COUNT),
+        );
+        assert_eq!(
+            process_test_instruction(&instruction, &mut accounts),
+            Err(TitleError::HouseHoldsTitles.into())
+        );
+
+        accounts[1] = TestAccount::program(house_address, &house_with_count(0));
+        // The emptied holdings close with the house.
+        let house_lamports = accounts[1].lamports + accounts[2].lamports;
+        process_test_instruction(&instruction, &mut accounts).unwrap();
+        assert_eq!(accounts[0].lamports, house_lamports);
+        assert_eq!(accounts[1].lamports, 0);
+        assert_eq!(accounts[2].lamports, 0);
+        assert!(accounts[1].data().iter().all(|byte| *byte == 0));
+    }
+
+    #[test]
+    fn migrate_older_houses() {
+        let rent = Rent::default();
+        let funder_wallet_address = Pubkey::new_unique();
+        let cases = [
+            (3, HouseData::V3_SIZE),
+            (4, HouseData::V4_SIZE),
+            (5, HouseData::V5_SIZE),
+        ];
+        for (version, size) in cases {
+            let house_address = Pubkey::new_unique();
+            let house = HouseData {
+                version,
+                held_title_count: 2,
+                ..test_house_data()
+            };
+            let mut data = house.try_to_vec().unwrap();
+            data.resize(size, 0);
+            let mut accounts = vec![
+                TestAccount::wallet(funder_wallet_address, 100_000_000),
+                TestAccount::new(
+                    house_address,
+                    rent.minimum_balance(size),
+                    crate::id(),
+                    &data,
+                ),
+                TestAccount::wallet(system_program::id(), 0),
+            ];
+            let instruction =
+                instruction::migrate_account(&funder_wallet_address, &house_address, 0);
+            process_test_instruction(&instruction, &mut accounts).unwrap();
+
+            // Fields added since the house's version start out empty.
+            assert_eq!(accounts[1].lamports, rent.minimum_balance(HouseData::SIZE));
+            let migrated = try_from_account_data::<HouseData>(accounts[1].data()).unwrap();
+            assert_eq!(migrated.version, HouseData::CURRENT_VERSION);
+            assert_eq!(migrated.held_title_count, 2);
+            assert_eq!(migrated.authority, Pubkey::new(&[0; 32]));
+            assert_eq!(migrated.proposal_count, 0);
+            assert_eq!(migrated.heir_house_address, Pubkey::new(&[0; 32]));
+            assert_eq!(
+                process_test_instruction(&instruction, &mut accounts),
+                Err(TitleError::AccountAlreadyCurrent.into())
+            );
+        }
+    }
+
+    #[test]
+    fn update_house() {
+        let wallet_address = Pubkey::new_unique();
+        let house_address = get_house_address(&wallet_address);
+        let other_wallet_address = Pubkey::new_unique();
+        let mut accounts = vec![
+            TestAccount::wallet(wallet_address, 0),
+            TestAccount::wallet(other_wallet_address, 0),
+            TestAccount::program(house_address, &test_house_data()),
+        ];
+        let coat_of_arms = "https://example.com/house.png".to_string();
+
+        // Only the wallet governing the house may update it.
+        let instruction =
+            instruction::update_house(&other_wallet_address, &house_address, coat_of_arms.clone());
+        assert_eq!(
+            process_test_instruction(&instruction, &mut accounts),
+            Err(ProgramError::InvalidSeeds)
+        );
+        let instruction = instruction::update_house(
+            &wallet_address,
+            &house_address,
+            "x".repeat(MAX_STRING_LENGTH),
+        );
+        assert_eq!(
+            process_test_instruction(&instruction, &mut accounts),
+            Err(TitleError::StringTooLong.into())
+        );
+
+        let instruction =
+            instruction::update_house(&wallet_address, &house_address, coat_of_arms.clone());
+        process_test_instruction(&instruction, &mut accounts).unwrap();
+        let house = try_from_account_data::<HouseData>(accounts[2].data()).unwrap();
+        assert_eq!(
+            house,
+            HouseData {
+                coat_of_arms,
+                ..test_house_data()
+            }
+        );
+    }
+
+    #[test]
+    fn update_title_coat_of_arms() {
+        let wallet_address = Pubkey::new_unique();
+        let house_address = get_house_address(&wallet_address);
+        let title_address = Pubkey::new_unique();
+        let title = TitleData {
+            holder_house_address: house_address,
+            ..test_title_data()
+        };
+        let other_title_address = Pubkey::new_unique();
+        let mut accounts = vec![
+            TestAccount::wallet(wallet_address, 0),
+            TestAccount::program(house_address, &test_house_data()),
+            TestAccount::program(title_address, &title),
+            TestAccount::program(other_title_address, &test_title_data()),
+        ];
+        let coat_of_arms = "ipfs://arms".to_string();
+
+        // Only the holder of the title may change its heraldry.
+        let instruction = instruction::update_title_coat_of_arms(
+            &wallet_address,
+            &house_address,
+            &other_title_address,
+            coat_of_arms.clone(),
+        );
+        assert_eq!(
+            process_test_instruction(&instruction, &mut accounts),
+            Err(TitleError::IncorrectAuthority.into())
+        );
+
+        // Nothing but the coat of arms changes.
+        let instruction = instruction::update_title_coat_of_arms(
+            &wallet_address,
+            &house_address,
+            &title_address,
+            coat_of_arms.clone(),
+        );
+        process_test_instruction(&instruction, &mut accounts).unwrap();
+        let updated = try_from_account_data::<TitleData>(accounts[2].data()).unwrap();
+        assert_eq!(
+            updated,
+            TitleData {
+                coat_of_arms: pack_string(&coat_of_arms).unwrap(),
+                ..title
+            }
+        );
+    }
+
+    #[test]
+    fn title_errors() {
+        let wallet_address = Pubkey::new_unique();
+        let house_address = get_house_address(&wallet_address);
+        let realm_address = get_realm_address("Europa");
+        let mut accounts = vec![
+            TestAccount::wallet(wallet_address, 100_000_000),
+            TestAccount::program(house_address, &test_house_data()),
+            TestAccount::wallet(Pubkey::new_unique(), 0),
+            TestAccount::wallet(Pubkey::new(&[0; 32]), 0),
+            TestAccount::wallet(system_program::id(), 0),
+            TestAccount::program(
+                get_realm_config_address(),
+                &test_realm_config_data(Pubkey::new_unique(), RealmSettings::default()),
+            ),
+            TestAccount::program(realm_address, &test_realm_data("Europa", wallet_address)),
+            TestAccount::program(
+                get_holdings_address(&house_address),
+                &test_holdings_data(house_address, vec![]),
+            ),
+        ];
+        let mut instruction = instruction::create_title(
+            &wallet_address,
+            &house_address,
+            &accounts[2].key,
+            &realm_address,
+            VassalSlot {
+                liege_address: Pubkey::new(&[0; 32]),
+                liege_vassal_index: 0,
+            },
+            TitleParams {
+                rank: Rank::Duke,
+                kind: Kind::Noble,
+                required_stake_lamports: 1_000_000,
+                coat_of_arms: String::new(),
+                display_name: "Duchy of Normandy".to_string(),
+            },
+        );
+
+        // Rule violations fail with their own error, which clients decode.
+        let err = process_test_instruction(&instruction, &mut accounts).unwrap_err();
+        assert_eq!(err, TitleError::MissingLiege.into());
+        let code = match err {
+            ProgramError::Custom(code) => code,
+            _ => panic!("expected a custom error, got {:?}", err),
+        };
+        assert_eq!(
+            <TitleError as DecodeError<TitleError>>::decode_custom_error_to_enum(code),
+            Some(TitleError::MissingLiege)
+        );
+
+        // Unknown ranks and kinds fail to unpack with their TitleError, other malformed
+        // instructions with InvalidInstructionData.
+        let data = instruction.data.clone();
+        instruction.data[1] = 0;
+        assert_eq!(
+            process_test_instruction(&instruction, &mut accounts),
+            Err(TitleError::InvalidRank.into())
+        );
+        instruction.data = data.clone();
+        instruction.data[2] = 3;
+        assert_eq!(
+            process_test_instruction(&instruction, &mut accounts),
+            Err(TitleError::InvalidKind.into())
+        );
+        instruction.data = data[..3].to_vec();
+        assert_eq!(
+            process_test_instruction(&instruction, &mut accounts),
+            Err(ProgramError::InvalidInstructionData)
+        );
+    }
+
+    #[test]
+    fn escheat_title() {
+        let rent = Rent::default();
+        let liege_wallet_address = Pubkey::new_unique();
+        let liege_house_address = get_house_address(&liege_wallet_address);
+        let holder_wallet_address = Pubkey::new_unique();
+        let holder_house_address = get_house_address(&holder_wallet_address);
+        let liege_address = Pubkey::new_unique();
+        let liege = TitleData {
+            holder_house_address: liege_house_address,
+            ..test_title_data()
+        };
+        let inactive_title_address = Pubkey::new_unique();
+        let inactive_title = TitleData {
+            lifecycle_state: TitleData::INACTIVE_STATE,
+            last_tax_paid_slot: TEST_SLOT - 999,
+            holder_house_address,
+            stake_address: Pubkey::new(&[0; 32]),
+            liege_address,
+            ..test_title_data()
+        };
+        let active_title_address = Pubkey::new_unique();
+        let active_title = TitleData {
+            last_tax_paid_slot: TEST_SLOT,
+            holder_house_address,
+            stake_address: get_stake_address(&active_title_address),
+            liege_address,
+            ..test_title_data()
+        };
+        let settings = RealmSettings {
+            escheat_grace_period_slots: 1000,
+            ..RealmSettings::default()
+        };
+        let vault_rent = rent.minimum_balance(STAKE_VAULT_SIZE);
+        let mut accounts = vec![
+            TestAccount::wallet(liege_wallet_address, 100_000_000),
+            TestAccount::program(liege_house_address, &test_house_data()),
+            TestAccount::program(liege_address, &liege),
+            TestAccount::program(inactive_title_address, &inactive_title),
+            TestAccount::program(
+                get_realm_config_address(),
+                &test_realm_config_data(Pubkey::new_unique(), settings),
+            ),
+            TestAccount::program(active_title_address, &active_title),
+            TestAccount::new(
+                active_title.stake_address,
+                vault_rent + active_title.required_stake_lamports,
+                crate::id(),
+                &[AccountType::StakeVault as u8],
+            ),
+            TestAccount::wallet(holder_wallet_address, 0),
+            TestAccount::program(
+                holder_house_address,
+                &HouseData {
+                    held_title_count: 2,
+                    ..test_house_data()
+                },
+            ),
+            TestAccount::program(
+                get_holdings_address(&holder_house_address),
+                &test_holdings_data(
+                    holder_house_address,
+                    vec![inactive_title_address, active_title_address],
+                ),
+            ),
+            TestAccount::program(
+                get_holdings_address(&liege_house_address),
+                &test_holdings_data(liege_house_address, vec![]),
+            ),
+            TestAccount::wallet(system_program::id(), 0),
+        ];
+
+        // An inactive title may be escheated once its grace period is over.
+        let instruction = instruction::escheat_title(
+            &liege_wallet_address,
+            &liege_house_address,
+            &liege_address,
+            &inactive_title_address,
+            &holder_house_address,
+            None,
+        );
+        assert_eq!(
+            process_test_instruction(&instruction, &mut accounts),
+            Err(TitleError::NotDelinquent.into())
+        );
+        accounts[3] = TestAccount::program(
+            inactive_title_address,
+            &TitleData {
+                last_tax_paid_slot: TEST_SLOT - 1000,
+                ..inactive_title
+            },
+        );
+        process_test_instruction(&instruction, &mut accounts).unwrap();
+        let escheated = TitleData::unpack_from_slice(accounts[3].data()).unwrap();
+        assert_eq!(escheated.holder_house_address, liege_house_address);
+        assert_eq!(escheated.last_tax_paid_slot, TEST_SLOT);
+
+        // An active title may be escheated once its stake falls below the required
+        // stake. The stake goes back to its holder.
+        let instruction = instruction::escheat_title(
+            &liege_wallet_address,
+            &liege_house_address,
+            &liege_address,
+            &active_title_address,
+            &holder_house_address,
+            Some((&active_title.stake_address, &holder_wallet_address)),
+        );
+        assert_eq!(
+            process_test_instruction(&instruction, &mut accounts),
+            Err(TitleError::NotDelinquent.into())
+        );
+        accounts[6].lamports -= 1;
+        let stake_lamports = accounts[6].lamports;
+        process_test_instruction(&instruction, &mut accounts).unwrap();
+        assert_eq!(accounts[6].lamports, 0);
+        assert_eq!(accounts[7].lamports, stake_lamports);
+        let escheated = TitleData::unpack_from_slice(accounts[5].data()).unwrap();
+        assert_eq!(escheated.holder_house_address, liege_house_address);
+        assert_eq!(escheated.lifecycle_state, TitleData::INACTIVE_STATE);
+        assert_eq!(escheated.stake_address, Pubkey::new(&[0; 32]));
+
+        // The titles count towards the liege's house instead.
+        let house = try_from_account_data::<HouseData>(accounts[8].data()).unwrap();
+        assert_eq!(house.held_title_count, 0);
+        let house = try_from_account_data::<HouseData>(accounts[1].data()).unwrap();
+        assert_eq!(house.held_title_count, 2);
+        let holdings = try_from_account_data::<HoldingsData>(accounts[10].data()).unwrap();
+        assert_eq!(
+            holdings.title_addresses,
+            vec![inactive_title_address, active_title_address]
+        );
+    }
+
+    #[test]
+    fn foreclose_to_treasury() {
+        let rent = Rent::default();
+        let holder_house_address = Pubkey::new_unique();
+        let liege_address = Pubkey::new_unique();
+        let title_address = Pubkey::new_unique();
+        let realm_address = Pubkey::new_unique();
+        let treasury_address = get_treasury_address(&realm_address);
+        let liege = TitleData {
+            holder_house_address: treasury_address,
+            lifecycle_state: TitleData::INACTIVE_STATE,
+            realm_address,
+            ..test_title_data()
+        };
+        let title = TitleData {
+            holder_house_address,
+            stake_address: get_stake_address(&title_address),
+            liege_address,
+            realm_address,
+            ..test_title_data()
+        };
+        // The vault holds nothing above its rent, so it can't cover the tax owed.
+        let vault_rent = rent.minimum_balance(STAKE_VAULT_SIZE);
+        let mut accounts = vec![
+            TestAccount::program(title_address, &title),
+            TestAccount::new(
+                title.stake_address,
+                vault_rent,
+                crate::id(),
+                &[AccountType::StakeVault as u8],
+            ),
+            TestAccount::wallet(treasury_address, 0),
+            TestAccount::program(
+                get_realm_config_address(),
+                &test_realm_config_data(Pubkey::new_unique(), RealmSettings::default()),
+            ),
+            TestAccount::program(liege_address, &liege),
+            TestAccount::program(
+                holder_house_address,
+                &HouseData {
+                    held_title_count: 1,
+                    ..test_house_data()
+                },
+            ),
+            TestAccount::program(
+                get_holdings_address(&holder_house_address),
+                &test_holdings_data(holder_house_address, vec![title_address]),
+            ),
+            TestAccount::wallet(get_holdings_address(&treasury_address), 0),
+            TestAccount::wallet(system_program::id(), 0),
+        ];
+        let instruction = instruction::collect_tax(
+            &title_address,
+            &title.stake_address,
+            &treasury_address,
+            Some((&liege_address, &holder_house_address)),
+        );
+        process_test_instruction(&instruction, &mut accounts).unwrap();
+
+        // The treasury takes the vault and the title, and keeps no holdings.
+        assert_eq!(accounts[1].lamports, 0);
+        assert_eq!(accounts[2].lamports, vault_rent);
+        let foreclosed = TitleData::unpack_from_slice(accounts[0].data()).unwrap();
+        assert_eq!(foreclosed.holder_house_address, treasury_address);
+        assert_eq!(foreclosed.lifecycle_state, TitleData::INACTIVE_STATE);
+        assert_eq!(accounts[7].data().len(), 0);
+        let house = try_from_account_data::<HouseData>(accounts[5].data()).unwrap();
+        assert_eq!(house.held_title_count, 0);
+        let holdings = try_from_account_data::<HoldingsData>(accounts[6].data()).unwrap();
+        assert!(holdings.title_addresses.is_empty());
+    }
+
+    #[test]
+    fn reparent_frees_old_liege_vassal() {
+        let holder_wallet_address = Pubkey::new_unique();
+        let holder_house_address = get_house_address(&holder_wallet_address);
+        let new_liege_holder_wallet_address = Pubkey::new_unique();
+        let new_liege_holder_house_address = get_house_address(&new_liege_holder_wallet_address);
+        let liege_address = Pubkey::new_unique();
+        let new_liege_address = Pubkey::new_unique();
+        let title_address = Pubkey::new_unique();
+        let other_vassal_address = Pubkey::new_unique();
+        let liege = TitleData {
+            vassal_count: 2,
+            live_vassal_count: 2,
+            ..test_title_data()
+        };
+        let new_liege = TitleData {
+            holder_house_address: new_liege_holder_house_address,
+            realm_address: liege.realm_address,
+            vassal_count: 1,
+            live_vassal_count: 1,
+            ..test_title_data()
+        };
+        let title = TitleData {
+            rank: Rank::King,
+            holder_house_address,
+            liege_address,
+            liege_vassal_index: 1,
+            last_tax_paid_slot: TEST_SLOT,
+            realm_address: liege.realm_address,
+            seed_liege_address: liege_address,
+            seed_liege_vassal_index: 1,
+            ..test_title_data()
+        };
+        let mut accounts = vec![
+            TestAccount::wallet(holder_wallet_address, 0),
+            TestAccount::program(holder_house_address, &test_house_data()),
+            TestAccount::program(title_address, &title),
+            TestAccount::program(
+                get_vassal_page_address(&liege_address, 0),
+                &test_vassal_page_data(liege_address, vec![other_vassal_address, title_address]),
+            ),
+            TestAccount::wallet(new_liege_holder_wallet_address, 0),
+            TestAccount::program(new_liege_holder_house_address, &test_house_data()),
+            TestAccount::program(new_liege_address, &new_liege),
+            TestAccount::program(
+                get_vassal_page_address(&new_liege_address, 0),
+                &test_vassal_page_data(new_liege_address, vec![other_vassal_address]),
+            ),
+            TestAccount::wallet(system_program::id(), 0),
+            TestAccount::program(
+                get_realm_config_address(),
+                &test_realm_config_data(Pubkey::new_unique(), RealmSettings::default()),
+            ),
+            TestAccount::program(liege_address, &liege),
+        ];
+        let instruction = instruction::reparent_title(
+            &holder_wallet_address,
+            &holder_house_address,
+            &title_address,
+            VassalSlot {
+                liege_address,
+                liege_vassal_index: 1,
+            },
+            &new_liege_holder_wallet_address,
+            &new_liege_holder_house_address,
+            VassalSlot {
+                liege_address: new_liege_address,
+                liege_vassal_index: 1,
+            },
+        );
+        process_test_instruction(&instruction, &mut accounts).unwrap();
+
+        // The old liege keeps the title's index, but no longer counts it as a vassal.
+        let liege = TitleData::unpack_from_slice(accounts[10].data()).unwrap();
+        assert_eq!(liege.vassal_count, 2);
+        assert_eq!(liege.live_vassal_count, 1);
+        let page = try_from_account_data::<VassalPageData>(accounts[3].data()).unwrap();
+        assert_eq!(
+            page.vassal_addresses,
+            vec![other_vassal_address, Pubkey::new(&[0; 32])]
+        );
+        let new_liege = TitleData::unpack_from_slice(accounts[6].data()).unwrap();
+        assert_eq!(new_liege.vassal_count, 2);
+        assert_eq!(new_liege.live_vassal_count, 2);
+        let page = try_from_account_data::<VassalPageData>(accounts[7].data()).unwrap();
+        assert_eq!(
+            page.vassal_addresses,
+            vec![other_vassal_address, title_address]
+        );
+
+        // The title keeps the seeds of its address.
+        let title = TitleData::unpack_from_slice(accounts[2].data()).unwrap();
+        assert_eq!(title.liege_address, new_liege_address);
+        assert_eq!(title.liege_vassal_index, 1);
+        assert_eq!(title.seed_liege_address, liege_address);
+        assert_eq!(title.seed_liege_vassal_index, 1);
+    }
+
+    /// Realm settings for election tests, whose periods fit before TEST_SLOT
+    fn election_settings() -> RealmSettings {
+        RealmSettings {
+            election_period_slots: 1000,
+            vacancy_period_slots: 1000,
+            ..RealmSettings::default()
+        }
+    }
+
+    /// Elective title with two vassals, held by the given house
+    fn elective_title_data(title_address: &Pubkey, holder_house_address: Pubkey) -> TitleData {
+        TitleData {
+            holder_house_address,
+            stake_address: get_stake_address(title_address),
+            vassal_count: 2,
+            live_vassal_count: 2,
+            succession_law: SuccessionLaw::Elective,
+            ..test_title_data()
+        }
+    }
+
+    #[test]
+    fn conclude_election_without_ballots_or_heir() {
+        let rent = Rent::default();
+        let caller_wallet_address = Pubkey::new_unique();
+        let holder_house_address = Pubkey::new_unique();
+        let title_address = Pubkey::new_unique();
+        let title = elective_title_data(&title_address, holder_house_address);
+        let treasury_address = get_treasury_address(&title.realm_address);
+        let vault_rent = rent.minimum_balance(STAKE_VAULT_SIZE);
+        let mut election = ElectionData {
+            account_type: AccountType::Election,
+            version: ElectionData::CURRENT_VERSION,
+            title_address,
+            holder_house_address,
+            opened_slot: TEST_SLOT - 999,
+            ballots: vec![],
+        };
+        let mut accounts = vec![
+            TestAccount::wallet(caller_wallet_address, 0),
+            TestAccount::program(title_address, &title),
+            TestAccount::program(get_election_address(&title_address), &election),
+            TestAccount::program(
+                holder_house_address,
+                &HouseData {
+                    held_title_count: 1,
+                    ..test_house_data()
+                },
+            ),
+            TestAccount::program(
+                get_holdings_address(&holder_house_address),
+                &test_holdings_data(holder_house_address, vec![title_address]),
+            ),
+            TestAccount::wallet(treasury_address, 0),
+            TestAccount::wallet(get_holdings_address(&treasury_address), 0),
+            TestAccount::program(
+                get_realm_config_address(),
+                &test_realm_config_data(Pubkey::new_unique(), election_settings()),
+            ),
+            TestAccount::wallet(system_program::id(), 0),
+            TestAccount::new(
+                title.stake_address,
+                vault_rent + title.required_stake_lamports,
+                crate::id(),
+                &[AccountType::StakeVault as u8],
+            ),
+        ];
+        let instruction = instruction::conclude_election(
+            &caller_wallet_address,
+            &title_address,
+            &holder_house_address,
+            &treasury_address,
+        );
+        assert_eq!(
+            process_test_instruction(&instruction, &mut accounts),
+            Err(TitleError::ElectionUndecided.into())
+        );
+
+        // Once the election period is over, the treasury holds the title, listed for sale
+        // at its required stake.
+        election.opened_slot = TEST_SLOT - 1000;
+        accounts[2] = TestAccount::program(get_election_address(&title_address), &election);
+        let election_rent = accounts[2].lamports;
+        process_test_instruction(&instruction, &mut accounts).unwrap();
+        assert_eq!(accounts[0].lamports, election_rent);
+        assert_eq!(accounts[2].lamports, 0);
+        assert_eq!(
+            accounts[5].lamports,
+            vault_rent + title.required_stake_lamports
+        );
+        assert_eq!(accounts[9].lamports, 0);
+        let vacated = TitleData::unpack_from_slice(accounts[1].data()).unwrap();
+        assert_eq!(vacated.holder_house_address, treasury_address);
+        assert_eq!(vacated.lifecycle_state, TitleData::INACTIVE_STATE);
+        assert_eq!(vacated.sale_price_lamports, title.required_stake_lamports);
+        assert_eq!(vacated.last_tax_paid_slot, TEST_SLOT);
+        let house = try_from_account_data::<HouseData>(accounts[3].data()).unwrap();
+        assert_eq!(house.held_title_count, 0);
+        let holdings = try_from_account_data::<HoldingsData>(accounts[4].data()).unwrap();
+        assert!(holdings.title_addresses.is_empty());
+
+        // The sale price of the title goes to the treasury.
+        let buyer_wallet_address = Pubkey::new_unique();
+        let buyer_house_address = get_house_address(&buyer_wallet_address);
+        accounts.push(TestAccount::wallet(buyer_wallet_address, 100_000_000));
+        accounts.push(TestAccount::program(
+            buyer_house_address,
+            &test_house_data(),
+        ));
+        accounts.push(TestAccount::program(
+            get_holdings_address(&buyer_house_address),
+            &test_holdings_data(buyer_house_address, vec![]),
+        ));
+        let instruction = instruction::buy_title(
+            &buyer_wallet_address,
+            &buyer_house_address,
+            &title_address,
+            &treasury_address,
+            &treasury_address,
+            &title.realm_address,
+            title.required_stake_lamports,
+        );
+        process_test_instruction(&instruction, &mut accounts).unwrap();
+        assert_eq!(
+            accounts[5].lamports,
+            vault_rent + 2 * title.required_stake_lamports
+        );
+        let bought = TitleData::unpack_from_slice(accounts[1].data()).unwrap();
+        assert_eq!(bought.holder_house_address, buyer_house_address);
+        let holdings = try_from_account_data::<HoldingsData>(accounts[12].data()).unwrap();
+        assert_eq!(holdings.title_addresses, vec![title_address]);
+    }
+
+    #[test]
+    fn conclude_election_without_ballots_to_heir() {
+        let caller_wallet_address = Pubkey::new_unique();
+        let holder_house_address = Pubkey::new_unique();
+        let heir_house_address = Pubkey::new_unique();
+        let title_address = Pubkey::new_unique();
+        let title = elective_title_data(&title_address, holder_house_address);
+        let election = ElectionData {
+            account_type: AccountType::Election,
+            version: ElectionData::CURRENT_VERSION,
+            title_address,
+            holder_house_address,
+            opened_slot: TEST_SLOT - 1000,
+            ballots: vec![],
+        };
+        let mut accounts = vec![
+            TestAccount::wallet(caller_wallet_address, 100_000_000),
+            TestAccount::program(title_address, &title),
+            TestAccount::program(get_election_address(&title_address), &election),
+            TestAccount::program(
+                holder_house_address,
+                &HouseData {
+                    held_title_count: 1,
+                    heir_house_address,
+                    succession_period_slots: 1_000_000,
+                    ..test_house_data()
+                },
+            ),
+            TestAccount::program(
+                get_holdings_address(&holder_house_address),
+                &test_holdings_data(holder_house_address, vec![title_address]),
+            ),
+            TestAccount::program(heir_house_address, &test_house_data()),
+            TestAccount::program(
+                get_holdings_address(&heir_house_address),
+                &test_holdings_data(heir_house_address, vec![]),
+            ),
+            TestAccount::program(
+                get_realm_config_address(),
+                &test_realm_config_data(Pubkey::new_unique(), election_settings()),
+            ),
+            TestAccount::wallet(system_program::id(), 0),
+            TestAccount::wallet(title.stake_address, 0),
+        ];
+        let instruction = instruction::conclude_election(
+            &caller_wallet_address,
+            &title_address,
+            &holder_house_address,
+            &heir_house_address,
+        );
+        process_test_instruction(&instruction, &mut accounts).unwrap();
+
+        // The title passes as it is, active with its stake.
+        let inherited = TitleData::unpack_from_slice(accounts[1].data()).unwrap();
+        assert_eq!(
+            inherited,
+            TitleData {
+                holder_house_address: heir_house_address,
+                ..title
+            }
+        );
+        assert_eq!(accounts[2].lamports, 0);
+        let holdings = try_from_account_data::<HoldingsData>(accounts[6].data()).unwrap();
+        assert_eq!(holdings.title_addresses, vec![title_address]);
+    }
+
+    #[test]
+    fn trigger_succession() {
+        let funder_wallet_address = Pubkey::new_unique();
+        let house_address = Pubkey::new_unique();
+        let heir_house_address = Pubkey::new_unique();
+        let title_address = Pubkey::new_unique();
+        let unlisted_title_address = Pubkey::new_unique();
+        let title = TitleData {
+            holder_house_address: house_address,
+            ..test_title_data()
+        };
+        let mut accounts = vec![
+            TestAccount::wallet(funder_wallet_address, 100_000_000),
+            TestAccount::program(
+                house_address,
+                &HouseData {
+                    held_title_count: 2,
+                    heir_house_address,
+                    succession_period_slots: 1000,
+                    last_proof_of_life_slot: TEST_SLOT - 1000,
+                    ..test_house_data()
+                },
+            ),
+            TestAccount::program(
+                get_holdings_address(&house_address),
+                &test_holdings_data(house_address, vec![title_address]),
+            ),
+            TestAccount::program(heir_house_address, &test_house_data()),
+            TestAccount::program(
+                get_holdings_address(&heir_house_address),
+                &test_holdings_data(heir_house_address, vec![]),
+            ),
+            TestAccount::wallet(system_program::id(), 0),
+            TestAccount::program(title_address, &title),
+            TestAccount::program(unlisted_title_address, &title),
+        ];
+
+        // Titles must be listed once, and only those in the house's holdings pass.
+        let instruction = instruction::trigger_succession(
+            &funder_wallet_address,
+            &house_address,
+            &heir_house_address,
+            &[title_address, title_address],
+        );
+        assert_eq!(
+            process_test_instruction(&instruction, &mut accounts),
+            Err(TitleError::DuplicateTitle.into())
+        );
+        // The runtime would roll back the failed instruction's changes.
+        accounts[6] = TestAccount::program(title_address, &title);
+        let instruction = instruction::trigger_succession(
+            &funder_wallet_address,
+            &house_address,
+            &heir_house_address,
+            &[title_address, unlisted_title_address],
+        );
+        assert_eq!(
+            process_test_instruction(&instruction, &mut accounts),
+            Err(TitleError::TitleNotInHoldings.into())
+        );
+        accounts[6] = TestAccount::program(title_address, &title);
+
+        let instruction = instruction::trigger_succession(
+            &funder_wallet_address,
+            &house_address,
+            &heir_house_address,
+            &[title_address],
+        );
+        process_test_instruction(&instruction, &mut accounts).unwrap();
+        let inherited = TitleData::unpack_from_slice(accounts[6].data()).unwrap();
+        assert_eq!(inherited.holder_house_address, heir_house_address);
+        let house = try_from_account_data::<HouseData>(accounts[1].data()).unwrap();
+        assert_eq!(house.held_title_count, 1);
+        let holdings = try_from_account_data::<HoldingsData>(accounts[2].data()).unwrap();
+        assert!(holdings.title_addresses.is_empty());
+        let holdings = try_from_account_data::<HoldingsData>(accounts[4].data()).unwrap();
+        assert_eq!(holdings.title_addresses, vec![title_address]);
+    }
+
+    #[test]
+    fn execute_proposal_checks() {
+        let house_address = Pubkey::new_unique();
+        let house = HouseData {
+            governance_token_supply: 3,
+            authority: get_governance_address(&house_address),
+            ..test_house_data()
+        };
+        let proposal_address = Pubkey::new_unique();
+        let proposal = ProposalData {
+            account_type: AccountType::Proposal,
+            version: ProposalData::CURRENT_VERSION,
+            house_address,
+            proposal_index: 0,
+            approving_tokens: 1,
+            executed: false,
+            voting_deadline_slot: TEST_SLOT,
+            accounts: vec![],
+            data: TitleInstruction::ProveLife.try_to_vec().unwrap(),
+        };
+        let mut accounts = vec![
+            TestAccount::program(proposal_address, &proposal),
+            TestAccount::program(house_address, &house),
+            TestAccount::wallet(crate::id(), 0),
+        ];
+        let instruction = instruction::execute_proposal(&proposal_address, &proposal);
+
+        // Proposals can't be executed once their voting ends.
+        assert_eq!(
+            process_test_instruction(&instruction, &mut accounts),
+            Err(TitleError::VotingEnded.into())
+        );
+        let proposal = ProposalData {
+            voting_deadline_slot: TEST_SLOT + 1,
+            ..proposal
+        };
+        accounts[0] = TestAccount::program(proposal_address, &proposal);
+
+        // Nor once the house is no longer governed by its governance wallet.
+        accounts[1] = TestAccount::program(
+            house_address,
+            &HouseData {
+                authority: Pubkey::new_unique(),
+                ..house.clone()
+            },
+        );
+        assert_eq!(
+            process_test_instruction(&instruction, &mut accounts),
+            Err(TitleError::NotTokenGoverned.into())
+        );
+        accounts[1] = TestAccount::program(
+            house_address,
+            &HouseData {
+                governance_token_supply: 1,
+                ..house.clone()
+            },
+        );
+        assert_eq!(
+            process_test_instruction(&instruction, &mut accounts),
+            Err(TitleError::NotTokenGoverned.into())
+        );
+
+        accounts[1] = TestAccount::program(house_address, &house);
+        assert_eq!(
+            process_test_instruction(&instruction, &mut accounts),
+            Err(TitleError::ProposalNotApproved.into())
+        );
     }
 }
@@ -1,18 +1,77 @@
 //! Program state
 use {
+    crate::{error::TitleError, utils::try_from_slice_checked},
     borsh::{BorshDeserialize, BorshSchema, BorshSerialize},
-    solana_program::{program_pack::IsInitialized, pubkey::Pubkey},
+    solana_program::{
+        msg,
+        program_error::ProgramError,
+        program_pack::{IsInitialized, Pack, Sealed},
+        pubkey::Pubkey,
+    },
+    std::{convert::TryFrom, fmt, io, str::FromStr},
 };
 
+/// Space, in bytes, reserved for each coat of arms URI and display name in account
+/// data.
+pub const MAX_STRING_LENGTH: usize = 128;
+
+/// Maximum length, in bytes, of coat of arms URIs and display names. Leaves room for
+/// the 4-byte length prefix of the Borsh-encoded strings in HouseData.
+pub const MAX_STRING_BYTES: usize = MAX_STRING_LENGTH - 4;
+
+/// Maximum length, in bytes, of realm names, which are used as an address seed.
+pub const MAX_REALM_NAME_LENGTH: usize = 32;
+
+/// URI schemes accepted for coats of arms.
+pub const COAT_OF_ARMS_URI_SCHEMES: [&str; 3] = ["https://", "ipfs://", "ar://"];
+
+/// Type of an account owned by the program. Stored as the first byte of the
+/// account's data, so that one kind of account can't be read as another.
+#[derive(Clone, Copy, Debug, BorshSerialize, BorshDeserialize, BorshSchema, PartialEq)]
+pub enum AccountType {
+    /// Account data has not been initialized
+    Uninitialized,
+    /// HouseData
+    House,
+    /// TitleData
+    Title,
+    /// Stake vault of a title. Holds no data besides its account type.
+    StakeVault,
+    /// VassalPageData
+    VassalPage,
+    /// RealmConfigData
+    RealmConfig,
+    /// RealmData
+    Realm,
+    /// PetitionData
+    Petition,
+    /// HoldingsData
+    Holdings,
+    /// ProposalData
+    Proposal,
+    /// VoteData
+    Vote,
+    /// ElectionData
+    Election,
+}
+
+/// Serialized size of a stake vault's data.
+pub const STAKE_VAULT_SIZE: usize = 1;
+
 /// Struct defining a user's House - their nobility account. A House may hold
 /// 0 or more titles.
 #[derive(Clone, Debug, BorshSerialize, BorshDeserialize, BorshSchema, PartialEq)]
 pub struct HouseData {
+    /// Account type, always AccountType::House.
+    pub account_type: AccountType,
+
     /// Struct version, allows for upgrades to the program.
     pub version: u16,
 
     /// Number of tokens governing this house. Immutable. Default is 1, in which case the client wallet
-    /// has the authority to govern the house.
+    /// has the authority to govern the house. Above 1, the supply is minted at
+    /// get_governance_mint_address to the creating wallet, and the house is governed by
+    /// proposals approved by a majority of the tokens, see ProposalData.
     pub governance_token_supply: u16,
 
     /// The URI for the coat of arms. *Mutable*. Null-terminated.
@@ -26,13 +85,90 @@ pub struct HouseData {
 
     /// Total virtue accumulated by this house. *Mutable*.
     pub virtue: i32,
+
+    /// Number of titles held by this house. *Mutable*. UNKNOWN_TITLE_COUNT for houses
+    /// created before titles were counted, which can't be closed.
+    pub held_title_count: u32,
+
+    /// Wallet governing this house. *Mutable*, see TransferHouseAuthority. All zeroes
+    /// for houses created before authorities were recorded, which are governed by the
+    /// wallet they are derived from until their authority is first transferred.
+    pub authority: Pubkey,
+
+    /// Number of proposals made to the token holders of this house. *Mutable*. Also the
+    /// index of the next proposal, see get_proposal_address.
+    pub proposal_count: u32,
+
+    /// House inheriting the titles of this house once it stops proving life, see
+    /// TriggerSuccession. Elective titles are elected anew instead, see ElectionData.
+    /// *Mutable*, see SetHeir. All zeroes for no heir.
+    pub heir_house_address: Pubkey,
+
+    /// Slots the house may go without proving life before its heir may inherit its
+    /// titles. *Mutable*, see SetHeir.
+    pub succession_period_slots: u64,
+
+    /// Slot the house last proved life at. *Mutable*, see ProveLife and SetHeir.
+    pub last_proof_of_life_slot: u64,
 }
 
 impl HouseData {
     /// Version to fill in on new created accounts
-    pub const CURRENT_VERSION: u16 = 1;
+    pub const CURRENT_VERSION: u16 = 6;
     /// Serialized size of the struct
-    pub const SIZE: usize = 2 + 2 + 128 + 128 + 4 + 4;
+    pub const SIZE: usize = 1 + 2 + 2 + 128 + 128 + 4 + 4 + 4 + 32 + 4 + 32 + 8 + 8;
+    /// Serialized size of version 5, before heir_house_address.
+    pub const V5_SIZE: usize = Self::SIZE - 32 - 8 - 8;
+    /// Serialized size of version 4, before proposal_count.
+    pub const V4_SIZE: usize = Self::V5_SIZE - 4;
+    /// Serialized size of version 3, before authority.
+    pub const V3_SIZE: usize = Self::V4_SIZE - 32;
+    /// Serialized size of version 2, before held_title_count.
+    pub const V2_SIZE: usize = Self::V3_SIZE - 4;
+    /// held_title_count of houses migrated from before titles were counted.
+    pub const UNKNOWN_TITLE_COUNT: u32 = u32::MAX;
+
+    /// Count a title that the house came to hold.
+    pub fn add_held_title(&mut self) -> Result<(), TitleError> {
+        if self.held_title_count != Self::UNKNOWN_TITLE_COUNT {
+            self.held_title_count = self
+                .held_title_count
+                .checked_add(1)
+                .filter(|count| *count != Self::UNKNOWN_TITLE_COUNT)
+                .ok_or(TitleError::Overflow)?;
+        }
+        Ok(())
+    }
+
+    /// Whether the house is governed by proposals to the holders of its governance
+    /// tokens.
+    pub fn is_token_governed(&self) -> bool {
+        self.governance_token_supply > 1
+    }
+
+    /// Whether the house has named an heir and gone without proving life for its
+    /// succession period as of the slot.
+    pub fn is_succession_due(&self, slot: u64) -> bool {
+        self.heir_house_address != Pubkey::new(&[0; 32])
+            && slot.saturating_sub(self.last_proof_of_life_slot) >= self.succession_period_slots
+    }
+
+    /// Whether the house has gone without proving life for the realm's vacancy period as
+    /// of the slot, so that its elective titles may be elected anew.
+    pub fn has_vacated(&self, slot: u64, settings: &RealmSettings) -> bool {
+        slot.saturating_sub(self.last_proof_of_life_slot) >= settings.vacancy_period_slots
+    }
+
+    /// Count a title that the house no longer holds.
+    pub fn remove_held_title(&mut self) -> Result<(), TitleError> {
+        if self.held_title_count != Self::UNKNOWN_TITLE_COUNT {
+            self.held_title_count = self
+                .held_title_count
+                .checked_sub(1)
+                .ok_or(TitleError::Overflow)?;
+        }
+        Ok(())
+    }
 }
 
 impl IsInitialized for HouseData {
@@ -42,130 +178,2496 @@ impl IsInitialized for HouseData {
     }
 }
 
-/// Struct defining a noble Title.
-#[derive(Clone, Debug, BorshSerialize, BorshDeserialize, BorshSchema, PartialEq)]
+/// Struct defining a noble Title. Stored in a fixed layout, see the TitleData
+/// offsets, so that single fields can be updated in place through TitleDataMut and
+/// clients can filter title accounts by field with `memcmp`.
+#[derive(Clone, Debug, PartialEq)]
 pub struct TitleData {
+    /// Account type, always AccountType::Title.
+    pub account_type: AccountType,
+
     /// Struct version, allows for upgrades to the program.
     pub version: u8,
 
     /// Lifecycle state:
     /// 0: Uninitialized
-    /// 1: Created, Inactive (never sold)
-    /// 2: Active
+    /// 1: Created, Inactive (never staked)
+    /// 2: Active (stake vault funded)
     pub lifecycle_state: u8,
 
-    /// Title rank. Immutable. 1 == Deus (root), 2 == Emperor, 3 == King ...
-    pub rank: u8,
+    /// Title rank. Immutable. Rank::Deus for the root title, otherwise below the
+    /// rank of the liege title.
+    pub rank: Rank,
 
-    /// Title type. Immutable. 1 == Noble, 2 == Religious. Future types include
-    /// society groups.
-    pub kind: u8,
+    /// Title type. Immutable.
+    pub kind: Kind,
 
     /// Required stake, in lamports, to hold the title. Immutable. This is the
     /// also the price floor for the title.
     pub required_stake_lamports: u64,
 
-    /// Advertised sale price, in lamports. Immutable. Anybody with this many
-    /// lamports may buy the title from the current holder.
+    /// Advertised sale price, in lamports. *Mutable*. Anybody with this many
+    /// lamports may buy the title from the current holder. Never below
+    /// required_stake_lamports; NOT_FOR_SALE if the holder has delisted the title.
     pub sale_price_lamports: u64,
 
-    /// The URI for the coat of arms. *Mutable*. Null-terminated.
-    pub coat_of_arms: String,
-
-    /// Title name. Immutable. Null-terminated. Maximum length: 128.
-    pub display_name: String,
+    /// Slot up to which the Harberger tax on this title has been paid. *Mutable*.
+    /// Tax only accrues while the title is active. For an inactive title, the slot it
+    /// became inactive or last changed hands, from which its escheat grace period runs.
+    pub last_tax_paid_slot: u64,
 
     /// House address holding the title. *Mutable*. Never all zeroes.
     pub holder_house_address: Pubkey,
 
     /// Stake account address. Immutable. Will be all zeroes until the title is
-    /// first sold.
+    /// first activated.
     pub stake_address: Pubkey,
 
-    /// Liege title address. Immutable. All zeroes if this is the root title.
+    /// Liege title address. *Mutable*, see TitleInstruction::ReparentTitle. All
+    /// zeroes if this is the root title.
     pub liege_address: Pubkey,
 
-    /// Index of the title in the list of the liege's vassals. Immutable. Used to
-    /// form a seed for this title's address.
-    pub liege_vassal_index : u8,
+    /// Index of the title in the list of the liege's vassals. *Mutable*, see
+    /// TitleInstruction::ReparentTitle.
+    pub liege_vassal_index: u16,
 
-    /// Vassal title addresses. Mutable.
-    pub vassal_addresses: Vec<Pubkey>,
+    /// Number of entries in the list of vassals of this title, including those left
+    /// all zeroes by vassals that moved to another liege. *Mutable*. Vassal addresses
+    /// are stored in VassalPageData accounts, VassalPageData::VASSALS_PER_PAGE to a
+    /// page.
+    pub vassal_count: u16,
+
+    /// The URI for the coat of arms. *Mutable*. Null-padded.
+    pub coat_of_arms: [u8; MAX_STRING_LENGTH],
+
+    /// Title name. Immutable. Null-padded.
+    pub display_name: [u8; MAX_STRING_LENGTH],
+
+    /// Realm the title belongs to. Immutable. All zeroes for titles created before
+    /// realms, which form the legacy realm that has no RealmData account.
+    pub realm_address: Pubkey,
+
+    /// Number of vassals of this title that haven't moved to another liege.
+    /// *Mutable*.
+    pub live_vassal_count: u16,
+
+    /// Liege address used to form a seed for this title's address. Immutable. Unlike
+    /// liege_address, it doesn't change when the title moves to another liege, so a
+    /// title that moved is found through its liege's vassal pages rather than
+    /// get_title_address. All zeroes for root titles.
+    pub seed_liege_address: Pubkey,
+
+    /// Vassal index used to form a seed for this title's address. Immutable. Titles
+    /// written before version 3 derived their address from an older seed of the same
+    /// index, see TitleInstruction::MigrateAccount.
+    pub seed_liege_vassal_index: u16,
+
+    /// How the title passes on once its holder's house stops proving life. *Mutable*,
+    /// see TitleInstruction::SetSuccessionLaw.
+    pub succession_law: SuccessionLaw,
+}
+
+/// Rank of a title, from the root title down. Stored as a single byte, Deus == 1,
+/// Emperor == 2 and so on, so that vassals always have a numerically greater rank
+/// than their liege.
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+#[repr(u8)]
+pub enum Rank {
+    /// The root title of the realm
+    Deus = 1,
+    /// Emperor
+    Emperor = 2,
+    /// King
+    King = 3,
+    /// Duke
+    Duke = 4,
+    /// Count
+    Count = 5,
+    /// Baron
+    Baron = 6,
+    /// Baronet
+    Baronet = 7,
+    /// Knight
+    Knight = 8,
+}
+
+impl Rank {
+    /// All ranks, from the root down.
+    pub const ALL: [Rank; 8] = [
+        Rank::Deus,
+        Rank::Emperor,
+        Rank::King,
+        Rank::Duke,
+        Rank::Count,
+        Rank::Baron,
+        Rank::Baronet,
+        Rank::Knight,
+    ];
+
+    /// Name of the rank.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Rank::Deus => "Deus",
+            Rank::Emperor => "Emperor",
+            Rank::King => "King",
+            Rank::Duke => "Duke",
+            Rank::Count => "Count",
+            Rank::Baron => "Baron",
+            Rank::Baronet => "Baronet",
+            Rank::Knight => "Knight",
+        }
+    }
+}
+
+impl TryFrom<u8> for Rank {
+    type Error = ProgramError;
+
+    fn try_from(value: u8) -> Result<Self, Self::Error> {
+        Rank::ALL
+            .iter()
+            .find(|rank| **rank as u8 == value)
+            .copied()
+            .ok_or_else(|| {
+                msg!("Invalid rank: {}", value);
+                TitleError::InvalidRank.into()
+            })
+    }
+}
+
+/// Type of a title. Stored as a single byte, Noble == 1. Future types include
+/// society groups.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+#[repr(u8)]
+pub enum Kind {
+    /// Noble title
+    Noble = 1,
+    /// Religious title
+    Religious = 2,
+}
+
+impl Kind {
+    /// All kinds.
+    pub const ALL: [Kind; 2] = [Kind::Noble, Kind::Religious];
+
+    /// Name of the kind.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Kind::Noble => "Noble",
+            Kind::Religious => "Religious",
+        }
+    }
+}
+
+impl TryFrom<u8> for Kind {
+    type Error = ProgramError;
+
+    fn try_from(value: u8) -> Result<Self, Self::Error> {
+        Kind::ALL
+            .iter()
+            .find(|kind| **kind as u8 == value)
+            .copied()
+            .ok_or_else(|| {
+                msg!("Invalid kind: {}", value);
+                TitleError::InvalidKind.into()
+            })
+    }
+}
+
+/// Borsh encoding of Rank and Kind as their single byte value, rather than the
+/// variant index derived encodings would use. Invalid values fail with an io::Error
+/// wrapping the given TitleError, so that the processor can return it.
+macro_rules! impl_byte_enum {
+    ($name:ident, $error:ident) => {
+        impl BorshSerialize for $name {
+            fn serialize<W: io::Write>(&self, writer: &mut W) -> io::Result<()> {
+                (*self as u8).serialize(writer)
+            }
+        }
+
+        impl BorshDeserialize for $name {
+            fn deserialize(buf: &mut &[u8]) -> io::Result<Self> {
+                let value = u8::deserialize(buf)?;
+                $name::try_from(value)
+                    .map_err(|_| io::Error::new(io::ErrorKind::InvalidData, TitleError::$error))
+            }
+        }
+
+        impl fmt::Display for $name {
+            fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                f.write_str(self.name())
+            }
+        }
+
+        /// Parses a name, ignoring case, or the byte value.
+        impl FromStr for $name {
+            type Err = String;
+
+            fn from_str(s: &str) -> Result<Self, Self::Err> {
+                $name::ALL
+                    .iter()
+                    .find(|v| v.name().eq_ignore_ascii_case(s) || (**v as u8).to_string() == s)
+                    .copied()
+                    .ok_or_else(|| format!("Invalid {}: {}", stringify!($name), s))
+            }
+        }
+    };
+}
+
+/// Succession law of a title. Stored as a single byte, Heir == 0, so that titles
+/// from before succession laws pass to the heir.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+#[repr(u8)]
+pub enum SuccessionLaw {
+    /// The title passes to the heir of its holder's house
+    Heir = 0,
+    /// The holders of the title's vassals elect the next holder, see ElectionData.
+    /// Titles without vassals pass to the heir.
+    Elective = 1,
+    /// The title passes to the heir of its holder's house, listed for sale at its
+    /// required stake
+    Sale = 2,
+}
+
+impl SuccessionLaw {
+    /// All succession laws.
+    pub const ALL: [SuccessionLaw; 3] = [
+        SuccessionLaw::Heir,
+        SuccessionLaw::Elective,
+        SuccessionLaw::Sale,
+    ];
+
+    /// Name of the succession law.
+    pub fn name(&self) -> &'static str {
+        match self {
+            SuccessionLaw::Heir => "Heir",
+            SuccessionLaw::Elective => "Elective",
+            SuccessionLaw::Sale => "Sale",
+        }
+    }
 }
 
-/// Maximum number of vassals per title.
-pub const MAX_VASSALS: usize = 64;
+impl TryFrom<u8> for SuccessionLaw {
+    type Error = ProgramError;
+
+    fn try_from(value: u8) -> Result<Self, Self::Error> {
+        SuccessionLaw::ALL
+            .iter()
+            .find(|law| **law as u8 == value)
+            .copied()
+            .ok_or_else(|| {
+                msg!("Invalid succession law: {}", value);
+                TitleError::InvalidSuccessionLaw.into()
+            })
+    }
+}
 
-/// Minimum rank value
-pub const MIN_RANK: u8 = 1;
-/// Maximum rank value
-pub const MAX_RANK: u8 = 6;
-/// Minimum kind value
-pub const MIN_KIND: u8 = 1;
-/// Maximum kind value
-pub const MAX_KIND: u8 = 2;
+impl_byte_enum!(Rank, InvalidRank);
+impl_byte_enum!(Kind, InvalidKind);
+impl_byte_enum!(SuccessionLaw, InvalidSuccessionLaw);
 
 impl TitleData {
     /// Version to fill in on new created accounts.
-    pub const CURRENT_VERSION: u8 = 1;
+    pub const CURRENT_VERSION: u8 = 6;
     /// Lifecycle state that is created but not active (never sold/staked)
     pub const INACTIVE_STATE: u8 = 1;
     /// Lifecycle state that is active (stakde)
     pub const ACTIVE_STATE: u8 = 2;
+    /// Sale price of a title whose holder has delisted it.
+    pub const NOT_FOR_SALE: u64 = u64::MAX;
 
-    /// Serialized maximum size of the struct.
-    pub const SIZE: usize = 1 + 1 + 1 + 1 + 8 + 8 + 128 + 128 + 32 + 32 + 32 + 1 + 4 + (32 * MAX_VASSALS);
+    /// Offset of account_type in the account data.
+    pub const ACCOUNT_TYPE_OFFSET: usize = 0;
+    /// Offset of version in the account data.
+    pub const VERSION_OFFSET: usize = 1;
+    /// Offset of lifecycle_state in the account data.
+    pub const LIFECYCLE_STATE_OFFSET: usize = 2;
+    /// Offset of rank in the account data.
+    pub const RANK_OFFSET: usize = 3;
+    /// Offset of kind in the account data.
+    pub const KIND_OFFSET: usize = 4;
+    /// Offset of required_stake_lamports in the account data.
+    pub const REQUIRED_STAKE_LAMPORTS_OFFSET: usize = 5;
+    /// Offset of sale_price_lamports in the account data.
+    pub const SALE_PRICE_LAMPORTS_OFFSET: usize = 13;
+    /// Offset of last_tax_paid_slot in the account data.
+    pub const LAST_TAX_PAID_SLOT_OFFSET: usize = 21;
+    /// Offset of holder_house_address in the account data.
+    pub const HOLDER_HOUSE_ADDRESS_OFFSET: usize = 29;
+    /// Offset of stake_address in the account data.
+    pub const STAKE_ADDRESS_OFFSET: usize = 61;
+    /// Offset of liege_address in the account data.
+    pub const LIEGE_ADDRESS_OFFSET: usize = 93;
+    /// Offset of liege_vassal_index in the account data.
+    pub const LIEGE_VASSAL_INDEX_OFFSET: usize = 125;
+    /// Offset of vassal_count in the account data.
+    pub const VASSAL_COUNT_OFFSET: usize = 127;
+    /// Offset of coat_of_arms in the account data.
+    pub const COAT_OF_ARMS_OFFSET: usize = 129;
+    /// Offset of display_name in the account data.
+    pub const DISPLAY_NAME_OFFSET: usize = 257;
+    /// Offset of realm_address in the account data.
+    pub const REALM_ADDRESS_OFFSET: usize = 385;
+    /// Offset of live_vassal_count in the account data.
+    pub const LIVE_VASSAL_COUNT_OFFSET: usize = 417;
+    /// Offset of seed_liege_address in the account data.
+    pub const SEED_LIEGE_ADDRESS_OFFSET: usize = 419;
+    /// Offset of seed_liege_vassal_index in the account data.
+    pub const SEED_LIEGE_VASSAL_INDEX_OFFSET: usize = 451;
+    /// Offset of succession_law in the account data.
+    pub const SUCCESSION_LAW_OFFSET: usize = 453;
+    /// Size of the account data.
+    pub const SIZE: usize = 454;
+    /// Size of the account data at version 5, which ended before succession_law.
+    pub const V5_SIZE: usize = 453;
+    /// Size of the account data at version 4, which ended before live_vassal_count and
+    /// the seeds.
+    pub const V4_SIZE: usize = 417;
+    /// Size of the account data at version 3, which ended before realm_address.
+    pub const V3_SIZE: usize = 385;
+
+    /// Value the title is taxed on: its sale price, or a multiple of its required stake
+    /// if it isn't for sale.
+    pub fn assessed_value_lamports(&self, settings: &RealmSettings) -> u64 {
+        if self.sale_price_lamports == Self::NOT_FOR_SALE {
+            self.required_stake_lamports
+                .saturating_mul(settings.not_for_sale_assessment_multiplier)
+        } else {
+            self.sale_price_lamports
+        }
+    }
+
+    /// Harberger tax accrued since last_tax_paid_slot, up to the given slot. Inactive
+    /// titles accrue no tax.
+    pub fn tax_owed_lamports(&self, slot: u64, settings: &RealmSettings) -> Option<u64> {
+        if self.lifecycle_state != Self::ACTIVE_STATE {
+            return Some(0);
+        }
+        let elapsed_slots = slot.saturating_sub(self.last_tax_paid_slot) as u128;
+        let tax = (self.assessed_value_lamports(settings) as u128)
+            .checked_mul(settings.tax_rate_bps as u128)?
+            .checked_mul(elapsed_slots)?
+            / (10_000 * settings.tax_period_slots as u128);
+        u64::try_from(tax).ok()
+    }
+
+    /// Whether an active title with the given stake, after its tax has been collected,
+    /// is delinquent: its stake is below the required stake.
+    pub fn is_stake_delinquent(&self, stake_lamports: u64) -> bool {
+        stake_lamports < self.required_stake_lamports
+    }
 }
 
 impl IsInitialized for TitleData {
     /// Is initialized
     fn is_initialized(&self) -> bool {
         self.version == Self::CURRENT_VERSION
-            && self.rank != 0
-            && self.kind != 0
             && self.required_stake_lamports > 0
             && self.sale_price_lamports > 0
     }
 }
 
+impl Sealed for TitleData {}
 
-#[cfg(test)]
-pub mod tests {
-    use super::*;
-    use solana_program::program_error::ProgramError;
+impl Pack for TitleData {
+    const LEN: usize = Self::SIZE;
 
-    /// Version for tests
-    pub const TEST_VERSION: u16 = 1;
-    /// Pubkey for tests
-    // pub const TEST_PUBKEY: Pubkey = Pubkey::new_from_array([100; 32]);
+    fn pack_into_slice(&self, dst: &mut [u8]) {
+        dst[Self::ACCOUNT_TYPE_OFFSET] = self.account_type as u8;
+        dst[Self::VERSION_OFFSET] = self.version;
+        dst[Self::LIFECYCLE_STATE_OFFSET] = self.lifecycle_state;
+        dst[Self::RANK_OFFSET] = self.rank as u8;
+        dst[Self::KIND_OFFSET] = self.kind as u8;
+        write_bytes(
+            dst,
+            Self::REQUIRED_STAKE_LAMPORTS_OFFSET,
+            &self.required_stake_lamports.to_le_bytes(),
+        );
+        write_bytes(
+            dst,
+            Self::SALE_PRICE_LAMPORTS_OFFSET,
+            &self.sale_price_lamports.to_le_bytes(),
+        );
+        write_bytes(
+            dst,
+            Self::LAST_TAX_PAID_SLOT_OFFSET,
+            &self.last_tax_paid_slot.to_le_bytes(),
+        );
+        write_bytes(
+            dst,
+            Self::HOLDER_HOUSE_ADDRESS_OFFSET,
+            self.holder_house_address.as_ref(),
+        );
+        write_bytes(dst, Self::STAKE_ADDRESS_OFFSET, self.stake_address.as_ref());
+        write_bytes(dst, Self::LIEGE_ADDRESS_OFFSET, self.liege_address.as_ref());
+        write_bytes(
+            dst,
+            Self::LIEGE_VASSAL_INDEX_OFFSET,
+            &self.liege_vassal_index.to_le_bytes(),
+        );
+        write_bytes(
+            dst,
+            Self::VASSAL_COUNT_OFFSET,
+            &self.vassal_count.to_le_bytes(),
+        );
+        write_bytes(dst, Self::COAT_OF_ARMS_OFFSET, &self.coat_of_arms);
+        write_bytes(dst, Self::DISPLAY_NAME_OFFSET, &self.display_name);
+        write_bytes(dst, Self::REALM_ADDRESS_OFFSET, self.realm_address.as_ref());
+        write_bytes(
+            dst,
+            Self::LIVE_VASSAL_COUNT_OFFSET,
+            &self.live_vassal_count.to_le_bytes(),
+        );
+        write_bytes(
+            dst,
+            Self::SEED_LIEGE_ADDRESS_OFFSET,
+            self.seed_liege_address.as_ref(),
+        );
+        write_bytes(
+            dst,
+            Self::SEED_LIEGE_VASSAL_INDEX_OFFSET,
+            &self.seed_liege_vassal_index.to_le_bytes(),
+        );
+        dst[Self::SUCCESSION_LAW_OFFSET] = self.succession_law as u8;
+    }
 
-    pub fn test_bytes() -> Vec<u8> {
-        return vec![42; 8];
+    fn unpack_from_slice(src: &[u8]) -> Result<Self, ProgramError> {
+        if src.len() < Self::SIZE {
+            return Err(TitleError::DataTypeMismatch.into());
+        }
+        Ok(TitleData {
+            account_type: AccountType::try_from_slice(
+                &src[Self::ACCOUNT_TYPE_OFFSET..Self::ACCOUNT_TYPE_OFFSET + 1],
+            )?,
+            version: src[Self::VERSION_OFFSET],
+            lifecycle_state: src[Self::LIFECYCLE_STATE_OFFSET],
+            rank: Rank::try_from(src[Self::RANK_OFFSET])
+                .map_err(|_| ProgramError::InvalidAccountData)?,
+            kind: Kind::try_from(src[Self::KIND_OFFSET])
+                .map_err(|_| ProgramError::InvalidAccountData)?,
+            required_stake_lamports: u64::from_le_bytes(read_array(
+                src,
+                Self::REQUIRED_STAKE_LAMPORTS_OFFSET,
+            )),
+            sale_price_lamports: u64::from_le_bytes(read_array(
+                src,
+                Self::SALE_PRICE_LAMPORTS_OFFSET,
+            )),
+            last_tax_paid_slot: u64::from_le_bytes(read_array(
+                src,
+                Self::LAST_TAX_PAID_SLOT_OFFSET,
+            )),
+            holder_house_address: Pubkey::new_from_array(read_array(
+                src,
+                Self::HOLDER_HOUSE_ADDRESS_OFFSET,
+            )),
+            stake_address: Pubkey::new_from_array(read_array(src, Self::STAKE_ADDRESS_OFFSET)),
+            liege_address: Pubkey::new_from_array(read_array(src, Self::LIEGE_ADDRESS_OFFSET)),
+            liege_vassal_index: u16::from_le_bytes(read_array(
+                src,
+                Self::LIEGE_VASSAL_INDEX_OFFSET,
+            )),
+            vassal_count: u16::from_le_bytes(read_array(src, Self::VASSAL_COUNT_OFFSET)),
+            coat_of_arms: read_array(src, Self::COAT_OF_ARMS_OFFSET),
+            display_name: read_array(src, Self::DISPLAY_NAME_OFFSET),
+            realm_address: Pubkey::new_from_array(read_array(src, Self::REALM_ADDRESS_OFFSET)),
+            live_vassal_count: u16::from_le_bytes(read_array(src, Self::LIVE_VASSAL_COUNT_OFFSET)),
+            seed_liege_address: Pubkey::new_from_array(read_array(
+                src,
+                Self::SEED_LIEGE_ADDRESS_OFFSET,
+            )),
+            seed_liege_vassal_index: u16::from_le_bytes(read_array(
+                src,
+                Self::SEED_LIEGE_VASSAL_INDEX_OFFSET,
+            )),
+            succession_law: SuccessionLaw::try_from(src[Self::SUCCESSION_LAW_OFFSET])
+                .map_err(|_| ProgramError::InvalidAccountData)?,
+        })
     }
+}
 
-    #[test]
-    fn serialize_data() {
-        // Bytes for tests
-        let TEST_BYTES: Vec<u8> = test_bytes();
-        // HouseData for tests
-        let TEST_RECORD_DATA: HouseData = HouseData {
-            version: TEST_VERSION,
-            governance_token_supply: 1,
-            coat_of_arms: String::from_utf8(vec![0; 128]).unwrap(),
-            display_name: String::from_utf8(vec![0; 128]).unwrap(),
-            prestige: 10000,
-            virtue: 10000,
-        };
-        let mut expected = vec![1, 0];
-        // expected.extend_from_slice(&TEST_PUBKEY.to_bytes());
-        // expected.extend_from_slice(TEST_RECORD_DATA.data.try_to_vec().unwrap().as_slice());
-        assert_eq!(TEST_RECORD_DATA.try_to_vec().unwrap(), expected);
-        assert_eq!(
-            HouseData::try_from_slice(&expected).unwrap(),
-            TEST_RECORD_DATA
+/// Title account data, for updating the mutable fields of a title in place.
+pub struct TitleDataMut<'a> {
+    data: &'a mut [u8],
+}
+
+impl<'a> TitleDataMut<'a> {
+    /// Wrap the data of a title account.
+    pub fn new(data: &'a mut [u8]) -> Result<Self, ProgramError> {
+        if data.len() != TitleData::SIZE
+            || data[TitleData::ACCOUNT_TYPE_OFFSET] != AccountType::Title as u8
+        {
+            return Err(TitleError::AccountTypeMismatch.into());
+        }
+        Ok(Self { data })
+    }
+
+    /// Set lifecycle_state.
+    pub fn set_lifecycle_state(&mut self, lifecycle_state: u8) {
+        self.data[TitleData::LIFECYCLE_STATE_OFFSET] = lifecycle_state;
+    }
+
+    /// Set sale_price_lamports.
+    pub fn set_sale_price_lamports(&mut self, sale_price_lamports: u64) {
+        write_bytes(
+            self.data,
+            TitleData::SALE_PRICE_LAMPORTS_OFFSET,
+            &sale_price_lamports.to_le_bytes(),
+        );
+    }
+
+    /// Set last_tax_paid_slot.
+    pub fn set_last_tax_paid_slot(&mut self, last_tax_paid_slot: u64) {
+        write_bytes(
+            self.data,
+            TitleData::LAST_TAX_PAID_SLOT_OFFSET,
+            &last_tax_paid_slot.to_le_bytes(),
+        );
+    }
+
+    /// Set holder_house_address.
+    pub fn set_holder_house_address(&mut self, holder_house_address: &Pubkey) {
+        write_bytes(
+            self.data,
+            TitleData::HOLDER_HOUSE_ADDRESS_OFFSET,
+            holder_house_address.as_ref(),
+        );
+    }
+
+    /// Set stake_address.
+    pub fn set_stake_address(&mut self, stake_address: &Pubkey) {
+        write_bytes(
+            self.data,
+            TitleData::STAKE_ADDRESS_OFFSET,
+            stake_address.as_ref(),
+        );
+    }
+
+    /// Set liege_address and liege_vassal_index.
+    pub fn set_liege(&mut self, liege_address: &Pubkey, liege_vassal_index: u16) {
+        write_bytes(
+            self.data,
+            TitleData::LIEGE_ADDRESS_OFFSET,
+            liege_address.as_ref(),
+        );
+        write_bytes(
+            self.data,
+            TitleData::LIEGE_VASSAL_INDEX_OFFSET,
+            &liege_vassal_index.to_le_bytes(),
+        );
+    }
+
+    /// Set vassal_count.
+    pub fn set_vassal_count(&mut self, vassal_count: u16) {
+        write_bytes(
+            self.data,
+            TitleData::VASSAL_COUNT_OFFSET,
+            &vassal_count.to_le_bytes(),
+        );
+    }
+
+    /// Set coat_of_arms.
+    pub fn set_coat_of_arms(&mut self, coat_of_arms: &[u8; MAX_STRING_LENGTH]) {
+        write_bytes(self.data, TitleData::COAT_OF_ARMS_OFFSET, coat_of_arms);
+    }
+
+    /// Set live_vassal_count.
+    pub fn set_live_vassal_count(&mut self, live_vassal_count: u16) {
+        write_bytes(
+            self.data,
+            TitleData::LIVE_VASSAL_COUNT_OFFSET,
+            &live_vassal_count.to_le_bytes(),
         );
     }
 
+    /// Set succession_law.
+    pub fn set_succession_law(&mut self, succession_law: SuccessionLaw) {
+        self.data[TitleData::SUCCESSION_LAW_OFFSET] = succession_law as u8;
+    }
+}
+
+fn write_bytes(dst: &mut [u8], offset: usize, bytes: &[u8]) {
+    dst[offset..offset + bytes.len()].copy_from_slice(bytes);
+}
+
+fn read_array<const N: usize>(src: &[u8], offset: usize) -> [u8; N] {
+    let mut array = [0; N];
+    array.copy_from_slice(&src[offset..offset + N]);
+    array
+}
+
+/// Pack a string into a null-padded fixed-size array. Fails if the string doesn't
+/// leave room for a null terminator.
+pub fn pack_string(string: &str) -> Result<[u8; MAX_STRING_LENGTH], ProgramError> {
+    let bytes = string.as_bytes();
+    if bytes.len() >= MAX_STRING_LENGTH {
+        msg!("String too long: {} bytes", bytes.len());
+        return Err(TitleError::StringTooLong.into());
+    }
+    let mut packed = [0; MAX_STRING_LENGTH];
+    packed[..bytes.len()].copy_from_slice(bytes);
+    Ok(packed)
+}
+
+/// Unpack a string from a null-padded fixed-size array.
+pub fn unpack_string(packed: &[u8]) -> Result<&str, ProgramError> {
+    let len = packed.iter().position(|b| *b == 0).unwrap_or(packed.len());
+    std::str::from_utf8(&packed[..len]).map_err(|_| TitleError::InvalidUtf8.into())
+}
+
+/// Check a display name: at most MAX_STRING_BYTES bytes of UTF-8 with no control
+/// characters, so that it is stored and shown as given.
+pub fn validate_display_name(name: &[u8]) -> Result<&str, TitleError> {
+    if name.len() > MAX_STRING_BYTES {
+        return Err(TitleError::StringTooLong);
+    }
+    let name = std::str::from_utf8(name).map_err(|_| TitleError::InvalidUtf8)?;
+    if name.chars().any(char::is_control) {
+        return Err(TitleError::StringHasControlCharacter);
+    }
+    Ok(name)
+}
+
+/// Check a realm name: a non-empty display name of at most MAX_REALM_NAME_LENGTH
+/// bytes.
+pub fn validate_realm_name(name: &[u8]) -> Result<&str, TitleError> {
+    if name.is_empty() || name.len() > MAX_REALM_NAME_LENGTH {
+        return Err(TitleError::InvalidRealmName);
+    }
+    validate_display_name(name)
+}
+
+/// Check a coat of arms URI: a display name that is either empty, for no coat of
+/// arms, or a whitespace-free URI with one of COAT_OF_ARMS_URI_SCHEMES.
+pub fn validate_coat_of_arms(uri: &[u8]) -> Result<&str, TitleError> {
+    let uri = validate_display_name(uri)?;
+    if uri.is_empty() {
+        return Ok(uri);
+    }
+    let has_scheme = COAT_OF_ARMS_URI_SCHEMES
+        .iter()
+        .any(|scheme| uri.len() > scheme.len() && uri.starts_with(scheme));
+    if !has_scheme || uri.chars().any(char::is_whitespace) {
+        return Err(TitleError::UnsupportedUriScheme);
+    }
+    Ok(uri)
+}
+
+/// Game rules of the realm that the realm admin may tune without redeploying the
+/// program.
+#[derive(Clone, Debug, BorshSerialize, BorshDeserialize, PartialEq)]
+pub struct RealmSettings {
+    /// Lowest rank titles may be created at.
+    pub max_rank: Rank,
+
+    /// Highest kind value titles may be created with.
+    pub max_kind: Kind,
+
+    /// Maximum number of vassals per title.
+    pub max_vassals: u16,
+
+    /// Harberger tax owed per tax period, in basis points of the title's sale price.
+    pub tax_rate_bps: u16,
+
+    /// Length of a tax period, in slots.
+    pub tax_period_slots: u64,
+
+    /// Delisted titles are taxed as if they were for sale at this multiple of their
+    /// required stake.
+    pub not_for_sale_assessment_multiplier: u64,
+
+    /// Protocol fee on title sales, in basis points of the sale price. Paid by the
+    /// buyer to the realm treasury, on top of the sale price.
+    pub protocol_fee_bps: u16,
+
+    /// Slots an inactive vassal title may stay inactive before its liege's holder may
+    /// escheat it.
+    pub escheat_grace_period_slots: u64,
+
+    /// Slots the holders of an elective title's vassals have to elect its next holder,
+    /// see ElectionData.
+    pub election_period_slots: u64,
+
+    /// Slots a house may go without proving life before its elective titles fall
+    /// vacant and may be elected anew, whether or not it named an heir.
+    pub vacancy_period_slots: u64,
+}
+
+impl Default for RealmSettings {
+    fn default() -> Self {
+        RealmSettings {
+            max_rank: Rank::Knight,
+            max_kind: Kind::Religious,
+            max_vassals: u16::MAX,
+            tax_rate_bps: 100,
+            // Roughly one week.
+            tax_period_slots: 1_512_000,
+            not_for_sale_assessment_multiplier: 10,
+            protocol_fee_bps: 0,
+            // Roughly four weeks.
+            escheat_grace_period_slots: 6_048_000,
+            // Roughly one week.
+            election_period_slots: 1_512_000,
+            // Roughly four weeks.
+            vacancy_period_slots: 6_048_000,
+        }
+    }
+}
+
+impl RealmSettings {
+    /// Serialized size of the struct
+    pub const SIZE: usize = 1 + 1 + 2 + 2 + 8 + 8 + 2 + 8 + 8 + 8;
+
+    /// Check that the settings are usable: rates are at most 100% and tax, election and
+    /// vacancy periods are not empty.
+    pub fn validate(&self) -> Result<(), TitleError> {
+        if self.tax_rate_bps > 10_000
+            || self.protocol_fee_bps > 10_000
+            || self.tax_period_slots == 0
+            || self.election_period_slots == 0
+            || self.vacancy_period_slots == 0
+            || self.max_vassals == 0
+        {
+            return Err(TitleError::InvalidRealmSettings);
+        }
+        Ok(())
+    }
+
+    /// Protocol fee owed on a sale at the given price.
+    pub fn protocol_fee_lamports(&self, sale_price_lamports: u64) -> Option<u64> {
+        let fee = (sale_price_lamports as u128).checked_mul(self.protocol_fee_bps as u128)?
+            / 10_000;
+        u64::try_from(fee).ok()
+    }
+}
+
+/// Singleton account holding the realm's tunable rules and the admin who may change
+/// them.
+#[derive(Clone, Debug, BorshSerialize, BorshDeserialize, PartialEq)]
+pub struct RealmConfigData {
+    /// Account type, always AccountType::RealmConfig.
+    pub account_type: AccountType,
+
+    /// Struct version, allows for upgrades to the program.
+    pub version: u8,
+
+    /// Wallet allowed to update the config. *Mutable*.
+    pub admin: Pubkey,
+
+    /// Rules of the realm. *Mutable*.
+    pub settings: RealmSettings,
+}
+
+impl RealmConfigData {
+    /// Version to fill in on new created accounts.
+    pub const CURRENT_VERSION: u8 = 3;
+    /// Serialized size of the struct.
+    pub const SIZE: usize = 1 + 1 + 32 + RealmSettings::SIZE;
+    /// Serialized size of version 2, before RealmSettings.election_period_slots and
+    /// RealmSettings.vacancy_period_slots.
+    pub const V2_SIZE: usize = Self::SIZE - 8 - 8;
+    /// Serialized size of version 1, before RealmSettings.escheat_grace_period_slots.
+    pub const V1_SIZE: usize = Self::V2_SIZE - 8;
+}
+
+impl IsInitialized for RealmConfigData {
+    /// Is initialized
+    fn is_initialized(&self) -> bool {
+        self.version == Self::CURRENT_VERSION
+    }
+}
+
+/// A realm: an independent hierarchy of titles under one root title.
+#[derive(Clone, Debug, BorshSerialize, BorshDeserialize, BorshSchema, PartialEq)]
+pub struct RealmData {
+    /// Account type, always AccountType::Realm.
+    pub account_type: AccountType,
+
+    /// Struct version, allows for upgrades to the program.
+    pub version: u8,
+
+    /// Wallet that created the realm. Immutable. Only this wallet or the realm config
+    /// admin may create the realm's root title or withdraw from its treasury.
+    pub authority: Pubkey,
+
+    /// Root title of the realm, all zeroes until it is created. Always
+    /// get_root_title_address of the realm once set.
+    pub root_title_address: Pubkey,
+
+    /// Name of the realm. Immutable. Used to form a seed for the realm's address, see
+    /// validate_realm_name.
+    pub name: String,
+}
+
+impl RealmData {
+    /// Version to fill in on new created accounts.
+    pub const CURRENT_VERSION: u8 = 2;
+    /// Serialized maximum size of the struct.
+    pub const SIZE: usize = 1 + 1 + 32 + 32 + 4 + MAX_REALM_NAME_LENGTH;
+}
+
+impl IsInitialized for RealmData {
+    /// Is initialized
+    fn is_initialized(&self) -> bool {
+        self.version == Self::CURRENT_VERSION
+    }
+}
+
+/// A page of a title's vassal addresses. Page n of a title holds the addresses of
+/// vassals n * VASSALS_PER_PAGE up to (n + 1) * VASSALS_PER_PAGE - 1.
+#[derive(Clone, Debug, BorshSerialize, BorshDeserialize, BorshSchema, PartialEq)]
+pub struct VassalPageData {
+    /// Account type, always AccountType::VassalPage.
+    pub account_type: AccountType,
+
+    /// Struct version, allows for upgrades to the program.
+    pub version: u8,
+
+    /// Liege title whose vassals are listed. Immutable.
+    pub title_address: Pubkey,
+
+    /// Index of this page. Immutable. Used to form a seed for this page's address.
+    pub page_index: u16,
+
+    /// Vassal title addresses. *Mutable*. A vassal that moved to another liege is
+    /// left as an all-zero address, so that the other vassals keep their index.
+    pub vassal_addresses: Vec<Pubkey>,
+}
+
+impl VassalPageData {
+    /// Version to fill in on new created accounts.
+    pub const CURRENT_VERSION: u8 = 1;
+    /// Number of vassal addresses per page.
+    pub const VASSALS_PER_PAGE: usize = 32;
+    /// Serialized maximum size of the struct.
+    pub const SIZE: usize = 1 + 1 + 32 + 2 + 4 + (32 * Self::VASSALS_PER_PAGE);
+
+    /// Index of the page holding the given vassal index, and the vassal's position in
+    /// that page.
+    pub fn locate(vassal_index: u16) -> (u16, usize) {
+        let per_page = Self::VASSALS_PER_PAGE as u16;
+        (vassal_index / per_page, (vassal_index % per_page) as usize)
+    }
+}
+
+impl IsInitialized for VassalPageData {
+    /// Is initialized
+    fn is_initialized(&self) -> bool {
+        self.version == Self::CURRENT_VERSION
+    }
+}
+
+/// A house's petition to hold a new vassal title under a liege title. Once the liege's
+/// holder accepts it, the title is created as described and held by the petitioning
+/// house. The petition account also holds the rent for the new title.
+#[derive(Clone, Debug, BorshSerialize, BorshDeserialize, PartialEq)]
+pub struct PetitionData {
+    /// Account type, always AccountType::Petition.
+    pub account_type: AccountType,
+
+    /// Struct version, allows for upgrades to the program.
+    pub version: u8,
+
+    /// House that will hold the new title. Immutable.
+    pub petitioner_house_address: Pubkey,
+
+    /// Liege title of the new title. Immutable.
+    pub liege_address: Pubkey,
+
+    /// See TitleData.rank. Immutable.
+    pub rank: Rank,
+
+    /// See TitleData.kind. Immutable.
+    pub kind: Kind,
+
+    /// See TitleData.required_stake_lamports. Immutable.
+    pub required_stake_lamports: u64,
+
+    /// Coat of arms URI for the new title, see validate_coat_of_arms. Immutable.
+    pub coat_of_arms: String,
+
+    /// Display name for the new title, see validate_display_name. Immutable.
+    pub display_name: String,
+}
+
+impl PetitionData {
+    /// Version to fill in on new created accounts.
+    pub const CURRENT_VERSION: u8 = 1;
+    /// Serialized maximum size of the struct.
+    pub const SIZE: usize = 1 + 1 + 32 + 32 + 1 + 1 + 8 + MAX_STRING_LENGTH + MAX_STRING_LENGTH;
+}
+
+impl IsInitialized for PetitionData {
+    /// Is initialized
+    fn is_initialized(&self) -> bool {
+        self.version == Self::CURRENT_VERSION
+    }
+}
+
+/// The titles held by a house, kept up to date by every instruction that changes a
+/// title's holder. Created when the house first comes to hold a title, and resized as
+/// titles come and go. Titles that a house held before holdings were recorded are
+/// missing from them.
+#[derive(Clone, Debug, BorshSerialize, BorshDeserialize, PartialEq)]
+pub struct HoldingsData {
+    /// Account type, always AccountType::Holdings.
+    pub account_type: AccountType,
+
+    /// Struct version, allows for upgrades to the program.
+    pub version: u8,
+
+    /// House holding the titles. Immutable.
+    pub house_address: Pubkey,
+
+    /// Addresses of the titles held, in no particular order.
+    pub title_addresses: Vec<Pubkey>,
+}
+
+impl HoldingsData {
+    /// Version to fill in on new created accounts.
+    pub const CURRENT_VERSION: u8 = 1;
+    /// Serialized size of the struct without any title.
+    pub const SIZE: usize = 1 + 1 + 32 + 4;
+
+    /// Serialized size of the struct holding the given number of titles.
+    pub fn size(title_count: usize) -> usize {
+        Self::SIZE + 32 * title_count
+    }
+}
+
+impl IsInitialized for HoldingsData {
+    /// Is initialized
+    fn is_initialized(&self) -> bool {
+        self.version == Self::CURRENT_VERSION
+    }
+}
+
+/// An account of an instruction proposed to the token holders of a house.
+#[derive(Clone, Debug, BorshSerialize, BorshDeserialize, PartialEq)]
+pub struct ProposedAccount {
+    /// Address of the account.
+    pub pubkey: Pubkey,
+    /// Whether the account signs the instruction. Only the house's governance wallet
+    /// may sign without being a signer of the executing transaction.
+    pub is_signer: bool,
+    /// Whether the account is writable.
+    pub is_writable: bool,
+}
+
+impl ProposedAccount {
+    /// Serialized size of the struct.
+    pub const SIZE: usize = 32 + 1 + 1;
+}
+
+/// A proposal to the holders of a house's governance tokens to run an instruction of
+/// the program, signed by the house's governance wallet. Holders approve it by
+/// depositing their tokens in the proposal's escrow, see VoteData, and anybody may
+/// execute it once the deposits are a majority of the supply, until its voting
+/// deadline.
+#[derive(Clone, Debug, BorshSerialize, BorshDeserialize, PartialEq)]
+pub struct ProposalData {
+    /// Account type, always AccountType::Proposal.
+    pub account_type: AccountType,
+
+    /// Struct version, allows for upgrades to the program.
+    pub version: u8,
+
+    /// House the proposal is made to. Immutable.
+    pub house_address: Pubkey,
+
+    /// Index of the proposal among the house's proposals. Immutable.
+    pub proposal_index: u32,
+
+    /// Governance tokens deposited in approval. *Mutable*.
+    pub approving_tokens: u64,
+
+    /// Whether the proposal has been executed. *Mutable*. Executed proposals take no
+    /// more votes, and their deposits may only be withdrawn.
+    pub executed: bool,
+
+    /// Slot from which the proposal takes no more votes and can't be executed.
+    /// Immutable, see ProposalData::VOTING_PERIOD_SLOTS.
+    pub voting_deadline_slot: u64,
+
+    /// Accounts of the proposed instruction. Immutable.
+    pub accounts: Vec<ProposedAccount>,
+
+    /// Data of the proposed instruction, a serialized TitleInstruction. Immutable.
+    pub data: Vec<u8>,
+}
+
+impl ProposalData {
+    /// Version to fill in on new created accounts.
+    pub const CURRENT_VERSION: u8 = 2;
+    /// Serialized size of the struct without any accounts or data.
+    pub const SIZE: usize = 1 + 1 + 32 + 4 + 8 + 1 + 8 + 4 + 4;
+    /// Slots from the creation of a proposal to its voting deadline, roughly one week.
+    pub const VOTING_PERIOD_SLOTS: u64 = 1_512_000;
+
+    /// Serialized size of the struct proposing an instruction with the given number of
+    /// accounts and bytes of data.
+    pub fn size(account_count: usize, data_len: usize) -> usize {
+        Self::SIZE + ProposedAccount::SIZE * account_count + data_len
+    }
+
+    /// Whether the approving tokens are a majority of the given supply.
+    pub fn is_approved(&self, governance_token_supply: u16) -> bool {
+        self.approving_tokens > u64::from(governance_token_supply) / 2
+    }
+
+    /// Whether the voting deadline has passed as of the slot.
+    pub fn has_ended(&self, slot: u64) -> bool {
+        slot >= self.voting_deadline_slot
+    }
+}
+
+impl IsInitialized for ProposalData {
+    /// Is initialized
+    fn is_initialized(&self) -> bool {
+        self.version == Self::CURRENT_VERSION
+    }
+}
+
+/// A wallet's vote for a proposal: the governance tokens it deposited in the
+/// proposal's escrow, which it may withdraw at any time. Withdrawing them before the
+/// proposal is executed takes back the approval.
+#[derive(Clone, Debug, BorshSerialize, BorshDeserialize, PartialEq)]
+pub struct VoteData {
+    /// Account type, always AccountType::Vote.
+    pub account_type: AccountType,
+
+    /// Struct version, allows for upgrades to the program.
+    pub version: u8,
+
+    /// Proposal voted for. Immutable.
+    pub proposal_address: Pubkey,
+
+    /// Wallet that voted. Immutable.
+    pub voter_address: Pubkey,
+
+    /// Governance tokens deposited. *Mutable*.
+    pub deposited_tokens: u64,
+}
+
+impl VoteData {
+    /// Version to fill in on new created accounts.
+    pub const CURRENT_VERSION: u8 = 1;
+    /// Serialized size of the struct.
+    pub const SIZE: usize = 1 + 1 + 32 + 32 + 8;
+}
+
+impl IsInitialized for VoteData {
+    /// Is initialized
+    fn is_initialized(&self) -> bool {
+        self.version == Self::CURRENT_VERSION
+    }
+}
+
+/// A vassal title's vote for the next holder of its liege, see ElectionData.
+#[derive(Clone, Debug, BorshSerialize, BorshDeserialize, PartialEq)]
+pub struct Ballot {
+    /// Vassal title voting. One ballot per vassal.
+    pub vassal_address: Pubkey,
+
+    /// House voted for. *Mutable*, the vassal's holder may change its vote.
+    pub candidate_house_address: Pubkey,
+}
+
+impl Ballot {
+    /// Serialized size of the struct.
+    pub const SIZE: usize = 32 + 32;
+}
+
+/// Election of the next holder of an elective title whose holder's house vacated it,
+/// see HouseData::has_vacated. The holders of the title's vassals cast a ballot per
+/// vassal. The candidate with most ballots wins once it has ballots of more than half
+/// of the title's vassals, or when the election period of the realm is over. If the
+/// period ends without ballots, the title passes to the heir of the vacating house, or
+/// to the realm's treasury, see TitleInstruction::ConcludeElection. The election is
+/// closed once it concludes.
+#[derive(Clone, Debug, BorshSerialize, BorshDeserialize, PartialEq)]
+pub struct ElectionData {
+    /// Account type, always AccountType::Election.
+    pub account_type: AccountType,
+
+    /// Struct version, allows for upgrades to the program.
+    pub version: u8,
+
+    /// Title to elect a holder for. Immutable.
+    pub title_address: Pubkey,
+
+    /// House holding the title when the election opened. Immutable. The election lapses
+    /// if the title changes hands or the house proves life before it concludes.
+    pub holder_house_address: Pubkey,
+
+    /// Slot the election opened at. Immutable.
+    pub opened_slot: u64,
+
+    /// Ballots cast so far, in the order they were first cast. *Mutable*.
+    pub ballots: Vec<Ballot>,
+}
+
+impl ElectionData {
+    /// Version to fill in on new created accounts.
+    pub const CURRENT_VERSION: u8 = 1;
+    /// Serialized size of the struct without any ballot.
+    pub const SIZE: usize = 1 + 1 + 32 + 32 + 8 + 4;
+
+    /// Serialized size of the struct holding the given number of ballots.
+    pub fn size(ballot_count: usize) -> usize {
+        Self::SIZE + Ballot::SIZE * ballot_count
+    }
+
+    /// The candidate with most ballots and its number of ballots, none before any
+    /// ballot is cast. Ties go to the candidate first voted for.
+    pub fn leader(&self) -> Option<(Pubkey, usize)> {
+        let mut leader: Option<(Pubkey, usize)> = None;
+        for (position, ballot) in self.ballots.iter().enumerate() {
+            let candidate = ballot.candidate_house_address;
+            if self.ballots[..position]
+                .iter()
+                .any(|earlier| earlier.candidate_house_address == candidate)
+            {
+                continue;
+            }
+            let votes = self
+                .ballots
+                .iter()
+                .filter(|ballot| ballot.candidate_house_address == candidate)
+                .count();
+            let leads = match leader {
+                Some((_, leader_votes)) => votes > leader_votes,
+                None => true,
+            };
+            if leads {
+                leader = Some((candidate, votes));
+            }
+        }
+        leader
+    }
+
+    /// Whether the election period of the realm is over as of the slot.
+    pub fn has_ended(&self, slot: u64, settings: &RealmSettings) -> bool {
+        slot.saturating_sub(self.opened_slot) >= settings.election_period_slots
+    }
+
+    /// The winner of the election as of the slot, if any, given the title's live vassal
+    /// count. An election that ended without ballots has no winner.
+    pub fn winner(
+        &self,
+        live_vassal_count: u16,
+        slot: u64,
+        settings: &RealmSettings,
+    ) -> Option<Pubkey> {
+        let (candidate, votes) = self.leader()?;
+        if self.has_ended(slot, settings) || votes * 2 > live_vassal_count as usize {
+            Some(candidate)
+        } else {
+            None
+        }
+    }
+}
+
+impl IsInitialized for ElectionData {
+    /// Is initialized
+    fn is_initialized(&self) -> bool {
+        self.version == Self::CURRENT_VERSION
+    }
+}
+
+/// Version 1 layout of HouseData, written before accounts carried an AccountType.
+#[derive(Clone, Debug, BorshSerialize, BorshDeserialize, BorshSchema, PartialEq)]
+pub struct HouseDataV1 {
+    /// Struct version, always 1.
+    pub version: u16,
+    /// See HouseData.governance_token_supply.
+    pub governance_token_supply: u16,
+    /// See HouseData.coat_of_arms.
+    pub coat_of_arms: String,
+    /// See HouseData.display_name.
+    pub display_name: String,
+    /// See HouseData.prestige.
+    pub prestige: i32,
+    /// See HouseData.virtue.
+    pub virtue: i32,
+}
+
+impl HouseDataV1 {
+    /// Struct version
+    pub const VERSION: u16 = 1;
+    /// Serialized size of the struct
+    pub const SIZE: usize = 2 + 2 + 128 + 128 + 4 + 4;
+}
+
+impl From<HouseDataV1> for HouseData {
+    fn from(v1: HouseDataV1) -> Self {
+        HouseData {
+            account_type: AccountType::House,
+            version: HouseData::CURRENT_VERSION,
+            governance_token_supply: v1.governance_token_supply,
+            coat_of_arms: v1.coat_of_arms,
+            display_name: v1.display_name,
+            prestige: v1.prestige,
+            virtue: v1.virtue,
+            held_title_count: HouseData::UNKNOWN_TITLE_COUNT,
+            authority: Pubkey::new(&[0; 32]),
+            proposal_count: 0,
+            heir_house_address: Pubkey::new(&[0; 32]),
+            succession_period_slots: 0,
+            last_proof_of_life_slot: 0,
+        }
+    }
+}
+
+/// Version 1 layout of TitleData, written before accounts carried an AccountType and
+/// before titles were taxed.
+#[derive(Clone, Debug, BorshSerialize, BorshDeserialize, PartialEq)]
+pub struct TitleDataV1 {
+    /// Struct version, always 1.
+    pub version: u8,
+    /// See TitleData.lifecycle_state.
+    pub lifecycle_state: u8,
+    /// See TitleData.rank.
+    pub rank: Rank,
+    /// See TitleData.kind.
+    pub kind: Kind,
+    /// See TitleData.required_stake_lamports.
+    pub required_stake_lamports: u64,
+    /// See TitleData.sale_price_lamports.
+    pub sale_price_lamports: u64,
+    /// See TitleData.coat_of_arms.
+    pub coat_of_arms: String,
+    /// See TitleData.display_name.
+    pub display_name: String,
+    /// See TitleData.holder_house_address.
+    pub holder_house_address: Pubkey,
+    /// See TitleData.stake_address.
+    pub stake_address: Pubkey,
+    /// See TitleData.liege_address.
+    pub liege_address: Pubkey,
+    /// Index of the title in the list of the liege's vassals. Title addresses were
+    /// derived from this index repeated 32 times.
+    pub liege_vassal_index: u8,
+    /// Vassal title addresses, now kept in VassalPageData accounts.
+    pub vassal_addresses: Vec<Pubkey>,
+}
+
+impl TitleDataV1 {
+    /// Struct version
+    pub const VERSION: u8 = 1;
+    /// Maximum number of vassals per title.
+    pub const MAX_VASSALS: usize = 64;
+    /// Serialized maximum size of the struct.
+    pub const SIZE: usize =
+        1 + 1 + 1 + 1 + 8 + 8 + 128 + 128 + 32 + 32 + 32 + 1 + 4 + (32 * Self::MAX_VASSALS);
+}
+
+/// Pack a version 1 string, which was only length-checked by clients, dropping bytes
+/// that don't fit along with any partial character at the cut.
+fn pack_string_truncated(string: &str) -> [u8; MAX_STRING_LENGTH] {
+    let mut len = string.len().min(MAX_STRING_LENGTH - 1);
+    while !string.is_char_boundary(len) {
+        len -= 1;
+    }
+    pack_string(&string[..len]).unwrap()
+}
+
+impl From<&TitleDataV1> for TitleData {
+    /// Tax is considered paid up to slot 0; the caller should set last_tax_paid_slot.
+    /// Vassal addresses must be moved to vassal pages by the caller.
+    fn from(v1: &TitleDataV1) -> Self {
+        TitleData {
+            account_type: AccountType::Title,
+            version: TitleData::CURRENT_VERSION,
+            lifecycle_state: v1.lifecycle_state,
+            rank: v1.rank,
+            kind: v1.kind,
+            required_stake_lamports: v1.required_stake_lamports,
+            sale_price_lamports: v1.sale_price_lamports,
+            last_tax_paid_slot: 0,
+            holder_house_address: v1.holder_house_address,
+            stake_address: v1.stake_address,
+            liege_address: v1.liege_address,
+            liege_vassal_index: v1.liege_vassal_index.into(),
+            vassal_count: v1.vassal_addresses.len() as u16,
+            coat_of_arms: pack_string_truncated(&v1.coat_of_arms),
+            display_name: pack_string_truncated(&v1.display_name),
+            realm_address: Pubkey::default(),
+            live_vassal_count: v1.vassal_addresses.len() as u16,
+            seed_liege_address: v1.liege_address,
+            seed_liege_vassal_index: v1.liege_vassal_index.into(),
+            succession_law: SuccessionLaw::Heir,
+        }
+    }
+}
+
+/// Version 2 layout of TitleData, written before accounts carried an AccountType.
+#[derive(Clone, Debug, BorshSerialize, BorshDeserialize, PartialEq)]
+pub struct TitleDataV2 {
+    /// Struct version, always 2.
+    pub version: u8,
+    /// See TitleData.lifecycle_state.
+    pub lifecycle_state: u8,
+    /// See TitleData.rank.
+    pub rank: Rank,
+    /// See TitleData.kind.
+    pub kind: Kind,
+    /// See TitleData.required_stake_lamports.
+    pub required_stake_lamports: u64,
+    /// See TitleData.sale_price_lamports.
+    pub sale_price_lamports: u64,
+    /// See TitleData.last_tax_paid_slot.
+    pub last_tax_paid_slot: u64,
+    /// See TitleData.coat_of_arms.
+    pub coat_of_arms: String,
+    /// See TitleData.display_name.
+    pub display_name: String,
+    /// See TitleData.holder_house_address.
+    pub holder_house_address: Pubkey,
+    /// See TitleData.stake_address.
+    pub stake_address: Pubkey,
+    /// See TitleData.liege_address.
+    pub liege_address: Pubkey,
+    /// See TitleDataV1.liege_vassal_index.
+    pub liege_vassal_index: u8,
+    /// See TitleDataV1.vassal_addresses.
+    pub vassal_addresses: Vec<Pubkey>,
+}
+
+impl TitleDataV2 {
+    /// Struct version
+    pub const VERSION: u8 = 2;
+    /// Serialized maximum size of the struct: the version 1 layout and
+    /// last_tax_paid_slot.
+    pub const SIZE: usize = TitleDataV1::SIZE + 8;
+}
+
+impl From<&TitleDataV2> for TitleData {
+    /// Vassal addresses must be moved to vassal pages by the caller.
+    fn from(v2: &TitleDataV2) -> Self {
+        TitleData {
+            account_type: AccountType::Title,
+            version: TitleData::CURRENT_VERSION,
+            lifecycle_state: v2.lifecycle_state,
+            rank: v2.rank,
+            kind: v2.kind,
+            required_stake_lamports: v2.required_stake_lamports,
+            sale_price_lamports: v2.sale_price_lamports,
+            last_tax_paid_slot: v2.last_tax_paid_slot,
+            holder_house_address: v2.holder_house_address,
+            stake_address: v2.stake_address,
+            liege_address: v2.liege_address,
+            liege_vassal_index: v2.liege_vassal_index.into(),
+            vassal_count: v2.vassal_addresses.len() as u16,
+            coat_of_arms: pack_string_truncated(&v2.coat_of_arms),
+            display_name: pack_string_truncated(&v2.display_name),
+            realm_address: Pubkey::default(),
+            live_vassal_count: v2.vassal_addresses.len() as u16,
+            seed_liege_address: v2.liege_address,
+            seed_liege_vassal_index: v2.liege_vassal_index.into(),
+            succession_law: SuccessionLaw::Heir,
+        }
+    }
+}
+
+/// Version 1 layout of RealmData, written before realms recorded their root title.
+#[derive(Clone, Debug, BorshSerialize, BorshDeserialize, PartialEq)]
+pub struct RealmDataV1 {
+    /// Account type, always AccountType::Realm.
+    pub account_type: AccountType,
+    /// Struct version, always 1.
+    pub version: u8,
+    /// See RealmData.authority.
+    pub authority: Pubkey,
+    /// See RealmData.name.
+    pub name: String,
+}
+
+impl RealmDataV1 {
+    /// Struct version
+    pub const VERSION: u8 = 1;
+    /// Serialized maximum size of the struct
+    pub const SIZE: usize = 1 + 1 + 32 + 4 + MAX_REALM_NAME_LENGTH;
+}
+
+impl From<RealmDataV1> for RealmData {
+    /// The caller should set root_title_address if the realm already has a root title.
+    fn from(v1: RealmDataV1) -> Self {
+        RealmData {
+            account_type: AccountType::Realm,
+            version: RealmData::CURRENT_VERSION,
+            authority: v1.authority,
+            root_title_address: Pubkey::new(&[0; 32]),
+            name: v1.name,
+        }
+    }
+}
+
+/// Version 1 layout of ProposalData, written before proposals had a voting deadline.
+#[derive(Clone, Debug, BorshSerialize, BorshDeserialize, PartialEq)]
+pub struct ProposalDataV1 {
+    /// Account type, always AccountType::Proposal.
+    pub account_type: AccountType,
+    /// Struct version, always 1.
+    pub version: u8,
+    /// See ProposalData.house_address.
+    pub house_address: Pubkey,
+    /// See ProposalData.proposal_index.
+    pub proposal_index: u32,
+    /// See ProposalData.approving_tokens.
+    pub approving_tokens: u64,
+    /// See ProposalData.executed.
+    pub executed: bool,
+    /// See ProposalData.accounts.
+    pub accounts: Vec<ProposedAccount>,
+    /// See ProposalData.data.
+    pub data: Vec<u8>,
+}
+
+impl ProposalDataV1 {
+    /// Struct version
+    pub const VERSION: u8 = 1;
+}
+
+impl From<ProposalDataV1> for ProposalData {
+    /// Proposals without a deadline are read as ended, so that their deposits may only
+    /// be withdrawn.
+    fn from(v1: ProposalDataV1) -> Self {
+        ProposalData {
+            account_type: AccountType::Proposal,
+            version: ProposalData::CURRENT_VERSION,
+            house_address: v1.house_address,
+            proposal_index: v1.proposal_index,
+            approving_tokens: v1.approving_tokens,
+            executed: v1.executed,
+            voting_deadline_slot: 0,
+            accounts: v1.accounts,
+            data: v1.data,
+        }
+    }
+}
+
+/// Account data of any version the program has written. Layouts are told apart by the
+/// size of the account data, then checked against their version field.
+#[derive(Clone, Debug, PartialEq)]
+pub enum VersionedAccount {
+    /// House at HouseData::CURRENT_VERSION
+    House(HouseData),
+    /// House at version 5, read without an heir
+    HouseV5(HouseData),
+    /// House at version 4, read with a zero proposal_count
+    HouseV4(HouseData),
+    /// House at version 3, read with an all-zero authority
+    HouseV3(HouseData),
+    /// House at version 2, read with a zero held_title_count and an all-zero authority
+    HouseV2(HouseData),
+    /// House at version 1
+    HouseV1(HouseDataV1),
+    /// Title at TitleData::CURRENT_VERSION
+    Title(TitleData),
+    /// Title at version 5, read with the Heir succession law
+    TitleV5(TitleData),
+    /// Title at version 4, read with a zero live_vassal_count, all-zero seeds and the
+    /// Heir succession law
+    TitleV4(TitleData),
+    /// Title at version 3, read with an all-zero realm_address, a zero
+    /// live_vassal_count, all-zero seeds and the Heir succession law
+    TitleV3(TitleData),
+    /// Title at version 2
+    TitleV2(TitleDataV2),
+    /// Title at version 1
+    TitleV1(TitleDataV1),
+    /// Realm config at RealmConfigData::CURRENT_VERSION
+    RealmConfig(RealmConfigData),
+    /// Realm config at version 2, read with a zero election_period_slots and
+    /// vacancy_period_slots
+    RealmConfigV2(RealmConfigData),
+    /// Realm config at version 1, read with a zero escheat_grace_period_slots
+    RealmConfigV1(RealmConfigData),
+    /// Realm at RealmData::CURRENT_VERSION
+    Realm(RealmData),
+    /// Realm at version 1
+    RealmV1(RealmDataV1),
+}
+
+impl VersionedAccount {
+    /// Decode account data of any known layout.
+    pub fn decode(data: &[u8]) -> Result<Self, ProgramError> {
+        let account = match data.len() {
+            HouseData::SIZE if data[0] == AccountType::House as u8 => {
+                Self::House(try_from_slice_checked(data, HouseData::SIZE)?)
+            }
+            HouseData::V5_SIZE if data[0] == AccountType::House as u8 => {
+                let mut padded = data.to_vec();
+                padded.resize(HouseData::SIZE, 0);
+                Self::HouseV5(try_from_slice_checked(&padded, HouseData::SIZE)?)
+            }
+            HouseData::V4_SIZE if data[0] == AccountType::House as u8 => {
+                let mut padded = data.to_vec();
+                padded.resize(HouseData::SIZE, 0);
+                Self::HouseV4(try_from_slice_checked(&padded, HouseData::SIZE)?)
+            }
+            HouseData::V3_SIZE if data[0] == AccountType::House as u8 => {
+                let mut padded = data.to_vec();
+                padded.resize(HouseData::SIZE, 0);
+                Self::HouseV3(try_from_slice_checked(&padded, HouseData::SIZE)?)
+            }
+            HouseData::V2_SIZE if data[0] == AccountType::House as u8 => {
+                let mut padded = data.to_vec();
+                padded.resize(HouseData::SIZE, 0);
+                Self::HouseV2(try_from_slice_checked(&padded, HouseData::SIZE)?)
+            }
+            TitleData::SIZE if data[0] == AccountType::Title as u8 => {
+                Self::Title(TitleData::unpack_from_slice(data)?)
+            }
+            TitleData::V5_SIZE if data[0] == AccountType::Title as u8 => {
+                let mut padded = data.to_vec();
+                padded.resize(TitleData::SIZE, 0);
+                Self::TitleV5(TitleData::unpack_from_slice(&padded)?)
+            }
+            TitleData::V4_SIZE if data[0] == AccountType::Title as u8 => {
+                let mut padded = data.to_vec();
+                padded.resize(TitleData::SIZE, 0);
+                Self::TitleV4(TitleData::unpack_from_slice(&padded)?)
+            }
+            TitleData::V3_SIZE if data[0] == AccountType::Title as u8 => {
+                let mut padded = data.to_vec();
+                padded.resize(TitleData::SIZE, 0);
+                Self::TitleV3(TitleData::unpack_from_slice(&padded)?)
+            }
+            RealmConfigData::SIZE if data[0] == AccountType::RealmConfig as u8 => {
+                Self::RealmConfig(try_from_slice_checked(data, RealmConfigData::SIZE)?)
+            }
+            RealmConfigData::V2_SIZE if data[0] == AccountType::RealmConfig as u8 => {
+                let mut padded = data.to_vec();
+                padded.resize(RealmConfigData::SIZE, 0);
+                Self::RealmConfigV2(try_from_slice_checked(&padded, RealmConfigData::SIZE)?)
+            }
+            RealmConfigData::V1_SIZE if data[0] == AccountType::RealmConfig as u8 => {
+                let mut padded = data.to_vec();
+                padded.resize(RealmConfigData::SIZE, 0);
+                Self::RealmConfigV1(try_from_slice_checked(&padded, RealmConfigData::SIZE)?)
+            }
+            RealmData::SIZE if data[0] == AccountType::Realm as u8 => {
+                Self::Realm(try_from_slice_checked(data, RealmData::SIZE)?)
+            }
+            RealmDataV1::SIZE if data[0] == AccountType::Realm as u8 => {
+                Self::RealmV1(try_from_slice_checked(data, RealmDataV1::SIZE)?)
+            }
+            HouseDataV1::SIZE => Self::HouseV1(try_from_slice_checked(data, HouseDataV1::SIZE)?),
+            TitleDataV2::SIZE => Self::TitleV2(try_from_slice_checked(data, TitleDataV2::SIZE)?),
+            TitleDataV1::SIZE => Self::TitleV1(try_from_slice_checked(data, TitleDataV1::SIZE)?),
+            len => {
+                msg!("Unknown account layout of {} bytes", len);
+                return Err(TitleError::DataTypeMismatch.into());
+            }
+        };
+        let (version, expected_version) = match &account {
+            Self::House(house) => (house.version, HouseData::CURRENT_VERSION),
+            Self::HouseV5(house) => (house.version, 5),
+            Self::HouseV4(house) => (house.version, 4),
+            Self::HouseV3(house) => (house.version, 3),
+            Self::HouseV2(house) => (house.version, 2),
+            Self::HouseV1(house) => (house.version, HouseDataV1::VERSION),
+            Self::Title(title) => (title.version.into(), TitleData::CURRENT_VERSION.into()),
+            Self::TitleV5(title) => (title.version.into(), 5),
+            Self::TitleV4(title) => (title.version.into(), 4),
+            Self::TitleV3(title) => (title.version.into(), 3),
+            Self::TitleV2(title) => (title.version.into(), TitleDataV2::VERSION.into()),
+            Self::TitleV1(title) => (title.version.into(), TitleDataV1::VERSION.into()),
+            Self::RealmConfig(config) => (
+                config.version.into(),
+                RealmConfigData::CURRENT_VERSION.into(),
+            ),
+            Self::RealmConfigV2(config) => (config.version.into(), 2),
+            Self::RealmConfigV1(config) => (config.version.into(), 1),
+            Self::Realm(realm) => (realm.version.into(), RealmData::CURRENT_VERSION.into()),
+            Self::RealmV1(realm) => (realm.version.into(), RealmDataV1::VERSION.into()),
+        };
+        if version != expected_version {
+            msg!("Expected version {}, got {}", expected_version, version);
+            return Err(TitleError::DataTypeMismatch.into());
+        }
+        Ok(account)
+    }
+
+    /// Whether the data is already in the current layout.
+    pub fn is_current(&self) -> bool {
+        matches!(
+            self,
+            Self::House(_) | Self::Title(_) | Self::RealmConfig(_) | Self::Realm(_)
+        )
+    }
+}
+
+/// Tests
+#[cfg(test)]
+pub mod tests {
+    use super::*;
+    use crate::{
+        error::TitleError,
+        utils::{try_from_account_data, try_from_slice_checked},
+    };
+    use solana_program::program_error::ProgramError;
+
+    /// Version for tests
+    pub const TEST_VERSION: u16 = 1;
+    /// Pubkey for tests
+    // pub const TEST_PUBKEY: Pubkey = Pubkey::new_from_array([100; 32]);
+
+    /// Bytes for tests
+    pub fn test_bytes() -> Vec<u8> {
+        return vec![42; 8];
+    }
+
+    #[test]
+    fn serialize_data() {
+        // Bytes for tests
+        let TEST_BYTES: Vec<u8> = test_bytes();
+        // HouseData for tests
+        let TEST_RECORD_DATA: HouseData = HouseData {
+            account_type: AccountType::House,
+            version: TEST_VERSION,
+            governance_token_supply: 1,
+            coat_of_arms: String::from_utf8(vec![0; 128]).unwrap(),
+            display_name: String::from_utf8(vec![0; 128]).unwrap(),
+            prestige: 10000,
+            virtue: 10000,
+            held_title_count: 0,
+            authority: Pubkey::new(&[0; 32]),
+            proposal_count: 0,
+            heir_house_address: Pubkey::new(&[0; 32]),
+            succession_period_slots: 0,
+            last_proof_of_life_slot: 0,
+        };
+        let mut expected = vec![AccountType::House as u8];
+        expected.extend_from_slice(&TEST_VERSION.to_le_bytes());
+        expected.extend_from_slice(&1u16.to_le_bytes());
+        for _ in 0..2 {
+            expected.extend_from_slice(&128u32.to_le_bytes());
+            expected.extend_from_slice(&[0; 128]);
+        }
+        expected.extend_from_slice(&10000i32.to_le_bytes());
+        expected.extend_from_slice(&10000i32.to_le_bytes());
+        expected.extend_from_slice(&0u32.to_le_bytes());
+        expected.extend_from_slice(&[0; 32]);
+        expected.extend_from_slice(&0u32.to_le_bytes());
+        expected.extend_from_slice(&[0; 32]);
+        expected.extend_from_slice(&0u64.to_le_bytes());
+        expected.extend_from_slice(&0u64.to_le_bytes());
+        assert_eq!(TEST_RECORD_DATA.try_to_vec().unwrap(), expected);
+        assert_eq!(
+            HouseData::try_from_slice(&expected).unwrap(),
+            TEST_RECORD_DATA
+        );
+    }
+
+    /// Active title for tests
+    pub fn test_title_data() -> TitleData {
+        TitleData {
+            account_type: AccountType::Title,
+            version: TitleData::CURRENT_VERSION,
+            lifecycle_state: TitleData::ACTIVE_STATE,
+            rank: Rank::Emperor,
+            kind: Kind::Noble,
+            required_stake_lamports: 1_000_000,
+            sale_price_lamports: 10_000_000,
+            last_tax_paid_slot: 1000,
+            holder_house_address: Pubkey::new_unique(),
+            stake_address: Pubkey::new_unique(),
+            liege_address: Pubkey::new_unique(),
+            liege_vassal_index: 0,
+            vassal_count: 0,
+            coat_of_arms: pack_string("https://example.com/arms.png").unwrap(),
+            display_name: pack_string("Duchy of Normandy").unwrap(),
+            realm_address: Pubkey::new_unique(),
+            live_vassal_count: 0,
+            seed_liege_address: Pubkey::new_unique(),
+            seed_liege_vassal_index: 0,
+            succession_law: SuccessionLaw::Heir,
+        }
+    }
+
+    /// House for tests
+    pub fn test_house_data() -> HouseData {
+        HouseData {
+            account_type: AccountType::House,
+            version: HouseData::CURRENT_VERSION,
+            governance_token_supply: 1,
+            coat_of_arms: String::new(),
+            display_name: String::from("Plantagenet"),
+            prestige: 0,
+            virtue: 0,
+            held_title_count: 0,
+            authority: Pubkey::new(&[0; 32]),
+            proposal_count: 0,
+            heir_house_address: Pubkey::new(&[0; 32]),
+            succession_period_slots: 0,
+            last_proof_of_life_slot: 0,
+        }
+    }
+
+    /// Holdings of a house for tests
+    pub fn test_holdings_data(
+        house_address: Pubkey,
+        title_addresses: Vec<Pubkey>,
+    ) -> HoldingsData {
+        HoldingsData {
+            account_type: AccountType::Holdings,
+            version: HoldingsData::CURRENT_VERSION,
+            house_address,
+            title_addresses,
+        }
+    }
+
+    /// First vassal page of a title for tests
+    pub fn test_vassal_page_data(
+        title_address: Pubkey,
+        vassal_addresses: Vec<Pubkey>,
+    ) -> VassalPageData {
+        VassalPageData {
+            account_type: AccountType::VassalPage,
+            version: VassalPageData::CURRENT_VERSION,
+            title_address,
+            page_index: 0,
+            vassal_addresses,
+        }
+    }
+
+    /// Realm for tests
+    pub fn test_realm_data(name: &str, authority: Pubkey) -> RealmData {
+        RealmData {
+            account_type: AccountType::Realm,
+            version: RealmData::CURRENT_VERSION,
+            authority,
+            root_title_address: Pubkey::default(),
+            name: name.to_string(),
+        }
+    }
+
+    /// Realm config for tests
+    pub fn test_realm_config_data(admin: Pubkey, settings: RealmSettings) -> RealmConfigData {
+        RealmConfigData {
+            account_type: AccountType::RealmConfig,
+            version: RealmConfigData::CURRENT_VERSION,
+            admin,
+            settings,
+        }
+    }
+
+    #[test]
+    fn tax_owed() {
+        let settings = RealmSettings::default();
+        let period = settings.tax_period_slots;
+        let mut title = test_title_data();
+        assert_eq!(title.tax_owed_lamports(1000, &settings), Some(0));
+        assert_eq!(
+            title.tax_owed_lamports(1000 + period, &settings),
+            Some(10_000_000 * settings.tax_rate_bps as u64 / 10_000)
+        );
+
+        title.sale_price_lamports = TitleData::NOT_FOR_SALE;
+        assert_eq!(
+            title.tax_owed_lamports(1000 + period, &settings),
+            Some(
+                1_000_000 * settings.not_for_sale_assessment_multiplier
+                    * settings.tax_rate_bps as u64
+                    / 10_000
+            )
+        );
+
+        title.lifecycle_state = TitleData::INACTIVE_STATE;
+        assert_eq!(title.tax_owed_lamports(1000 + period, &settings), Some(0));
+    }
+
+    #[test]
+    fn stake_delinquency() {
+        let title = test_title_data();
+        let required = title.required_stake_lamports;
+        assert!(!title.is_stake_delinquent(required));
+        assert!(!title.is_stake_delinquent(required + 1));
+
+        // Without a top-up, any tax collected from the stake makes the title delinquent.
+        assert!(title.is_stake_delinquent(required - 1));
+        assert!(title.is_stake_delinquent(0));
+    }
+
+    #[test]
+    fn realm_settings() {
+        let mut settings = RealmSettings::default();
+        assert_eq!(settings.validate(), Ok(()));
+        assert_eq!(settings.try_to_vec().unwrap().len(), RealmSettings::SIZE);
+        assert_eq!(settings.protocol_fee_lamports(1_000_000), Some(0));
+
+        settings.protocol_fee_bps = 250;
+        assert_eq!(settings.protocol_fee_lamports(1_000_000), Some(25_000));
+        assert_eq!(settings.protocol_fee_lamports(u64::MAX), Some(u64::MAX / 40));
+
+        settings.tax_period_slots = 0;
+        assert_eq!(settings.validate(), Err(TitleError::InvalidRealmSettings));
+        settings.tax_period_slots = 1;
+        settings.election_period_slots = 0;
+        assert_eq!(settings.validate(), Err(TitleError::InvalidRealmSettings));
+        settings.election_period_slots = 1;
+        settings.vacancy_period_slots = 0;
+        assert_eq!(settings.validate(), Err(TitleError::InvalidRealmSettings));
+
+        let config = RealmConfigData {
+            account_type: AccountType::RealmConfig,
+            version: RealmConfigData::CURRENT_VERSION,
+            admin: Pubkey::new_unique(),
+            settings: RealmSettings::default(),
+        };
+        let data = config.try_to_vec().unwrap();
+        assert_eq!(data.len(), RealmConfigData::SIZE);
+        assert_eq!(try_from_account_data::<RealmConfigData>(&data).unwrap(), config);
+    }
+
+    #[test]
+    fn convert_v1_title() {
+        let v1 = TitleDataV1 {
+            version: TitleDataV1::VERSION,
+            lifecycle_state: TitleData::ACTIVE_STATE,
+            rank: Rank::Emperor,
+            kind: Kind::Noble,
+            required_stake_lamports: 1_000_000,
+            sale_price_lamports: 10_000_000,
+            coat_of_arms: String::from("https://example.com/arms.png"),
+            display_name: String::from("Duchy of Normandy"),
+            holder_house_address: Pubkey::new_unique(),
+            stake_address: Pubkey::new_unique(),
+            liege_address: Pubkey::new_unique(),
+            liege_vassal_index: 3,
+            vassal_addresses: vec![Pubkey::new_unique()],
+        };
+        let mut data = v1.try_to_vec().unwrap();
+        data.resize(TitleDataV1::SIZE, 0);
+
+        // The version of a version 1 title sits where the account type is now, and reads
+        // as AccountType::House.
+        assert_eq!(
+            try_from_account_data::<TitleData>(&data).unwrap_err(),
+            TitleError::AccountTypeMismatch.into()
+        );
+        let decoded = try_from_slice_checked::<TitleDataV1>(&data, TitleDataV1::SIZE).unwrap();
+        assert_eq!(decoded, v1);
+
+        let title = TitleData::from(&decoded);
+        assert!(title.is_initialized());
+        assert_eq!(title.last_tax_paid_slot, 0);
+        assert_eq!(unpack_string(&title.display_name).unwrap(), v1.display_name);
+        assert_eq!(title.vassal_count, 1);
+    }
+
+    #[test]
+    fn account_type_mismatch() {
+        let title = test_title_data();
+        let mut data = vec![0; TitleData::SIZE];
+        title.pack_into_slice(&mut data);
+        assert_eq!(try_from_account_data::<TitleData>(&data).unwrap(), title);
+
+        data.resize(HouseData::SIZE, 0);
+        let err = try_from_account_data::<HouseData>(&data).unwrap_err();
+        assert_eq!(err, TitleError::AccountTypeMismatch.into());
+    }
+
+    #[test]
+    fn convert_v2_title() {
+        let v2 = TitleDataV2 {
+            version: TitleDataV2::VERSION,
+            lifecycle_state: TitleData::ACTIVE_STATE,
+            rank: Rank::Emperor,
+            kind: Kind::Noble,
+            required_stake_lamports: 1_000_000,
+            sale_price_lamports: 10_000_000,
+            last_tax_paid_slot: 1000,
+            coat_of_arms: String::from("https://example.com/arms.png"),
+            display_name: String::from("Duchy of Normandy"),
+            holder_house_address: Pubkey::new_unique(),
+            stake_address: Pubkey::new_unique(),
+            liege_address: Pubkey::new_unique(),
+            liege_vassal_index: 3,
+            vassal_addresses: vec![Pubkey::new_unique()],
+        };
+        let mut data = v2.try_to_vec().unwrap();
+        data.resize(TitleDataV2::SIZE, 0);
+
+        // The version of a version 2 title sits where the account type is now, and reads
+        // as AccountType::Title; only the size tells the layouts apart.
+        assert_eq!(data[0], AccountType::Title as u8);
+        assert_eq!(
+            try_from_account_data::<TitleData>(&data).unwrap_err(),
+            TitleError::DataTypeMismatch.into()
+        );
+        let decoded = try_from_slice_checked::<TitleDataV2>(&data, TitleDataV2::SIZE).unwrap();
+        assert_eq!(decoded, v2);
+
+        let title = TitleData::from(&decoded);
+        assert!(title.is_initialized());
+        assert_eq!(title.last_tax_paid_slot, v2.last_tax_paid_slot);
+        assert_eq!(unpack_string(&title.display_name).unwrap(), v2.display_name);
+        assert_eq!(title.vassal_count, 1);
+    }
+
+    #[test]
+    fn convert_v1_house() {
+        let v1 = HouseDataV1 {
+            version: HouseDataV1::VERSION,
+            governance_token_supply: 1,
+            coat_of_arms: String::from("https://example.com/arms.png"),
+            display_name: String::from("Plantagenet"),
+            prestige: 10,
+            virtue: -3,
+        };
+        let mut data = v1.try_to_vec().unwrap();
+        data.resize(HouseDataV1::SIZE, 0);
+
+        assert_eq!(
+            try_from_account_data::<HouseData>(&data).unwrap_err(),
+            TitleError::DataTypeMismatch.into()
+        );
+        let decoded = try_from_slice_checked::<HouseDataV1>(&data, HouseDataV1::SIZE).unwrap();
+        assert_eq!(decoded, v1);
+
+        let house = HouseData::from(decoded);
+        assert!(house.is_initialized());
+        assert_eq!(house.display_name, v1.display_name);
+        assert_eq!(house.virtue, v1.virtue);
+    }
+
+    #[test]
+    fn rank_and_kind_bytes() {
+        assert_eq!(Rank::Deus.try_to_vec().unwrap(), vec![1]);
+        assert_eq!(Rank::Baron.try_to_vec().unwrap(), vec![6]);
+        assert_eq!(Kind::Religious.try_to_vec().unwrap(), vec![2]);
+        assert_eq!(Rank::try_from_slice(&[3]).unwrap(), Rank::King);
+        let err = Rank::try_from_slice(&[0]).unwrap_err();
+        assert_eq!(
+            err.get_ref().unwrap().downcast_ref::<TitleError>(),
+            Some(&TitleError::InvalidRank)
+        );
+        assert_eq!(Kind::try_from(3).unwrap_err(), TitleError::InvalidKind.into());
+        assert!(Rank::Emperor < Rank::King);
+
+        assert_eq!("duke".parse::<Rank>().unwrap(), Rank::Duke);
+        assert_eq!("2".parse::<Kind>().unwrap(), Kind::Religious);
+        assert_eq!(Rank::Count.to_string(), "Count");
+        assert_eq!(
+            "elective".parse::<SuccessionLaw>().unwrap(),
+            SuccessionLaw::Elective
+        );
+        assert_eq!(
+            SuccessionLaw::try_from(3).unwrap_err(),
+            TitleError::InvalidSuccessionLaw.into()
+        );
+    }
+
+    #[test]
+    fn string_validation() {
+        assert_eq!(validate_display_name(b"Duchy of Normandy"), Ok("Duchy of Normandy"));
+        assert_eq!(
+            validate_display_name(&[b'a'; MAX_STRING_BYTES]).unwrap().len(),
+            MAX_STRING_BYTES
+        );
+        assert_eq!(
+            validate_display_name(&[b'a'; MAX_STRING_BYTES + 1]),
+            Err(TitleError::StringTooLong)
+        );
+        assert_eq!(validate_display_name(&[0xc3, 0x28]), Err(TitleError::InvalidUtf8));
+        assert_eq!(
+            validate_display_name(b"Duchy\0"),
+            Err(TitleError::StringHasControlCharacter)
+        );
+        assert_eq!(
+            validate_display_name("Duchy\u{85}".as_bytes()),
+            Err(TitleError::StringHasControlCharacter)
+        );
+
+        assert_eq!(validate_coat_of_arms(b""), Ok(""));
+        for uri in ["https://example.com/arms.png", "ipfs://bafybeigdyr", "ar://bNbA3TEQ"] {
+            assert_eq!(validate_coat_of_arms(uri.as_bytes()), Ok(uri));
+        }
+        for uri in ["http://example.com/arms.png", "https://", "arms.png", "ar://a b"] {
+            assert_eq!(
+                validate_coat_of_arms(uri.as_bytes()),
+                Err(TitleError::UnsupportedUriScheme)
+            );
+        }
+    }
+
+    #[test]
+    fn title_data_mut_writes_in_place() {
+        let title = test_title_data();
+        let mut data = vec![0; TitleData::SIZE];
+        title.pack_into_slice(&mut data);
+        assert_eq!(
+            data[TitleData::LIEGE_ADDRESS_OFFSET..TitleData::LIEGE_ADDRESS_OFFSET + 32],
+            title.liege_address.to_bytes()
+        );
+
+        let holder = Pubkey::new_unique();
+        let liege = Pubkey::new_unique();
+        let mut title_mut = TitleDataMut::new(&mut data).unwrap();
+        title_mut.set_holder_house_address(&holder);
+        title_mut.set_sale_price_lamports(TitleData::NOT_FOR_SALE);
+        title_mut.set_vassal_count(7);
+        title_mut.set_liege(&liege, 300);
+        title_mut.set_live_vassal_count(6);
+        title_mut.set_succession_law(SuccessionLaw::Sale);
+
+        let updated = TitleData::unpack_from_slice(&data).unwrap();
+        assert_eq!(updated.holder_house_address, holder);
+        assert_eq!(updated.sale_price_lamports, TitleData::NOT_FOR_SALE);
+        assert_eq!(updated.vassal_count, 7);
+        assert_eq!(updated.liege_address, liege);
+        assert_eq!(updated.liege_vassal_index, 300);
+        assert_eq!(updated.live_vassal_count, 6);
+        assert_eq!(updated.succession_law, SuccessionLaw::Sale);
+        assert_eq!(updated.display_name, title.display_name);
+        assert_eq!(
+            unpack_string(&updated.display_name).unwrap(),
+            "Duchy of Normandy"
+        );
+
+        assert!(TitleDataMut::new(&mut data[..TitleData::SIZE - 1]).is_err());
+    }
+
+    #[test]
+    fn decode_v1_house() {
+        let v1 = HouseDataV1 {
+            version: HouseDataV1::VERSION,
+            governance_token_supply: 1,
+            coat_of_arms: String::from("https://example.com/arms.png"),
+            display_name: String::from("Plantagenet"),
+            prestige: 10,
+            virtue: -3,
+        };
+        let mut data = v1.try_to_vec().unwrap();
+        data.resize(HouseDataV1::SIZE, 0);
+
+        let versioned = VersionedAccount::decode(&data).unwrap();
+        assert!(!versioned.is_current());
+        assert_eq!(versioned, VersionedAccount::HouseV1(v1.clone()));
+
+        let house = HouseData::from(v1);
+        assert!(house.is_initialized());
+        let mut data = house.try_to_vec().unwrap();
+        data.resize(HouseData::SIZE, 0);
+        assert_eq!(
+            VersionedAccount::decode(&data).unwrap(),
+            VersionedAccount::House(house)
+        );
+    }
+
+    #[test]
+    fn held_title_count() {
+        let mut house = test_house_data();
+        assert_eq!(house.remove_held_title(), Err(TitleError::Overflow));
+        house.add_held_title().unwrap();
+        house.add_held_title().unwrap();
+        house.remove_held_title().unwrap();
+        assert_eq!(house.held_title_count, 1);
+
+        house.held_title_count = HouseData::UNKNOWN_TITLE_COUNT - 1;
+        assert_eq!(house.add_held_title(), Err(TitleError::Overflow));
+
+        // A house migrated from version 2 keeps an unknown count.
+        house.version = 2;
+        house.held_title_count = 0;
+        let mut data = house.try_to_vec().unwrap();
+        data.resize(HouseData::V2_SIZE, 0);
+        let versioned = VersionedAccount::decode(&data).unwrap();
+        assert!(!versioned.is_current());
+        assert_eq!(versioned, VersionedAccount::HouseV2(house.clone()));
+
+        house.held_title_count = HouseData::UNKNOWN_TITLE_COUNT;
+        house.add_held_title().unwrap();
+        house.remove_held_title().unwrap();
+        assert_eq!(house.held_title_count, HouseData::UNKNOWN_TITLE_COUNT);
+    }
+
+    #[test]
+    fn decode_older_houses() {
+        // Each version adds fields after those of the version before: the authority in
+        // version 4, the proposal count in version 5 and the succession fields in version 6.
+        let cases = [
+            (3, HouseData::V3_SIZE, VersionedAccount::HouseV3 as fn(_) -> _),
+            (4, HouseData::V4_SIZE, VersionedAccount::HouseV4),
+            (5, HouseData::V5_SIZE, VersionedAccount::HouseV5),
+        ];
+        for (version, size, variant) in cases {
+            let mut house = HouseData {
+                version,
+                held_title_count: 2,
+                ..test_house_data()
+            };
+            if version >= 4 {
+                house.authority = Pubkey::new_unique();
+            }
+            if version >= 5 {
+                house.proposal_count = 3;
+            }
+            let mut data = house.try_to_vec().unwrap();
+            data.resize(size, 0);
+            let versioned = VersionedAccount::decode(&data).unwrap();
+            assert!(!versioned.is_current());
+            assert_eq!(versioned, variant(house));
+        }
+
+        let house = HouseData {
+            held_title_count: 2,
+            authority: Pubkey::new_unique(),
+            proposal_count: 3,
+            heir_house_address: Pubkey::new_unique(),
+            succession_period_slots: 1000,
+            last_proof_of_life_slot: 50,
+            ..test_house_data()
+        };
+        let mut data = house.try_to_vec().unwrap();
+        data.resize(HouseData::SIZE, 0);
+        assert_eq!(
+            VersionedAccount::decode(&data).unwrap(),
+            VersionedAccount::House(house)
+        );
+    }
+
+    #[test]
+    fn succession_due() {
+        let mut house = HouseData {
+            account_type: AccountType::House,
+            version: HouseData::CURRENT_VERSION,
+            governance_token_supply: 1,
+            coat_of_arms: String::new(),
+            display_name: String::from("Plantagenet"),
+            prestige: 0,
+            virtue: 0,
+            held_title_count: 2,
+            authority: Pubkey::new_unique(),
+            proposal_count: 0,
+            heir_house_address: Pubkey::new(&[0; 32]),
+            succession_period_slots: 1000,
+            last_proof_of_life_slot: 50,
+        };
+        assert!(!house.is_succession_due(2000));
+
+        house.heir_house_address = Pubkey::new_unique();
+        assert!(!house.is_succession_due(0));
+        assert!(!house.is_succession_due(1049));
+        assert!(house.is_succession_due(1050));
+    }
+
+    #[test]
+    fn vacated_without_heir() {
+        let settings = RealmSettings {
+            vacancy_period_slots: 1000,
+            ..RealmSettings::default()
+        };
+        let house = HouseData {
+            last_proof_of_life_slot: 50,
+            ..test_house_data()
+        };
+        assert_eq!(house.heir_house_address, Pubkey::new(&[0; 32]));
+        assert!(!house.has_vacated(0, &settings));
+        assert!(!house.has_vacated(1049, &settings));
+        assert!(house.has_vacated(1050, &settings));
+    }
+
+    #[test]
+    fn decode_v2_title() {
+        let v2 = TitleDataV2 {
+            version: TitleDataV2::VERSION,
+            lifecycle_state: TitleData::ACTIVE_STATE,
+            rank: Rank::Emperor,
+            kind: Kind::Noble,
+            required_stake_lamports: 1_000_000,
+            sale_price_lamports: 10_000_000,
+            last_tax_paid_slot: 1000,
+            coat_of_arms: String::from("https://example.com/arms.png"),
+            display_name: String::from("Duchy of Normandy"),
+            holder_house_address: Pubkey::new_unique(),
+            stake_address: Pubkey::new_unique(),
+            liege_address: Pubkey::new_unique(),
+            liege_vassal_index: 3,
+            vassal_addresses: vec![],
+        };
+        let mut data = v2.try_to_vec().unwrap();
+        data.resize(TitleDataV2::SIZE, 0);
+
+        let versioned = VersionedAccount::decode(&data).unwrap();
+        assert!(!versioned.is_current());
+        assert_eq!(versioned, VersionedAccount::TitleV2(v2.clone()));
+
+        let title = TitleData::from(&v2);
+        let mut data = vec![0; TitleData::SIZE];
+        title.pack_into_slice(&mut data);
+        assert_eq!(
+            VersionedAccount::decode(&data).unwrap(),
+            VersionedAccount::Title(title)
+        );
+    }
+
+    #[test]
+    fn decode_v3_title() {
+        let mut title = test_title_data();
+        title.version = 3;
+        title.realm_address = Pubkey::default();
+        title.seed_liege_address = Pubkey::default();
+        let mut data = vec![0; TitleData::SIZE];
+        title.pack_into_slice(&mut data);
+        data.truncate(TitleData::V3_SIZE);
+
+        let versioned = VersionedAccount::decode(&data).unwrap();
+        assert!(!versioned.is_current());
+        assert_eq!(versioned, VersionedAccount::TitleV3(title));
+    }
+
+    #[test]
+    fn decode_v4_title() {
+        let mut title = test_title_data();
+        title.version = 4;
+        title.vassal_count = 3;
+        title.seed_liege_address = Pubkey::default();
+        let mut data = vec![0; TitleData::SIZE];
+        title.pack_into_slice(&mut data);
+        data.truncate(TitleData::V4_SIZE);
+
+        let versioned = VersionedAccount::decode(&data).unwrap();
+        assert!(!versioned.is_current());
+        assert_eq!(versioned, VersionedAccount::TitleV4(title));
+    }
+
+    #[test]
+    fn decode_v5_title() {
+        let mut title = test_title_data();
+        title.version = 5;
+        title.live_vassal_count = 2;
+        let mut data = vec![0; TitleData::SIZE];
+        title.pack_into_slice(&mut data);
+        data.truncate(TitleData::V5_SIZE);
+
+        let versioned = VersionedAccount::decode(&data).unwrap();
+        assert!(!versioned.is_current());
+        assert_eq!(versioned, VersionedAccount::TitleV5(title));
+    }
+
+    #[test]
+    fn longest_realm_name_fits() {
+        let realm = RealmData {
+            account_type: AccountType::Realm,
+            version: RealmData::CURRENT_VERSION,
+            authority: Pubkey::new_unique(),
+            root_title_address: Pubkey::new_unique(),
+            name: "r".repeat(MAX_REALM_NAME_LENGTH),
+        };
+        assert_eq!(realm.try_to_vec().unwrap().len(), RealmData::SIZE);
+    }
+
+    #[test]
+    fn decode_v1_realm() {
+        let v1 = RealmDataV1 {
+            account_type: AccountType::Realm,
+            version: RealmDataV1::VERSION,
+            authority: Pubkey::new_unique(),
+            name: String::from("westeros"),
+        };
+        let mut data = v1.try_to_vec().unwrap();
+        data.resize(RealmDataV1::SIZE, 0);
+        let versioned = VersionedAccount::decode(&data).unwrap();
+        assert!(!versioned.is_current());
+        assert_eq!(versioned, VersionedAccount::RealmV1(v1.clone()));
+
+        let mut realm = RealmData::from(v1);
+        assert_eq!(realm.root_title_address, Pubkey::new(&[0; 32]));
+        assert_eq!(realm.name, "westeros");
+        realm.root_title_address = Pubkey::new_unique();
+        let mut data = realm.try_to_vec().unwrap();
+        data.resize(RealmData::SIZE, 0);
+        assert_eq!(
+            VersionedAccount::decode(&data).unwrap(),
+            VersionedAccount::Realm(realm)
+        );
+    }
+
+    #[test]
+    fn longest_petition_fits() {
+        let petition = PetitionData {
+            account_type: AccountType::Petition,
+            version: PetitionData::CURRENT_VERSION,
+            petitioner_house_address: Pubkey::new_unique(),
+            liege_address: Pubkey::new_unique(),
+            rank: Rank::Knight,
+            kind: Kind::Religious,
+            required_stake_lamports: u64::MAX,
+            coat_of_arms: format!("ipfs://{}", "c".repeat(MAX_STRING_BYTES - 7)),
+            display_name: "d".repeat(MAX_STRING_BYTES),
+        };
+        assert!(validate_coat_of_arms(petition.coat_of_arms.as_bytes()).is_ok());
+        assert!(validate_display_name(petition.display_name.as_bytes()).is_ok());
+        assert_eq!(petition.try_to_vec().unwrap().len(), PetitionData::SIZE);
+    }
+
+    #[test]
+    fn holdings_size() {
+        let mut holdings = HoldingsData {
+            account_type: AccountType::Holdings,
+            version: HoldingsData::CURRENT_VERSION,
+            house_address: Pubkey::new_unique(),
+            title_addresses: vec![],
+        };
+        assert_eq!(holdings.try_to_vec().unwrap().len(), HoldingsData::size(0));
+        holdings.title_addresses = vec![Pubkey::new_unique(), Pubkey::new_unique()];
+        assert_eq!(holdings.try_to_vec().unwrap().len(), HoldingsData::size(2));
+    }
+
+    #[test]
+    fn proposal_approval() {
+        let mut proposal = ProposalData {
+            account_type: AccountType::Proposal,
+            version: ProposalData::CURRENT_VERSION,
+            house_address: Pubkey::new_unique(),
+            proposal_index: 0,
+            approving_tokens: 0,
+            executed: false,
+            voting_deadline_slot: 1000,
+            accounts: vec![],
+            data: vec![],
+        };
+        assert_eq!(
+            proposal.try_to_vec().unwrap().len(),
+            ProposalData::size(0, 0)
+        );
+        assert!(!proposal.has_ended(999));
+        assert!(proposal.has_ended(1000));
+        proposal.accounts = vec![
+            ProposedAccount {
+                pubkey: Pubkey::new_unique(),
+                is_signer: true,
+                is_writable: false,
+            };
+            2
+        ];
+        proposal.data = vec![7; 40];
+        assert_eq!(
+            proposal.try_to_vec().unwrap().len(),
+            ProposalData::size(2, 40)
+        );
+
+        proposal.approving_tokens = 50;
+        assert!(!proposal.is_approved(100));
+        assert!(proposal.is_approved(99));
+        proposal.approving_tokens = 51;
+        assert!(proposal.is_approved(100));
+    }
+
+    #[test]
+    fn decode_v1_proposal() {
+        let v1 = ProposalDataV1 {
+            account_type: AccountType::Proposal,
+            version: ProposalDataV1::VERSION,
+            house_address: Pubkey::new_unique(),
+            proposal_index: 2,
+            approving_tokens: 51,
+            executed: false,
+            accounts: vec![],
+            data: vec![7; 3],
+        };
+        let proposal = try_from_account_data::<ProposalData>(&v1.try_to_vec().unwrap()).unwrap();
+        assert_eq!(proposal, ProposalData::from(v1));
+        assert_eq!(proposal.approving_tokens, 51);
+        assert!(proposal.has_ended(0));
+    }
+
+    #[test]
+    fn decode_v1_realm_config() {
+        let mut config = RealmConfigData {
+            account_type: AccountType::RealmConfig,
+            version: 1,
+            admin: Pubkey::new_unique(),
+            settings: RealmSettings::default(),
+        };
+        config.settings.escheat_grace_period_slots = 0;
+        config.settings.election_period_slots = 0;
+        config.settings.vacancy_period_slots = 0;
+        let mut data = config.try_to_vec().unwrap();
+        data.truncate(RealmConfigData::V1_SIZE);
+
+        let versioned = VersionedAccount::decode(&data).unwrap();
+        assert!(!versioned.is_current());
+        assert_eq!(versioned, VersionedAccount::RealmConfigV1(config));
+    }
+
+    #[test]
+    fn decode_v2_realm_config() {
+        let mut config = RealmConfigData {
+            account_type: AccountType::RealmConfig,
+            version: 2,
+            admin: Pubkey::new_unique(),
+            settings: RealmSettings::default(),
+        };
+        config.settings.election_period_slots = 0;
+        config.settings.vacancy_period_slots = 0;
+        let mut data = config.try_to_vec().unwrap();
+        data.truncate(RealmConfigData::V2_SIZE);
+
+        let versioned = VersionedAccount::decode(&data).unwrap();
+        assert!(!versioned.is_current());
+        assert_eq!(versioned, VersionedAccount::RealmConfigV2(config));
+    }
+
+    #[test]
+    fn election_winner() {
+        let settings = RealmSettings::default();
+        let (alice, bob) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let mut election = ElectionData {
+            account_type: AccountType::Election,
+            version: ElectionData::CURRENT_VERSION,
+            title_address: Pubkey::new_unique(),
+            holder_house_address: Pubkey::new_unique(),
+            opened_slot: 100,
+            ballots: vec![],
+        };
+        assert_eq!(election.try_to_vec().unwrap().len(), ElectionData::size(0));
+        assert_eq!(election.leader(), None);
+        assert!(election.has_ended(u64::MAX, &settings));
+        assert_eq!(election.winner(4, u64::MAX, &settings), None);
+
+        for candidate in [bob, alice, alice, bob] {
+            election.ballots.push(Ballot {
+                vassal_address: Pubkey::new_unique(),
+                candidate_house_address: candidate,
+            });
+        }
+        assert_eq!(election.try_to_vec().unwrap().len(), ElectionData::size(4));
+        // Tied, bob was voted for first.
+        assert_eq!(election.leader(), Some((bob, 2)));
+        assert_eq!(election.winner(4, 101, &settings), None);
+        let ended = 100 + settings.election_period_slots;
+        assert_eq!(election.winner(4, ended - 1, &settings), None);
+        assert_eq!(election.winner(4, ended, &settings), Some(bob));
+
+        // A majority of the vassals decides the election early.
+        election.ballots[0].candidate_house_address = alice;
+        assert_eq!(election.leader(), Some((alice, 3)));
+        assert_eq!(election.winner(5, 101, &settings), Some(alice));
+        assert_eq!(election.winner(6, 101, &settings), None);
+    }
+
+    #[test]
+    fn full_vassal_page_fits() {
+        let page = VassalPageData {
+            account_type: AccountType::VassalPage,
+            version: VassalPageData::CURRENT_VERSION,
+            title_address: Pubkey::new_unique(),
+            page_index: 3,
+            vassal_addresses: vec![Pubkey::new_unique(); VassalPageData::VASSALS_PER_PAGE],
+        };
+        assert_eq!(page.try_to_vec().unwrap().len(), VassalPageData::SIZE);
+
+        assert_eq!(VassalPageData::locate(0), (0, 0));
+        assert_eq!(VassalPageData::locate(31), (0, 31));
+        assert_eq!(VassalPageData::locate(32), (1, 0));
+        assert_eq!(VassalPageData::locate(u16::MAX), (2047, 31));
+    }
+
     // #[test]
     // fn deserialize_invalid_slice() {
     //     let data = [200; Data::DATA_SIZE - 1];
@@ -1,8 +1,135 @@
 //! Utilities for nobility program
 
-use crate::error::TitleError;
+use crate::{
+    error::TitleError,
+    state::{
+        AccountType, ElectionData, HoldingsData, HouseData, PetitionData, ProposalData,
+        ProposalDataV1, RealmConfigData, RealmData, TitleData, VassalPageData, VoteData,
+    },
+};
 use borsh::BorshDeserialize;
-use solana_program::{borsh::try_from_slice_unchecked, program_error::ProgramError};
+use solana_program::{
+    account_info::AccountInfo,
+    borsh::try_from_slice_unchecked,
+    entrypoint::ProgramResult,
+    msg,
+    program_error::ProgramError,
+    program_pack::Pack,
+    pubkey::Pubkey,
+};
+
+/// Data stored in an account owned by the program, led by its AccountType.
+pub trait ProgramAccount: Sized {
+    /// Account type expected in the first byte of the data
+    const ACCOUNT_TYPE: AccountType;
+    /// Serialized size of the account data, or its minimum size if it varies
+    const SIZE: usize;
+
+    /// Deserialize account data of SIZE bytes, or of any size for data which varies
+    fn unpack_account_data(data: &[u8]) -> Result<Self, ProgramError>;
+}
+
+impl ProgramAccount for HouseData {
+    const ACCOUNT_TYPE: AccountType = AccountType::House;
+    const SIZE: usize = HouseData::SIZE;
+
+    fn unpack_account_data(data: &[u8]) -> Result<Self, ProgramError> {
+        try_from_slice_checked(data, Self::SIZE)
+    }
+}
+
+impl ProgramAccount for TitleData {
+    const ACCOUNT_TYPE: AccountType = AccountType::Title;
+    const SIZE: usize = TitleData::SIZE;
+
+    fn unpack_account_data(data: &[u8]) -> Result<Self, ProgramError> {
+        if data.len() != Self::SIZE {
+            return Err(TitleError::DataTypeMismatch.into());
+        }
+        TitleData::unpack_from_slice(data)
+    }
+}
+
+impl ProgramAccount for VassalPageData {
+    const ACCOUNT_TYPE: AccountType = AccountType::VassalPage;
+    const SIZE: usize = VassalPageData::SIZE;
+
+    fn unpack_account_data(data: &[u8]) -> Result<Self, ProgramError> {
+        try_from_slice_checked(data, Self::SIZE)
+    }
+}
+
+impl ProgramAccount for RealmConfigData {
+    const ACCOUNT_TYPE: AccountType = AccountType::RealmConfig;
+    const SIZE: usize = RealmConfigData::SIZE;
+
+    fn unpack_account_data(data: &[u8]) -> Result<Self, ProgramError> {
+        try_from_slice_checked(data, Self::SIZE)
+    }
+}
+
+impl ProgramAccount for RealmData {
+    const ACCOUNT_TYPE: AccountType = AccountType::Realm;
+    const SIZE: usize = RealmData::SIZE;
+
+    fn unpack_account_data(data: &[u8]) -> Result<Self, ProgramError> {
+        try_from_slice_checked(data, Self::SIZE)
+    }
+}
+
+impl ProgramAccount for PetitionData {
+    const ACCOUNT_TYPE: AccountType = AccountType::Petition;
+    const SIZE: usize = PetitionData::SIZE;
+
+    fn unpack_account_data(data: &[u8]) -> Result<Self, ProgramError> {
+        try_from_slice_checked(data, Self::SIZE)
+    }
+}
+
+impl ProgramAccount for HoldingsData {
+    const ACCOUNT_TYPE: AccountType = AccountType::Holdings;
+    const SIZE: usize = HoldingsData::SIZE;
+
+    fn unpack_account_data(data: &[u8]) -> Result<Self, ProgramError> {
+        // Holdings are sized to their titles, so all of the data must be read.
+        HoldingsData::try_from_slice(data).map_err(|_| TitleError::DataTypeMismatch.into())
+    }
+}
+
+impl ProgramAccount for ProposalData {
+    const ACCOUNT_TYPE: AccountType = AccountType::Proposal;
+    const SIZE: usize = ProposalData::SIZE;
+
+    fn unpack_account_data(data: &[u8]) -> Result<Self, ProgramError> {
+        // Proposals are sized to their instruction, so all of the data must be read.
+        // They aren't migrated, so older layouts are read as they are.
+        if data.get(1) == Some(&ProposalDataV1::VERSION) {
+            return ProposalDataV1::try_from_slice(data)
+                .map(ProposalData::from)
+                .map_err(|_| TitleError::DataTypeMismatch.into());
+        }
+        ProposalData::try_from_slice(data).map_err(|_| TitleError::DataTypeMismatch.into())
+    }
+}
+
+impl ProgramAccount for ElectionData {
+    const ACCOUNT_TYPE: AccountType = AccountType::Election;
+    const SIZE: usize = ElectionData::SIZE;
+
+    fn unpack_account_data(data: &[u8]) -> Result<Self, ProgramError> {
+        // Elections are sized to their ballots, so all of the data must be read.
+        ElectionData::try_from_slice(data).map_err(|_| TitleError::DataTypeMismatch.into())
+    }
+}
+
+impl ProgramAccount for VoteData {
+    const ACCOUNT_TYPE: AccountType = AccountType::Vote;
+    const SIZE: usize = VoteData::SIZE;
+
+    fn unpack_account_data(data: &[u8]) -> Result<Self, ProgramError> {
+        try_from_slice_checked(data, Self::SIZE)
+    }
+}
 
 /// Deserialize and ignore if the type doesn't read all the bytes in the data
 pub fn try_from_slice_checked<T: BorshDeserialize>(
@@ -17,3 +144,63 @@ pub fn try_from_slice_checked<T: BorshDeserialize>(
 
     Ok(result)
 }
+
+/// Deserialize program account data, checking its size and its leading account type
+pub fn try_from_account_data<T: ProgramAccount>(data: &[u8]) -> Result<T, ProgramError> {
+    check_account_type(data, T::ACCOUNT_TYPE)?;
+    T::unpack_account_data(data)
+}
+
+/// Deserialize the data of an account owned by the program, checking its owner, size
+/// and account type
+pub fn load_account<T: ProgramAccount>(
+    program_id: &Pubkey,
+    account_info: &AccountInfo,
+) -> Result<T, ProgramError> {
+    check_account_owner(program_id, account_info)?;
+    try_from_account_data(&account_info.data.borrow())
+}
+
+/// Check that an account is owned by the program
+pub fn check_account_owner(
+    program_id: &Pubkey,
+    account_info: &AccountInfo,
+) -> Result<(), ProgramError> {
+    if account_info.owner != program_id {
+        msg!(
+            "Account {} is owned by {}, expected {}",
+            account_info.key,
+            account_info.owner,
+            program_id
+        );
+        return Err(TitleError::IncorrectAccountOwner.into());
+    }
+    Ok(())
+}
+
+/// Check the leading account type of program account data
+pub fn check_account_type(data: &[u8], account_type: AccountType) -> Result<(), ProgramError> {
+    if data.first() != Some(&(account_type as u8)) {
+        msg!(
+            "Expected {:?} account, got type {:?}",
+            account_type,
+            data.first()
+        );
+        return Err(TitleError::AccountTypeMismatch.into());
+    }
+    Ok(())
+}
+
+/// Close an account owned by the program: move all its lamports to the recipient and
+/// zero its data, so that it can't be read as its former type within the transaction.
+/// The runtime deletes the account once the transaction completes.
+pub fn close_account(account_info: &AccountInfo, recipient_info: &AccountInfo) -> ProgramResult {
+    let recipient_lamports = recipient_info
+        .lamports()
+        .checked_add(account_info.lamports())
+        .ok_or(TitleError::Overflow)?;
+    **recipient_info.try_borrow_mut_lamports()? = recipient_lamports;
+    **account_info.try_borrow_mut_lamports()? = 0;
+    account_info.data.borrow_mut().fill(0);
+    Ok(())
+}